@@ -50,10 +50,11 @@ pub(crate) struct Scope {
     pub inner: Vec<Bind>,
     pub(crate) free: HashMap<String, Span>, // Things referred to in this scope, or inner scopes, that we don't define
     pub(crate) bound: HashMap<String, (Assigner, Span)>, // Things bound in this scope, doesn't include inner scope bindings
+    pub(crate) span: Span,                               // The code this scope covers
 }
 
 impl Scope {
-    fn new(inner: Vec<Bind>) -> Self {
+    fn new(inner: Vec<Bind>, span: Span) -> Self {
         let mut bound: HashMap<String, _> = HashMap::new();
         let mut free: HashMap<String, _> = HashMap::new();
         for x in &inner {
@@ -74,7 +75,12 @@ impl Scope {
             free.remove(x);
         }
 
-        Self { inner, free, bound }
+        Self {
+            inner,
+            free,
+            bound,
+            span,
+        }
     }
 }
 
@@ -85,6 +91,7 @@ fn opt_expr(x: Option<&AstExpr>, res: &mut Vec<Bind>) {
 }
 
 fn comprehension(
+    span: Span,
     for_: &ForClause,
     clauses: &[Clause],
     res: &mut Vec<Bind>,
@@ -103,7 +110,7 @@ fn comprehension(
         }
     }
     end(&mut inner);
-    res.push(Bind::Scope(Scope::new(inner)))
+    res.push(Bind::Scope(Scope::new(inner, span)))
 }
 
 fn expr(x: &AstExpr, res: &mut Vec<Bind>) {
@@ -113,16 +120,18 @@ fn expr(x: &AstExpr, res: &mut Vec<Bind>) {
             let mut inner = Vec::new();
             parameters(args, res, &mut inner);
             expr(body, &mut inner);
-            res.push(Bind::Scope(Scope::new(inner)));
+            res.push(Bind::Scope(Scope::new(inner, x.span)));
         }
 
-        Expr::ListComprehension(x, for_, clauses) => {
-            comprehension(for_, clauses, res, |res| expr(x, res))
+        Expr::ListComprehension(e, for_, clauses) => {
+            comprehension(x.span, for_, clauses, res, |res| expr(e, res))
+        }
+        Expr::DictComprehension(e, for_, clauses) => {
+            comprehension(x.span, for_, clauses, res, |res| {
+                expr(&e.0, res);
+                expr(&e.1, res)
+            })
         }
-        Expr::DictComprehension(x, for_, clauses) => comprehension(for_, clauses, res, |res| {
-            expr(&x.0, res);
-            expr(&x.1, res)
-        }),
 
         // Uninteresting - just recurse
         _ => x.visit_expr(|x| expr(x, res)),
@@ -183,7 +192,7 @@ fn stmt(x: &AstStmt, res: &mut Vec<Bind>) {
             parameters(args, res, &mut inner);
             res.push(Bind::Set(Assigner::Assign, name.clone()));
             stmt(body, &mut inner);
-            res.push(Bind::Scope(Scope::new(inner)));
+            res.push(Bind::Scope(Scope::new(inner, x.span)));
         }
         Stmt::Assign(lhs, rhs) => {
             expr(rhs, res);
@@ -217,5 +226,5 @@ fn stmt(x: &AstStmt, res: &mut Vec<Bind>) {
 pub(crate) fn scope(module: &AstModule) -> Scope {
     let mut res = Vec::new();
     stmt(&module.statement, &mut res);
-    Scope::new(res)
+    Scope::new(res, module.statement.span)
 }
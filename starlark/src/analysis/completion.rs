@@ -0,0 +1,245 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use gazebo::prelude::*;
+
+use crate::{
+    analysis::{
+        bind::{self, Assigner, Bind, Scope},
+        definition::identifier_at,
+        hover::{binder, Binder},
+    },
+    codemap::Pos,
+    environment::Globals,
+    syntax::{
+        ast::{AstExpr, AstLiteral, AstStmt, Expr},
+        uniplate::Visit,
+        AstModule,
+    },
+    values::{dict::Dict, Heap, Value},
+};
+
+/// What sort of name a [`Completion`] is.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// Imported with `load`.
+    Load,
+    /// A parameter of an enclosing function or lambda.
+    Parameter,
+    /// Assigned to, or defined with `def`.
+    Variable,
+    /// Provided by the globals.
+    Global,
+    /// An attribute of the value before a `.`.
+    Member,
+}
+
+/// A name that could be written at the cursor, as returned by [`AstModule::complete_at`].
+#[derive(Debug, Clone)]
+pub struct Completion {
+    /// The name to insert.
+    pub name: String,
+    /// Where the name comes from.
+    pub kind: CompletionKind,
+}
+
+impl AstModule {
+    /// The names that could be written at a 0-based line and column, for editor completion.
+    ///
+    /// Usually these are the names visible at that point, innermost scope first, followed by
+    /// the `globals`. If the cursor is on the attribute of an `x.attr` expression, they are
+    /// instead the attributes of `x`, as given by `dir()`, which are only known if `x` is
+    /// a literal, a variable assigned a literal, or a global.
+    pub fn complete_at(&self, globals: &Globals, line: usize, column: usize) -> Vec<Completion> {
+        let pos = match self.codemap.find_pos(line, column) {
+            Some(pos) => pos,
+            None => return Vec::new(),
+        };
+        let scope = bind::scope(self);
+        if let Some(receiver) = dot_at(&self.statement, pos) {
+            let heap = Heap::new();
+            return match self.receiver(&scope, receiver, globals, &heap) {
+                None => Vec::new(),
+                Some(x) => x.dir_attr().into_map(|name| Completion {
+                    name,
+                    kind: CompletionKind::Member,
+                }),
+            };
+        }
+
+        let mut stack = Vec::new();
+        enclosing(&scope, pos, &mut stack);
+        let mut seen = HashSet::new();
+        let mut res = Vec::new();
+        for scope in stack.iter().rev() {
+            let mut names: Vec<_> = scope.bound.iter().collect();
+            names.sort_by_key(|x| x.0);
+            for (name, (assigner, _)) in names {
+                if seen.insert(name.as_str()) {
+                    let kind = match assigner {
+                        Assigner::Load => CompletionKind::Load,
+                        Assigner::Argument => CompletionKind::Parameter,
+                        Assigner::Assign => CompletionKind::Variable,
+                    };
+                    res.push(Completion {
+                        name: name.clone(),
+                        kind,
+                    });
+                }
+            }
+        }
+        let mut names = globals.names();
+        names.sort();
+        for name in names {
+            if !seen.contains(name.as_str()) {
+                res.push(Completion {
+                    name,
+                    kind: CompletionKind::Global,
+                });
+            }
+        }
+        res
+    }
+
+    /// A value with the same attributes as `x`, if we can tell without evaluating anything.
+    fn receiver<'v>(
+        &self,
+        scope: &Scope,
+        x: &AstExpr,
+        globals: &'v Globals,
+        heap: &'v Heap,
+    ) -> Option<Value<'v>> {
+        match &**x {
+            Expr::Identifier(..) => match identifier_at(scope, x.span.begin())? {
+                (name, None) => globals.get(name),
+                (_, Some((Assigner::Assign, span))) => match binder(&self.statement, span)? {
+                    Binder::Assign(rhs) => literal(rhs, heap),
+                    Binder::Def(_) => None,
+                },
+                _ => None,
+            },
+            _ => literal(x, heap),
+        }
+    }
+}
+
+/// An empty value of the same type as a literal expression.
+fn literal<'v>(x: &AstExpr, heap: &'v Heap) -> Option<Value<'v>> {
+    match &**x {
+        Expr::Literal(AstLiteral::Int(_)) => Some(Value::new_int(0)),
+        Expr::Literal(AstLiteral::String(_)) => Some(heap.alloc_str("").to_value()),
+        Expr::List(_) | Expr::ListComprehension(..) => Some(heap.alloc_list(&[])),
+        Expr::Dict(_) | Expr::DictComprehension(..) => Some(heap.alloc(Dict::default())),
+        Expr::Tuple(_) => Some(heap.alloc_tuple(&[])),
+        _ => None,
+    }
+}
+
+/// The scopes enclosing `pos`, outermost first.
+fn enclosing<'a>(scope: &'a Scope, pos: Pos, stack: &mut Vec<&'a Scope>) {
+    stack.push(scope);
+    for x in &scope.inner {
+        if let Bind::Scope(inner) = x {
+            if inner.span.contains(pos) {
+                return enclosing(inner, pos, stack);
+            }
+        }
+    }
+}
+
+/// Find the receiver of an `x.attr` expression where `pos` is on `attr`.
+fn dot_at(x: &AstStmt, pos: Pos) -> Option<&AstExpr> {
+    let mut res = None;
+    x.visit_children(|x| {
+        if res.is_none() {
+            res = match x {
+                Visit::Stmt(x) => dot_at(x, pos),
+                Visit::Expr(x) => dot_at_expr(x, pos),
+            }
+        }
+    });
+    res
+}
+
+fn dot_at_expr(x: &AstExpr, pos: Pos) -> Option<&AstExpr> {
+    if let Expr::Dot(receiver, attr) = &**x {
+        if attr.span.contains(pos) {
+            return Some(&**receiver);
+        }
+    }
+    let mut res = None;
+    x.visit_expr(|x| {
+        if res.is_none() {
+            res = dot_at_expr(x, pos);
+        }
+    });
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn complete_at(x: &str, line: usize, column: usize) -> Vec<(String, CompletionKind)> {
+        let module = AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap();
+        module
+            .complete_at(&Globals::standard(), line, column)
+            .into_map(|x| (x.name, x.kind))
+    }
+
+    fn has(res: &[(String, CompletionKind)], name: &str, kind: CompletionKind) -> bool {
+        res.iter().any(|x| x.0 == name && x.1 == kind)
+    }
+
+    #[test]
+    fn test_complete_top_level() {
+        let program = r#"load("m", "loaded")
+x = 1
+def f(a):
+    b = a
+    return b
+"#;
+        let res = complete_at(program, 1, 0);
+        assert!(has(&res, "loaded", CompletionKind::Load));
+        assert!(has(&res, "x", CompletionKind::Variable));
+        assert!(has(&res, "f", CompletionKind::Variable));
+        assert!(has(&res, "len", CompletionKind::Global));
+        assert!(!res.iter().any(|x| x.0 == "a" || x.0 == "b"));
+
+        // Locals come first, then the top level, then globals
+        let res = complete_at(program, 4, 11);
+        assert_eq!(res[0], ("a".to_owned(), CompletionKind::Parameter));
+        assert_eq!(res[1], ("b".to_owned(), CompletionKind::Variable));
+        assert!(has(&res, "x", CompletionKind::Variable));
+        assert!(has(&res, "len", CompletionKind::Global));
+    }
+
+    #[test]
+    fn test_complete_member() {
+        // Tuples have no attributes, and we shouldn't offer ordinary names instead
+        assert!(complete_at("(1, 2).x", 0, 7).is_empty());
+        let res = complete_at("s = 'abc'\n[1].a\ns.up", 1, 4);
+        assert!(has(&res, "append", CompletionKind::Member));
+        assert!(res.iter().all(|x| x.1 == CompletionKind::Member));
+        let res = complete_at("s = 'abc'\n[1].a\ns.up", 2, 2);
+        assert!(has(&res, "upper", CompletionKind::Member));
+        assert!(!has(&res, "append", CompletionKind::Member));
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    analysis::bind::{self, Assigner, Bind, Scope},
+    codemap::{FileSpan, Pos, Span},
+    syntax::AstModule,
+};
+
+impl AstModule {
+    /// Find where the identifier at a 0-based line and column is bound, for go-to-definition.
+    /// The cursor may be on a use of the identifier, or on an assignment to it.
+    ///
+    /// Names are resolved in the innermost function, lambda or comprehension binding them,
+    /// falling back to the top level, and go to the first parameter, assignment or `load`
+    /// binding them there. Returns [`None`] if there is no identifier at the position,
+    /// or it isn't bound in this module, e.g. a builtin.
+    pub fn resolve_at(&self, line: usize, column: usize) -> Option<FileSpan> {
+        let pos = self.codemap.find_pos(line, column)?;
+        let scope = bind::scope(self);
+        let (_, binding) = identifier_at(&scope, pos)?;
+        let (_, span) = binding?;
+        Some(self.file_span(span))
+    }
+}
+
+/// Find the identifier at `pos`, and its binding if it is bound in the module.
+pub(crate) fn identifier_at(scope: &Scope, pos: Pos) -> Option<(&str, Option<(Assigner, Span)>)> {
+    let mut stack = Vec::new();
+    let name = find(scope, pos, &mut stack)?;
+    let binding = stack.iter().rev().find_map(|x| x.bound.get(name).copied());
+    Some((name, binding))
+}
+
+/// Find the name of the identifier at `pos`, leaving the scopes enclosing it in `stack`.
+fn find<'a>(scope: &'a Scope, pos: Pos, stack: &mut Vec<&'a Scope>) -> Option<&'a str> {
+    stack.push(scope);
+    for x in &scope.inner {
+        match x {
+            Bind::Get(x) if x.span.contains(pos) => return Some(&x.node),
+            Bind::Set(_, x) if x.span.contains(pos) => return Some(&x.0),
+            Bind::Scope(inner) => {
+                if let Some(name) = find(inner, pos, stack) {
+                    return Some(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    stack.pop();
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn resolve_at(x: &str, line: usize, column: usize) -> Option<(usize, usize)> {
+        let module = AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap();
+        let span = module.resolve_at(line, column)?.resolve_span();
+        Some((span.begin_line, span.begin_column))
+    }
+
+    #[test]
+    fn test_resolve_at() {
+        let program = r#"x = 1
+def f(a, b):
+    c = [a for a in b]
+    return a + x + len(c)
+"#;
+        // Parameter
+        assert_eq!(resolve_at(program, 3, 11), Some((1, 6)));
+        assert_eq!(resolve_at(program, 3, 12), Some((1, 6)));
+        // Global
+        assert_eq!(resolve_at(program, 3, 15), Some((0, 0)));
+        // Local
+        assert_eq!(resolve_at(program, 3, 23), Some((2, 4)));
+        // Comprehension variable shadowing the parameter
+        assert_eq!(resolve_at(program, 2, 9), Some((2, 15)));
+        // Comprehension source, evaluated outside the comprehension
+        assert_eq!(resolve_at(program, 2, 20), Some((1, 9)));
+        // The binding itself
+        assert_eq!(resolve_at(program, 1, 4), Some((1, 4)));
+        // Builtins, and things which aren't identifiers
+        assert_eq!(resolve_at(program, 3, 19), None);
+        assert_eq!(resolve_at(program, 0, 2), None);
+        assert_eq!(resolve_at(program, 10, 0), None);
+    }
+}
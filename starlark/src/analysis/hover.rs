@@ -0,0 +1,234 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use gazebo::prelude::*;
+use itertools::Itertools;
+
+use crate::{
+    analysis::{
+        bind::{self, Assigner},
+        definition::identifier_at,
+    },
+    codemap::Span,
+    environment::Globals,
+    syntax::{
+        ast::{Assign, AstExpr, AstLiteral, AstStmt, Expr, Stmt},
+        AstModule,
+    },
+    values::{
+        docs::{DocItem, DocString, DocStringKind, Function, Param, Type},
+        function::FUNCTION_TYPE,
+    },
+};
+
+/// What sort of name a [`HoverInfo`] describes.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum HoverKind {
+    /// Imported with `load`.
+    Load,
+    /// A parameter of a function or lambda.
+    Parameter,
+    /// Assigned to, including by a `for` loop or comprehension.
+    Variable,
+    /// Defined with `def` in this module.
+    Function,
+    /// Not bound in the module, but provided by the globals.
+    Global,
+}
+
+/// Information about the identifier under the cursor, as returned by [`AstModule::hover_at`].
+#[derive(Debug, Clone)]
+pub struct HoverInfo {
+    /// The name of the identifier.
+    pub name: String,
+    /// How the identifier is bound.
+    pub kind: HoverKind,
+    /// The type of the value, as returned by `type()`, if it can be determined.
+    pub typ: Option<String>,
+    /// For functions, the signature, e.g. `f(x, y = 1)`.
+    pub signature: Option<String>,
+    /// For functions, the docstring.
+    pub docs: Option<DocString>,
+}
+
+impl AstModule {
+    /// Describe the identifier at a 0-based line and column, for hover tooltips.
+    ///
+    /// Names bound in the module are described from the AST, so types are only known for
+    /// simple assignments such as `x = [1]`. Other names are looked up in `globals`, which
+    /// for builtins gives the signature and documentation. Returns [`None`] if there is
+    /// no identifier at the position, or it isn't bound anywhere.
+    pub fn hover_at(&self, globals: &Globals, line: usize, column: usize) -> Option<HoverInfo> {
+        let pos = self.codemap.find_pos(line, column)?;
+        let scope = bind::scope(self);
+        let (name, binding) = identifier_at(&scope, pos)?;
+        let mut info = HoverInfo {
+            name: name.to_owned(),
+            kind: HoverKind::Variable,
+            typ: None,
+            signature: None,
+            docs: None,
+        };
+        match binding {
+            Some((Assigner::Load, _)) => info.kind = HoverKind::Load,
+            Some((Assigner::Argument, _)) => info.kind = HoverKind::Parameter,
+            Some((Assigner::Assign, span)) => match binder(&self.statement, span) {
+                Some(Binder::Def(x)) => {
+                    if let Stmt::Def(_, params, ret, body, _) = &**x {
+                        info.kind = HoverKind::Function;
+                        info.typ = Some(FUNCTION_TYPE.to_owned());
+                        let mut sig =
+                            format!("{}({})", name, params.iter().map(|p| &p.node).join(", "));
+                        if let Some(ret) = ret {
+                            sig.push_str(&format!(" -> {}", ret.node));
+                        }
+                        info.signature = Some(sig);
+                        info.docs = DocString::extract_raw_starlark_docstring(body)
+                            .and_then(|x| DocString::from_docstring(DocStringKind::Starlark, &x));
+                    }
+                }
+                Some(Binder::Assign(rhs)) => info.typ = literal_type(rhs).map(str::to_owned),
+                None => {}
+            },
+            None => {
+                let value = globals.get(name)?;
+                info.kind = HoverKind::Global;
+                info.typ = Some(value.get_type().to_owned());
+                if let Some(DocItem::Function(f)) = value.documentation() {
+                    info.signature = Some(signature(name, &f));
+                    info.docs = f.docs;
+                }
+            }
+        }
+        Some(info)
+    }
+}
+
+/// The statement which binds a name at a given span.
+pub(crate) enum Binder<'a> {
+    Def(&'a AstStmt),
+    Assign(&'a AstExpr),
+}
+
+pub(crate) fn binder(x: &AstStmt, span: Span) -> Option<Binder> {
+    match &**x {
+        Stmt::Def(name, ..) if name.span == span => return Some(Binder::Def(x)),
+        Stmt::Assign(lhs, rhs) => {
+            if let Assign::Identifier(name) = &**lhs {
+                if name.span == span {
+                    return Some(Binder::Assign(rhs));
+                }
+            }
+        }
+        _ => {}
+    }
+    let mut res = None;
+    x.visit_stmt(|x| {
+        if res.is_none() {
+            res = binder(x, span);
+        }
+    });
+    res
+}
+
+/// The type of an expression, if it is obvious from the syntax.
+fn literal_type(x: &AstExpr) -> Option<&'static str> {
+    Some(match &**x {
+        Expr::Literal(AstLiteral::Int(_)) => "int",
+        Expr::Literal(AstLiteral::Float(_)) => "float",
+        Expr::Literal(AstLiteral::String(_)) => "string",
+        Expr::List(_) | Expr::ListComprehension(..) => "list",
+        Expr::Dict(_) | Expr::DictComprehension(..) => "dict",
+        Expr::Tuple(_) => "tuple",
+        Expr::Lambda(..) => FUNCTION_TYPE,
+        _ => return None,
+    })
+}
+
+/// Render the signature of a documented function, e.g. `f(x: int, *args) -> string`.
+fn signature(name: &str, f: &Function) -> String {
+    let typ = |t: &Option<_>| match t {
+        Some(Type { raw_type }) => format!(": {}", raw_type),
+        None => String::new(),
+    };
+    let params = f.params.iter().map(|p| match p {
+        Param::Arg {
+            name,
+            typ: t,
+            default_value,
+            ..
+        } => match default_value {
+            Some(d) => format!("{}{} = {}", name, typ(t), d),
+            None => format!("{}{}", name, typ(t)),
+        },
+        Param::NoArgs => "*".to_owned(),
+        Param::Args { name, typ: t, .. } | Param::Kwargs { name, typ: t, .. } => {
+            format!("{}{}", name, typ(t))
+        }
+    });
+    let mut res = format!("{}({})", name, params.join(", "));
+    if let Some(ret) = &f.ret.typ {
+        res.push_str(&format!(" -> {}", ret.raw_type));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn hover_at(x: &str, line: usize, column: usize) -> Option<HoverInfo> {
+        let module = AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap();
+        module.hover_at(&Globals::standard(), line, column)
+    }
+
+    #[test]
+    fn test_hover_builtin() {
+        let info = hover_at("x = len([1])", 0, 5).unwrap();
+        assert_eq!(info.name, "len");
+        assert_eq!(info.kind, HoverKind::Global);
+        assert_eq!(info.typ.as_deref(), Some("function"));
+        assert!(info.signature.unwrap().starts_with("len(a"));
+        assert!(info.docs.unwrap().summary.contains("length of a sequence"));
+    }
+
+    #[test]
+    fn test_hover_module() {
+        let program = r#"
+def f(a, b = 1) -> "string":
+    """Do something.
+
+    At length.
+    """
+    c = [a]
+    return c
+f(1)
+"#;
+        let info = hover_at(program, 8, 0).unwrap();
+        assert_eq!(info.kind, HoverKind::Function);
+        assert_eq!(info.signature.as_deref(), Some("f(a, b = 1) -> \"string\""));
+        assert_eq!(info.docs.unwrap().summary, "Do something.");
+        let info = hover_at(program, 6, 9).unwrap();
+        assert_eq!((info.name.as_str(), info.kind), ("a", HoverKind::Parameter));
+        let info = hover_at(program, 7, 11).unwrap();
+        assert_eq!(info.kind, HoverKind::Variable);
+        assert_eq!(info.typ.as_deref(), Some("list"));
+        assert!(hover_at(program, 7, 4).is_none());
+        assert!(hover_at("undefined", 0, 3).is_none());
+    }
+}
@@ -15,17 +15,24 @@
  * limitations under the License.
  */
 
+pub use completion::{Completion, CompletionKind};
+pub use hover::{HoverInfo, HoverKind};
+pub use symbols::{Symbol, SymbolKind};
 pub use types::Lint;
 
 use crate::{analysis::types::LintT, syntax::AstModule};
 
 mod bind;
+mod completion;
+mod definition;
 mod dubious;
 mod exported;
 mod flow;
+mod hover;
 mod incompatible;
 mod names;
 mod performance;
+mod symbols;
 mod types;
 
 impl AstModule {
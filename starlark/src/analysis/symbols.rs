@@ -0,0 +1,157 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use gazebo::prelude::*;
+
+use crate::{
+    codemap::FileSpan,
+    syntax::{
+        ast::{AstAssignIdent, AstStmt, Stmt},
+        AstModule,
+    },
+};
+
+/// What sort of binding a [`Symbol`] is.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// Defined with `def`.
+    Function,
+    /// Assigned to, including by a `for` loop.
+    Variable,
+    /// Imported with `load`.
+    Load,
+}
+
+/// A symbol defined by a module, as returned by [`AstModule::symbols`].
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    /// The name of the symbol.
+    pub name: String,
+    /// How the symbol was bound.
+    pub kind: SymbolKind,
+    /// The location of the name where it is first bound.
+    pub span: FileSpan,
+    /// For a [`Function`](SymbolKind::Function), the functions defined inside it.
+    pub children: Vec<Symbol>,
+}
+
+impl AstModule {
+    /// The symbols bound at the top level of this module, in the order they are first bound,
+    /// suitable for an outline view. Unlike [`exported_symbols`](AstModule::exported_symbols)
+    /// this includes `load`ed and private symbols, along with nested functions.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut seen = HashSet::new();
+        let mut res = Vec::new();
+        top_level(self, &self.statement, &mut seen, &mut res);
+        res
+    }
+
+    fn symbol(&self, kind: SymbolKind, name: &AstAssignIdent, children: Vec<Symbol>) -> Symbol {
+        Symbol {
+            name: name.0.clone(),
+            kind,
+            span: self.file_span(name.span),
+            children,
+        }
+    }
+}
+
+fn top_level<'a>(
+    module: &AstModule,
+    x: &'a AstStmt,
+    seen: &mut HashSet<&'a str>,
+    res: &mut Vec<Symbol>,
+) {
+    let mut add = |kind, name: &'a AstAssignIdent, children| {
+        if seen.insert(&name.0) {
+            res.push(module.symbol(kind, name, children));
+        }
+    };
+    match &**x {
+        Stmt::Load(load) => {
+            for (local, _) in &load.args {
+                add(SymbolKind::Load, local, Vec::new());
+            }
+        }
+        Stmt::Def(name, _, _, body, _) => {
+            let mut children = Vec::new();
+            nested(module, body, &mut children);
+            add(SymbolKind::Function, name, children);
+        }
+        Stmt::Assign(dest, _) | Stmt::AssignModify(dest, _, _) => {
+            dest.visit_lvalue(|name| add(SymbolKind::Variable, name, Vec::new()));
+        }
+        Stmt::For(dest, box (_, body)) => {
+            dest.visit_lvalue(|name| add(SymbolKind::Variable, name, Vec::new()));
+            top_level(module, body, seen, res);
+        }
+        _ => x.visit_stmt(|x| top_level(module, x, seen, res)),
+    }
+}
+
+fn nested(module: &AstModule, x: &AstStmt, res: &mut Vec<Symbol>) {
+    match &**x {
+        Stmt::Def(name, _, _, body, _) => {
+            let mut children = Vec::new();
+            nested(module, body, &mut children);
+            res.push(module.symbol(SymbolKind::Function, name, children));
+        }
+        _ => x.visit_stmt(|x| nested(module, x, res)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gazebo::prelude::*;
+
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn module(x: &str) -> AstModule {
+        AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap()
+    }
+
+    #[test]
+    fn test_symbols() {
+        let modu = module(
+            r#"
+load("test", "a")
+def b(x):
+    def inner(): pass
+    return x
+C = 1
+C += 1
+for i in []:
+    pass
+"#,
+        );
+        let res = modu.symbols();
+        assert_eq!(
+            res.map(|x| format!("{} {} {:?}", x.span, x.name, x.kind)),
+            &[
+                "X:2:14-17 a Load",
+                "X:3:5-6 b Function",
+                "X:6:1-2 C Variable",
+                "X:8:5-6 i Variable"
+            ]
+        );
+        assert_eq!(res[1].children.map(|x| x.name.as_str()), &["inner"]);
+        assert!(res[0].children.is_empty());
+    }
+}
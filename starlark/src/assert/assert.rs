@@ -279,7 +279,8 @@ impl<'a> Assert<'a> {
         };
 
         match gc {
-            GcStrategy::Never => eval.disable_gc(),
+            // Not `disable_gc`, which the code under test could undo with `enable_gc`
+            GcStrategy::Never => eval.gc_forbidden = true,
             GcStrategy::Auto => {}
             GcStrategy::Always => eval.before_stmt(&gc_always),
         }
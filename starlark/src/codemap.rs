@@ -43,6 +43,11 @@ impl Pos {
     pub fn new(x: u32) -> Self {
         Self(x)
     }
+
+    /// The byte offset of the position.
+    pub fn get(self) -> u32 {
+        self.0
+    }
 }
 
 impl Add<u32> for Pos {
@@ -69,6 +74,11 @@ impl Span {
         Span { begin, end }
     }
 
+    /// The position in the codemap representing the first byte of the span.
+    pub fn begin(self) -> Pos {
+        self.begin
+    }
+
     /// The position after the last byte of the span.
     pub fn end(self) -> Pos {
         self.end
@@ -79,6 +89,20 @@ impl Span {
         self.end.0 - self.begin.0
     }
 
+    /// Is `pos` within the span, counting the position just after the end as within.
+    pub fn contains(self, pos: Pos) -> bool {
+        self.begin <= pos && pos <= self.end
+    }
+
+    /// Move the span `by` bytes, e.g. after text was inserted before it.
+    pub(crate) fn shift(self, by: i64) -> Span {
+        let shift = |x: Pos| Pos((x.0 as i64 + by) as u32);
+        Span {
+            begin: shift(self.begin),
+            end: shift(self.end),
+        }
+    }
+
     /// Create a span that encloses both `self` and `other`.
     pub fn merge(self, other: Span) -> Span {
         Span {
@@ -235,6 +259,23 @@ impl CodeMap {
         LineCol { line, column }
     }
 
+    /// Gets the position of a 0-based line and column, the inverse of `find_line_col`.
+    /// The column may be one past the end of the line.
+    ///
+    /// Returns [`None`] if the line or column is out of range.
+    pub(crate) fn find_pos(&self, line: usize, column: usize) -> Option<Pos> {
+        if line >= self.num_lines() {
+            return None;
+        }
+        let text = self.source_line(line);
+        let byte_col = match text.char_indices().nth(column) {
+            Some((i, _)) => i,
+            None if text.chars().count() == column => text.len(),
+            None => return None,
+        };
+        Some(self.line_span(line).begin + byte_col as u32)
+    }
+
     /// Gets the full source text of the file
     pub fn source(&self) -> &str {
         &self.0.source
@@ -558,4 +599,15 @@ mod tests {
         let span = ResolvedSpan::from_span(begin, end);
         assert_eq!(span.to_string(), "1:1-3:33");
     }
+
+    #[test]
+    fn test_find_pos() {
+        let codemap = CodeMap::new("test.rs".to_owned(), "ab\nc\u{e9}d\n".to_owned());
+        for (line, column) in [(0, 0), (0, 2), (1, 1), (1, 2), (1, 3)] {
+            let pos = codemap.find_pos(line, column).unwrap();
+            assert_eq!(codemap.find_line_col(pos), LineCol { line, column });
+        }
+        assert_eq!(codemap.find_pos(0, 3), None);
+        assert_eq!(codemap.find_pos(3, 0), None);
+    }
 }
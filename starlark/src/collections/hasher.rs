@@ -24,6 +24,9 @@ use crate::collections::StarlarkHashValue;
 /// A hasher used by Starlark implementation.
 ///
 /// Starlark relies on stable hashing, and this is the hasher.
+/// It uses FNV-1a with no random seed, and writes integers as little-endian bytes,
+/// so a value has the same hash across runs, platforms and Rust versions,
+/// and hashes may be persisted.
 #[derive(Default)]
 pub struct StarlarkHasher(FnvHasher);
 
@@ -39,7 +42,7 @@ impl StarlarkHasher {
     pub(crate) fn finish_small(self) -> StarlarkHashValue {
         // NOTE: Here we throw away half the key material we are given,
         // taking only the lower 32 bits.
-        // Not a problem because `DefaultHasher` produces well-swizzled bits.
+        // Not a problem because FNV-1a mixes every byte into the lower bits.
         StarlarkHashValue::new_unchecked(self.finish() as u32)
     }
 }
@@ -54,4 +57,85 @@ impl Hasher for StarlarkHasher {
     fn write(&mut self, bytes: &[u8]) {
         self.0.write(bytes)
     }
+
+    // The default implementations use native-endian bytes, which would make hashes
+    // differ between platforms.
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        // Always 8 bytes, so 32-bit and 64-bit platforms agree.
+        self.write_u64(i as u64)
+    }
+
+    #[inline]
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    #[inline]
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    #[inline]
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    #[inline]
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    #[inline]
+    fn write_isize(&mut self, i: isize) {
+        self.write_usize(i as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use crate::{
+        collections::StarlarkHasher,
+        values::{Heap, Value},
+    };
+
+    // Hashes may be persisted, so these must never change.
+    #[test]
+    fn test_stable_hash() {
+        let mut hasher = StarlarkHasher::new();
+        hasher.write(b"hello");
+        assert_eq!(hasher.finish(), 0xa430d84680aabd0b);
+        let mut hasher = StarlarkHasher::new();
+        hasher.write_u64(1);
+        assert_eq!(hasher.finish(), 0x89cd31291d2aefa4);
+
+        let heap = Heap::new();
+        assert_eq!(Value::new_int(1).get_hash().unwrap().get(), 0x34c2cb2c);
+        assert_eq!(heap.alloc("hello").get_hash().unwrap().get(), 0xa220c59c);
+        let tuple = heap.alloc_tuple(&[Value::new_int(1), heap.alloc("a")]);
+        assert_eq!(tuple.get_hash().unwrap().get(), 0xa948a6ab);
+    }
 }
@@ -15,16 +15,111 @@
  * limitations under the License.
  */
 
+//! Pausing evaluation at chosen lines, built on [`before_stmt`](Evaluator::before_stmt).
+
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+};
+
 use gazebo::dupe::Dupe;
+use thiserror::Error;
 
 use crate::{
-    codemap::{CodeMap, FileSpan},
+    codemap::{CodeMap, FileSpan, FileSpanRef},
+    eval::Evaluator,
     syntax::{
         ast::{AstStmt, Stmt},
         AstModule,
     },
 };
 
+#[derive(Debug, Error)]
+enum BreakpointError {
+    #[error("Evaluation aborted at breakpoint")]
+    Abort,
+}
+
+/// What to do after pausing, as returned by the handler passed to [`Breakpoints::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakpointAction {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause again before the next statement, whether or not it has a breakpoint.
+    Step,
+    /// Stop evaluation with an error.
+    Abort,
+}
+
+/// A set of `(file, line)` locations, with lines starting at 1, where evaluation pauses
+/// by calling a handler. The handler gets the span of the statement about to run and the
+/// [`Evaluator`], so can look at [`local_variables`](Evaluator::local_variables) before
+/// deciding what to do next. Installed with [`Evaluator::breakpoints`].
+pub struct Breakpoints<'a> {
+    locations: HashMap<String, HashSet<usize>>,
+    handler: Box<dyn Fn(FileSpanRef, &mut Evaluator) -> BreakpointAction + 'a>,
+    /// The last action was [`BreakpointAction::Step`].
+    stepping: Cell<bool>,
+}
+
+impl<'a> Breakpoints<'a> {
+    /// No breakpoints, calling `handler` whenever one is hit.
+    pub fn new(handler: impl Fn(FileSpanRef, &mut Evaluator) -> BreakpointAction + 'a) -> Self {
+        Self {
+            locations: HashMap::new(),
+            handler: box handler,
+            stepping: Cell::new(false),
+        }
+    }
+
+    /// Pause before any statement starting on `line` of `file`.
+    pub fn add(&mut self, file: &str, line: usize) {
+        self.locations
+            .entry(file.to_owned())
+            .or_default()
+            .insert(line);
+    }
+
+    /// Remove a breakpoint, returning `false` if it was not set.
+    pub fn remove(&mut self, file: &str, line: usize) -> bool {
+        self.locations
+            .get_mut(file)
+            .map_or(false, |lines| lines.remove(&line))
+    }
+
+    fn is_breakpoint(&self, span: FileSpanRef) -> bool {
+        match self.locations.get(span.file.filename()) {
+            Some(lines) => lines.contains(&(span.resolve_span().begin_line + 1)),
+            None => false,
+        }
+    }
+
+    /// Called before every statement, pausing if we are at a breakpoint or stepping.
+    pub(crate) fn before_stmt(
+        &self,
+        span: FileSpanRef,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<()> {
+        if !self.stepping.get() && !self.is_breakpoint(span) {
+            return Ok(());
+        }
+        let action = (self.handler)(span, eval);
+        self.stepping.set(action == BreakpointAction::Step);
+        match action {
+            BreakpointAction::Abort => Err(BreakpointError::Abort.into()),
+            BreakpointAction::Continue | BreakpointAction::Step => Ok(()),
+        }
+    }
+}
+
+impl<'v, 'a> Evaluator<'v, 'a> {
+    /// Pause at the given [`Breakpoints`]. As with [`before_stmt`](Evaluator::before_stmt),
+    /// this must be called before the module is evaluated.
+    pub fn breakpoints(&mut self, breakpoints: &'a Breakpoints<'a>) {
+        self.before_stmt.breakpoints = Some(breakpoints);
+    }
+}
+
 fn go(x: &AstStmt, codemap: &CodeMap, res: &mut Vec<FileSpan>) {
     match &**x {
         Stmt::Statements(_) => {} // These are not interesting statements that come up
@@ -46,3 +141,79 @@ impl AstModule {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{
+        environment::{Globals, Module},
+        syntax::Dialect,
+    };
+
+    const PROGRAM: &str = "\
+x = 1
+def f(y):
+    z = x + y
+    return z
+a = f(2)
+b = f(3)
+";
+
+    fn run(breakpoints: &Breakpoints, module: &Module) -> anyhow::Result<()> {
+        let mut eval = Evaluator::new(module);
+        eval.breakpoints(breakpoints);
+        let ast = AstModule::parse("a.star", PROGRAM.to_owned(), &Dialect::Extended)?;
+        eval.eval_module(ast, &Globals::new())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_breakpoint_continue() {
+        let hits = RefCell::new(Vec::new());
+        let mut breakpoints = Breakpoints::new(|span, eval| {
+            let y = eval.local_variables().get("y").unwrap().to_string();
+            hits.borrow_mut()
+                .push((span.resolve_span().begin_line + 1, y));
+            BreakpointAction::Continue
+        });
+        breakpoints.add("a.star", 3);
+        breakpoints.add("other.star", 1);
+        let module = Module::new();
+        run(&breakpoints, &module).unwrap();
+        assert_eq!(
+            vec![(3, "2".to_owned()), (3, "3".to_owned())],
+            hits.into_inner()
+        );
+        assert_eq!(Some(4), module.get("b").unwrap().unpack_int());
+    }
+
+    #[test]
+    fn test_breakpoint_step_and_abort() {
+        let hits = RefCell::new(Vec::new());
+        let mut breakpoints = Breakpoints::new(|span, _| {
+            let mut hits = hits.borrow_mut();
+            hits.push(span.resolve_span().begin_line + 1);
+            if hits.len() < 3 {
+                BreakpointAction::Step
+            } else {
+                BreakpointAction::Continue
+            }
+        });
+        breakpoints.add("a.star", 5);
+        run(&breakpoints, &Module::new()).unwrap();
+        assert_eq!(vec![5, 3, 4], hits.into_inner());
+
+        let mut breakpoints = Breakpoints::new(|_, _| BreakpointAction::Abort);
+        breakpoints.add("a.star", 4);
+        assert!(breakpoints.remove("a.star", 4));
+        assert!(!breakpoints.remove("a.star", 4));
+        breakpoints.add("a.star", 6);
+        let module = Module::new();
+        let err = run(&breakpoints, &module).unwrap_err().to_string();
+        assert!(err.contains("aborted at breakpoint"), "{}", err);
+        assert!(module.get("a").is_some());
+        assert!(module.get("b").is_none());
+    }
+}
@@ -23,12 +23,17 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     /// Evaluate statements in the existing context. This function is designed for debugging,
     /// not production use.
     ///
+    /// The [`Module`](crate::environment::Module) is modified in place, so repeated calls
+    /// (e.g. one per line of a REPL) see the bindings made by earlier calls. Returns the
+    /// value of the final statement if it is an expression, otherwise `None`.
+    ///
     /// There are lots of health warnings on this code. Might not work with frozen modules, unassigned variables,
     /// nested definitions etc. It would be a bad idea to rely on the results of continued execution
     /// after evaluating stuff randomly.
     pub fn eval_statements(&mut self, statements: AstModule) -> anyhow::Result<Value<'v>> {
         // We are doing a lot of funky stuff here. It's amazing anything works, so let's not push our luck with GC.
-        self.disable_gc();
+        // Set directly, so `enable_gc` can't turn it back on.
+        self.gc_forbidden = true;
 
         // Everything must be evaluated with the current heap (or we'll lose memory), which means
         // the current module (eval.module_env).
@@ -102,7 +107,11 @@ mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::{self as starlark, assert, environment::GlobalsBuilder, syntax::Dialect};
+    use crate::{
+        self as starlark, assert,
+        environment::{GlobalsBuilder, Module},
+        syntax::Dialect,
+    };
 
     #[starlark_module]
     fn debugger(builder: &mut GlobalsBuilder) {
@@ -170,4 +179,19 @@ def bar(y):
         );
         a.pass("load('test', 'bar'); assert_eq(bar(4), 4 + 7 + 2)");
     }
+
+    #[test]
+    fn test_eval_statements_incremental() {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let mut eval_line = |code: &str| {
+            let ast = AstModule::parse("repl", code.to_owned(), &Dialect::Extended).unwrap();
+            eval.eval_statements(ast).unwrap().to_repr()
+        };
+        assert_eq!(eval_line("x = 1"), "None");
+        assert_eq!(eval_line("x + 1 == 2"), "True");
+        assert_eq!(eval_line("y = [x]"), "None");
+        assert_eq!(eval_line("y.append(x + 1); y"), "[1, 2]");
+        assert_eq!(module.get("y").unwrap().to_repr(), "[1, 2]");
+    }
 }
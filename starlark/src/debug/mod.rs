@@ -15,6 +15,6 @@
  * limitations under the License.
  */
 
-mod breakpoint;
+pub(crate) mod breakpoint;
 mod evaluate;
 mod inspect;
@@ -15,7 +15,7 @@
  * limitations under the License.
  */
 
-use std::{collections::HashMap, mem, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
 use derive_more::Display;
 use gazebo::prelude::*;
@@ -74,8 +74,8 @@ pub struct GlobalsBuilder {
     heap: FrozenHeap,
     // Normal top-level variables, e.g. True/hash
     variables: SymbolMap<FrozenValue>,
-    // Set to Some when we are in a struct builder, otherwise None
-    struct_fields: Option<SmallMap<FrozenStringValue, FrozenValue>>,
+    // The fields of the structs being built, innermost last, empty at the top level
+    struct_fields: Vec<SmallMap<FrozenStringValue, FrozenValue>>,
     // The raw docstring for this module
     docstring: Option<String>,
 }
@@ -209,7 +209,7 @@ impl GlobalsBuilder {
         Self {
             heap: FrozenHeap::new(),
             variables: SymbolMap::new(),
-            struct_fields: None,
+            struct_fields: Vec::new(),
             docstring: None,
         }
     }
@@ -238,18 +238,25 @@ impl GlobalsBuilder {
 
     /// Add a nested struct to the builder. If `f` adds the definition `foo`,
     /// it will end up on a struct `name`, accessible as `name.foo`.
-    /// This function cannot be called recursively from inside `f`.
+    /// This is how namespaces of functions such as `json.encode` are defined,
+    /// usually by passing a function annotated with `#[starlark_module]` as `f`.
+    /// Calling this function from inside `f` nests the structs, e.g. `name.inner.foo`.
     pub fn struct_(&mut self, name: &str, f: impl Fn(&mut GlobalsBuilder)) {
-        assert!(
-            self.struct_fields.is_none(),
-            "Can't recursively nest GlobalsBuilder::struct_"
-        );
-        self.struct_fields = Some(SmallMap::new());
-        f(self);
-        let fields = mem::take(&mut self.struct_fields).unwrap();
+        let fields = self.namespace_fields(f);
         self.set(name, FrozenStruct::new(fields));
     }
 
+    /// The fields [`struct_`](GlobalsBuilder::struct_) would put on the struct, for
+    /// namespaces which are stored in some other value rather than a plain struct.
+    pub(crate) fn namespace_fields(
+        &mut self,
+        f: impl Fn(&mut GlobalsBuilder),
+    ) -> SmallMap<FrozenStringValue, FrozenValue> {
+        self.struct_fields.push(SmallMap::new());
+        f(self);
+        self.struct_fields.pop().unwrap()
+    }
+
     /// A fluent API for modifying [`GlobalsBuilder`] and returning the result.
     pub fn with(mut self, f: impl FnOnce(&mut Self)) -> Self {
         f(&mut self);
@@ -274,7 +281,7 @@ impl GlobalsBuilder {
     /// Set a value in the [`GlobalsBuilder`].
     pub fn set<'v, V: AllocFrozenValue>(&'v mut self, name: &str, value: V) {
         let value = value.alloc_frozen_value(&self.heap);
-        match &mut self.struct_fields {
+        match self.struct_fields.last_mut() {
             None => self.variables.insert(name, value),
             Some(fields) => {
                 let name = self.heap.alloc_str(name);
@@ -602,4 +609,26 @@ assert_eq(magic.my_type, "magic")
 assert_eq(magic.my_value, 42)"#,
         );
     }
+
+    #[test]
+    fn test_namespace() {
+        #[starlark_module]
+        fn json_members(builder: &mut GlobalsBuilder) {
+            fn encode(ref x: Value) -> anyhow::Result<String> {
+                x.to_json()
+            }
+        }
+
+        let mut a = Assert::new();
+        a.globals_add(|x| {
+            x.struct_("json", |x| {
+                json_members(x);
+                x.struct_("inner", |x| x.set("answer", 42));
+            })
+        });
+        a.eq("json.encode([1,2])", "'[1,2]'");
+        a.eq("json.inner.answer", "42");
+        a.eq("type(json)", "'struct'");
+        a.fail("encode([1,2])", "encode");
+    }
 }
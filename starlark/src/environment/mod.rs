@@ -52,4 +52,6 @@ pub(crate) enum EnvironmentError {
     ModuleSymbolIsNotExported(String),
     #[error("No imports are available, you tried `{0}` (no call to `Evaluator.set_loader`)")]
     NoImportsAvailable(String),
+    #[error("Module variable `{0}` has type `{2}`, but expected `{1}`")]
+    VariableTypeMismatch(String, String, String),
 }
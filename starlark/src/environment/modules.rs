@@ -24,6 +24,7 @@ use std::{
     cell::{Cell, RefCell},
     collections::HashMap,
     mem,
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -39,11 +40,13 @@ use crate::{
         EnvironmentError,
     },
     errors::did_you_mean::did_you_mean,
+    eval::runtime::heap_profile::HeapProfile,
     syntax::ast::Visibility,
     values::{
         docs,
         docs::{DocItem, DocString, DocStringKind},
-        Freezer, FrozenHeap, FrozenHeapRef, FrozenValue, Heap, OwnedFrozenValue, Value,
+        AllocValue, Freezer, FrozenHeap, FrozenHeapRef, FrozenValue, Heap, OwnedFrozenValue,
+        UnpackValue, Value,
     },
 };
 
@@ -148,10 +151,20 @@ impl FrozenModule {
     }
 
     /// Obtain the [`FrozenHeapRef`] which owns the storage of all values defined in this module.
+    /// Its [`allocated_bytes`](FrozenHeapRef::allocated_bytes) give the memory footprint of the
+    /// module, and [`allocated_summary`](FrozenHeapRef::allocated_summary) breaks that down by type.
     pub fn frozen_heap(&self) -> &FrozenHeapRef {
         &self.heap
     }
 
+    /// Write a CSV profile of the values retained on the frozen heap of this module,
+    /// giving the count and size for each type. Unlike
+    /// [`ProfileMode::HeapSummary`](crate::eval::ProfileMode::HeapSummary), values which were
+    /// no longer referenced when the module was frozen are not included.
+    pub fn write_retained_heap_profile(&self, filename: impl AsRef<Path>) -> anyhow::Result<()> {
+        HeapProfile::write_heap_summary(filename.as_ref(), self.heap.allocated_summary())
+    }
+
     /// Print out some approximation of the module definitions.
     pub fn describe(&self) -> String {
         self.module.0.describe()
@@ -290,6 +303,24 @@ impl Module {
             })
     }
 
+    /// Get the value of the exported variable `name`, unpacked to type `T`.
+    /// Returns [`None`] if the variable isn't defined in the module or it is private,
+    /// and an error if it is defined but has the wrong type.
+    pub fn get_typed<'v, T: UnpackValue<'v>>(&'v self, name: &str) -> anyhow::Result<Option<T>> {
+        match self.get(name) {
+            None => Ok(None),
+            Some(v) => match T::unpack_value(v) {
+                Some(x) => Ok(Some(x)),
+                None => Err(EnvironmentError::VariableTypeMismatch(
+                    name.to_owned(),
+                    T::expected(),
+                    v.get_type().to_owned(),
+                )
+                .into()),
+            },
+        }
+    }
+
     /// Freeze the environment, all its value will become immutable afterwards.
     pub fn freeze(self) -> anyhow::Result<FrozenModule> {
         let Module {
@@ -330,6 +361,11 @@ impl Module {
     /// Set the value of a variable in the environment.
     /// Modifying these variables while executing is ongoing can have
     /// surprising effects.
+    ///
+    /// Values set before [`eval_module`](crate::eval::Evaluator::eval_module) are visible to the
+    /// script as ordinary module variables, and take precedence over any
+    /// [`Globals`](crate::environment::Globals) with the same name.
+    /// Names starting with an underscore are private, so are not visible to `load()`.
     pub fn set<'v>(&'v self, name: &str, value: Value<'v>) {
         let slot = self.names.add_name(name);
         let slots = self.slots();
@@ -337,6 +373,11 @@ impl Module {
         slots.set_slot(slot, value);
     }
 
+    /// Like [`set`](Module::set), but allocates the value on this module's [`Heap`] first.
+    pub fn set_typed<'v, T: AllocValue<'v>>(&'v self, name: &str, value: T) {
+        self.set(name, self.heap().alloc(value))
+    }
+
     /// Symbols starting with underscore are considered private.
     pub(crate) fn default_visibility(symbol: &str) -> Visibility {
         match symbol.starts_with('_') {
@@ -37,6 +37,7 @@ use crate::{
         },
         runtime::call_stack::FrozenFileSpan,
     },
+    syntax::ensure_sufficient_stack,
     values::{FrozenValue, ValueLike},
 };
 
@@ -132,7 +133,7 @@ impl IrSpanned<ExprCompiled> {
 
     pub(crate) fn write_bc(&self, bc: &mut BcWriter) {
         let span = self.span;
-        match self.node {
+        ensure_sufficient_stack(|| match self.node {
             ExprCompiled::Value(v) => {
                 bc.write_const(span, v);
             }
@@ -267,6 +268,7 @@ impl IrSpanned<ExprCompiled> {
                 r.write_bc(bc);
                 match op {
                     ExprBinOp::In => bc.write_instr::<InstrIn>(span, ()),
+                    ExprBinOp::Is => bc.write_instr::<InstrIs>(span, ()),
                     ExprBinOp::Sub => bc.write_instr::<InstrSub>(span, ()),
                     ExprBinOp::Add => bc.write_instr::<InstrAdd>(span, ()),
                     ExprBinOp::Multiply => bc.write_instr::<InstrMultiply>(span, ()),
@@ -290,7 +292,7 @@ impl IrSpanned<ExprCompiled> {
             }
             ExprCompiled::Call(ref call) => call.write_bc(bc),
             ExprCompiled::Def(ref def) => def.write_bc(span, bc),
-        }
+        })
     }
 
     pub(crate) fn write_bc_for_effect(&self, bc: &mut BcWriter) {
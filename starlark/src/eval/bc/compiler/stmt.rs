@@ -33,6 +33,7 @@ use crate::{
         },
         runtime::call_stack::FrozenFileSpan,
     },
+    syntax::ensure_sufficient_stack,
     values::{FrozenHeap, FrozenValue},
 };
 
@@ -53,7 +54,7 @@ impl IrSpanned<StmtCompiled> {
                 _ => bc.write_instr::<InstrBeforeStmt>(self.span, self.span),
             }
         }
-        self.write_bc_inner(compiler, bc);
+        ensure_sufficient_stack(|| self.write_bc_inner(compiler, bc));
         assert_eq!(
             bc.stack_size(),
             0,
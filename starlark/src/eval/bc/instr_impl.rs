@@ -17,10 +17,12 @@
 
 //! Instruction implementations.
 
-use std::{cmp::Ordering, marker, mem::MaybeUninit, ptr};
+use std::{borrow::Cow, cmp::Ordering, marker, mem::MaybeUninit, ptr};
 
 use gazebo::coerce::coerce;
 
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
 use crate::{
     collections::{symbol_map::Symbol, Hashed, SmallMap},
     environment::slots::ModuleSlotId,
@@ -51,9 +53,11 @@ use crate::{
     values::{
         dict::Dict,
         function::NativeFunction,
+        int::int_floor_div,
         known_methods::KnownMethod,
         list::List,
         string::interpolation::{format_one, percent_s_one},
+        type_value::TypeValue,
         typed::FrozenValueTyped,
         typing::TypeCompiled,
         FrozenRef, FrozenStringValue, FrozenValue, Heap, StarlarkValue, StringValue, Value,
@@ -641,6 +645,8 @@ impl InstrUnOpImpl for InstrPlusImpl {
 }
 
 impl InstrUnOpImpl for InstrMinusImpl {
+    const INT_OP: Option<fn(i64) -> i64> = Some(|a| -a);
+
     #[inline(always)]
     fn eval<'v>(v: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         v.minus(heap)
@@ -649,17 +655,26 @@ impl InstrUnOpImpl for InstrMinusImpl {
 
 impl InstrUnOpImpl for InstrBitNotImpl {
     #[inline(always)]
-    fn eval<'v>(v: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        Ok(Value::new_int(!v.to_int()?))
+    fn eval<'v>(v: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        v.bit_not(heap)
     }
 }
 
 pub(crate) trait InstrBinOpImpl: 'static {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>>;
+
+    /// The operator on two small ints as `i64`, which can't overflow, for operators whose
+    /// result may not fit in 32 bits. Used instead of `eval` in code compiled with
+    /// `enable_bigint`, so the result is promoted rather than an overflow error.
+    /// Gives `None` if the operator fails, in which case `eval` reports the error.
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = None;
 }
 
 pub(crate) trait InstrUnOpImpl: 'static {
     fn eval<'v>(v: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>>;
+
+    /// Like [`InstrBinOpImpl::INT_OP`].
+    const INT_OP: Option<fn(i64) -> i64> = None;
 }
 
 pub(crate) struct InstrBinOpWrapper<I: InstrBinOpImpl>(marker::PhantomData<I>);
@@ -680,6 +695,16 @@ impl<I: InstrBinOpImpl> InstrNoFlowImpl for InstrBinOpWrapper<I> {
         (): &(),
         [v0, v1]: [Value<'v>; 2],
     ) -> anyhow::Result<Value<'v>> {
+        #[cfg(feature = "bigint")]
+        if let Some(op) = I::INT_OP {
+            if eval.bigint_enabled() {
+                if let (Some(a), Some(b)) = (v0.unpack_int(), v1.unpack_int()) {
+                    if let Some(x) = op(a as i64, b as i64) {
+                        return Ok(StarlarkBigInt::alloc_i64(x, eval.heap()));
+                    }
+                }
+            }
+        }
         I::eval(v0, v1, eval.heap())
     }
 }
@@ -697,6 +722,14 @@ impl<I: InstrUnOpImpl> InstrNoFlowImpl for InstrUnOpWrapper<I> {
         (): &(),
         v: Value<'v>,
     ) -> anyhow::Result<Value<'v>> {
+        #[cfg(feature = "bigint")]
+        if let Some(op) = I::INT_OP {
+            if eval.bigint_enabled() {
+                if let Some(a) = v.unpack_int() {
+                    return Ok(StarlarkBigInt::alloc_i64(op(a as i64), eval.heap()));
+                }
+            }
+        }
         I::eval(v, eval.heap())
     }
 }
@@ -716,6 +749,7 @@ pub(crate) struct InstrLeftShiftImpl;
 pub(crate) struct InstrRightShiftImpl;
 pub(crate) struct InstrInImpl;
 pub(crate) struct InstrNotInImpl;
+pub(crate) struct InstrIsImpl;
 
 pub(crate) type InstrAdd = InstrBinOp<InstrAddImpl>;
 pub(crate) type InstrAddAssign = InstrBinOp<InstrAddAssignImpl>;
@@ -732,8 +766,11 @@ pub(crate) type InstrLeftShift = InstrBinOp<InstrLeftShiftImpl>;
 pub(crate) type InstrRightShift = InstrBinOp<InstrRightShiftImpl>;
 pub(crate) type InstrIn = InstrBinOp<InstrInImpl>;
 pub(crate) type InstrNotIn = InstrBinOp<InstrNotInImpl>;
+pub(crate) type InstrIs = InstrBinOp<InstrIsImpl>;
 
 impl InstrBinOpImpl for InstrAddImpl {
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = Some(|a, b| Some(a + b));
+
     #[inline(always)]
     fn eval<'v>(l: Value<'v>, r: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         // Addition of string is super common and pretty cheap, so have a special case for it.
@@ -754,6 +791,8 @@ impl InstrBinOpImpl for InstrAddImpl {
 }
 
 impl InstrBinOpImpl for InstrAddAssignImpl {
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = Some(|a, b| Some(a + b));
+
     #[inline(always)]
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         add_assign(v0, v1, heap)
@@ -761,6 +800,8 @@ impl InstrBinOpImpl for InstrAddAssignImpl {
 }
 
 impl InstrBinOpImpl for InstrSubImpl {
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = Some(|a, b| Some(a - b));
+
     #[inline(always)]
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         v0.sub(v1, heap)
@@ -768,6 +809,8 @@ impl InstrBinOpImpl for InstrSubImpl {
 }
 
 impl InstrBinOpImpl for InstrMultiplyImpl {
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = Some(|a, b| Some(a * b));
+
     #[inline(always)]
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         v0.mul(v1, heap)
@@ -782,6 +825,8 @@ impl InstrBinOpImpl for InstrPercentImpl {
 }
 
 impl InstrBinOpImpl for InstrFloorDivideImpl {
+    const INT_OP: Option<fn(i64, i64) -> Option<i64>> = Some(int_floor_div);
+
     #[inline(always)]
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         v0.floor_div(v1, heap)
@@ -797,8 +842,8 @@ impl InstrBinOpImpl for InstrDivideImpl {
 
 impl InstrBinOpImpl for InstrBitAndImpl {
     #[inline(always)]
-    fn eval<'v>(v0: Value<'v>, v1: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        v0.bit_and(v1)
+    fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        v0.bit_and(v1, heap)
     }
 }
 
@@ -818,8 +863,8 @@ impl InstrBinOpImpl for InstrBitOrAssignImpl {
 
 impl InstrBinOpImpl for InstrBitXorImpl {
     #[inline(always)]
-    fn eval<'v>(v0: Value<'v>, v1: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        v0.bit_xor(v1)
+    fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        v0.bit_xor(v1, heap)
     }
 }
 
@@ -851,6 +896,13 @@ impl InstrBinOpImpl for InstrNotInImpl {
     }
 }
 
+impl InstrBinOpImpl for InstrIsImpl {
+    #[inline(always)]
+    fn eval<'v>(v0: Value<'v>, v1: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(Value::new_bool(v0.ptr_eq(v1)))
+    }
+}
+
 pub(crate) struct InstrPercentSOneImpl;
 pub(crate) type InstrPercentSOne = InstrNoFlow<InstrPercentSOneImpl>;
 pub(crate) struct InstrFormatOneImpl;
@@ -947,7 +999,7 @@ pub(crate) type InstrType = InstrUnOp<InstrTypeImpl>;
 impl InstrUnOpImpl for InstrTypeImpl {
     #[inline(always)]
     fn eval<'v>(v: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        Ok(v.get_type_value().unpack().to_value())
+        Ok(TypeValue::frozen(v.get_type_value()).to_value())
     }
 }
 
@@ -1426,10 +1478,25 @@ impl InstrNoFlowImpl for InstrDefImpl {
 
         let mut pop_index = 0;
 
+        // Parameters before a '/' are positional-only, which `ParametersSpec`
+        // expects to be marked by prefixing the name with '$'
+        let positional_only = def_data
+            .params
+            .iter()
+            .position(|x| matches!(x.node, ParameterCompiled::PositionalOnly))
+            .unwrap_or(0);
+        fn spec_name(name: &str, positional_only: bool) -> Cow<str> {
+            if positional_only {
+                Cow::Owned(format!("${}", name))
+            } else {
+                Cow::Borrowed(name)
+            }
+        }
+
         // count here rather than enumerate because '*' doesn't get a real
         // index in the parameter mapping, and it messes up the indexes
         let mut i = 0;
-        for x in &def_data.params {
+        for (index, x) in def_data.params.iter().enumerate() {
             if let Some(t) = x.ty() {
                 assert!(*t == pop_index);
                 let v = pop[pop_index as usize];
@@ -1443,7 +1510,9 @@ impl InstrNoFlowImpl for InstrDefImpl {
                 ));
             }
             match &x.node {
-                ParameterCompiled::Normal(n, _) => parameters.required(&n.name),
+                ParameterCompiled::Normal(n, _) => {
+                    parameters.required(&spec_name(&n.name, index < positional_only))
+                }
                 ParameterCompiled::WithDefaultValue(n, ty, v) => {
                     assert!(*v == pop_index);
                     let value = pop[pop_index as usize];
@@ -1459,16 +1528,20 @@ impl InstrNoFlowImpl for InstrDefImpl {
                         )
                         .map_err(|e| e.0)?;
                     }
-                    parameters.defaulted(&n.name, value);
+                    parameters.defaulted(&spec_name(&n.name, index < positional_only), value);
                 }
                 ParameterCompiled::NoArgs => parameters.no_args(),
+                ParameterCompiled::PositionalOnly => {}
                 ParameterCompiled::Args(_, _) => parameters.args(),
                 ParameterCompiled::KwArgs(_, _) => parameters.kwargs(),
             };
             if let Captured::Yes = x.captured() {
                 parameter_captures.push(i);
             }
-            if !matches!(x.node, ParameterCompiled::NoArgs) {
+            if !matches!(
+                x.node,
+                ParameterCompiled::NoArgs | ParameterCompiled::PositionalOnly
+            ) {
                 i += 1;
             }
         }
@@ -1743,8 +1816,7 @@ impl InstrNoFlowImpl for InstrBeforeStmtImpl {
         span: &Self::Arg,
         (): (),
     ) -> anyhow::Result<()> {
-        before_stmt(*span, eval);
-        Ok(())
+        before_stmt(*span, eval)
     }
 }
 
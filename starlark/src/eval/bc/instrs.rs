@@ -243,6 +243,46 @@ impl BcInstrs {
         self.fmt_impl(&mut w, true).unwrap();
         w
     }
+
+    /// Instructions as opcode name followed by arguments, excluding the trailing `End`.
+    pub(crate) fn disassemble(&self) -> Vec<String> {
+        self.iter()
+            .filter(|(ptr, _)| ptr.get_opcode() != BcOpcode::End)
+            .map(|(ptr, ip)| {
+                let opcode = ptr.get_opcode();
+                let mut w = format!("{:?}", opcode);
+                opcode.fmt_append_arg(ptr, ip, &mut w).unwrap();
+                w
+            })
+            .collect()
+    }
+
+    /// Instructions with addresses, arguments and source spans, one per line.
+    pub(crate) fn disassemble_pretty(&self) -> String {
+        let slow_args = self
+            .iter()
+            .find(|(ptr, _)| ptr.get_opcode() == BcOpcode::End)
+            .map(|(ptr, _)| &ptr.get_instr::<InstrEnd>().arg.1);
+        let mut w = String::new();
+        for (ptr, ip) in self.iter() {
+            let opcode = ptr.get_opcode();
+            if opcode == BcOpcode::End {
+                break;
+            }
+            write!(w, "{:>4}: {:?}", ip.0, opcode).unwrap();
+            opcode.fmt_append_arg(ptr, ip, &mut w).unwrap();
+            let span = slow_args
+                .and_then(|slow_args| slow_args.iter().find(|(addr, _)| *addr == ip))
+                .map(|(_, slow_arg)| slow_arg.span);
+            match span {
+                // Instructions synthesized by the compiler have no location.
+                Some(span) if !span.file.filename().is_empty() => writeln!(w, "  # {}", span),
+                _ => writeln!(w),
+            }
+            .unwrap();
+        }
+        w
+    }
 }
 
 impl Display for BcInstrs {
@@ -66,6 +66,7 @@ pub(crate) enum BcOpcode {
     GreaterOrEqual,
     In,
     NotIn,
+    Is,
     Add,
     AddAssign,
     Sub,
@@ -27,7 +27,7 @@ use crate::{
             instr::BcInstr,
             instr_impl::{
                 InstrBr, InstrConst, InstrConst2, InstrConst3, InstrConst4, InstrContinue,
-                InstrForLoop, InstrIfBr, InstrIfNotBr, InstrLoadLocal, InstrLoadLocal2,
+                InstrDup, InstrForLoop, InstrIfBr, InstrIfNotBr, InstrLoadLocal, InstrLoadLocal2,
                 InstrLoadLocal3, InstrLoadLocal4, InstrLoadLocalAndConst, InstrLoadLocalCaptured,
                 InstrProfileBc, InstrStoreLocal, InstrStoreLocalCaptured,
             },
@@ -57,6 +57,9 @@ pub(crate) struct BcWriter<'f> {
     /// Max observed stack size.
     max_stack_size: u32,
 
+    // Store of the local is queued before loads of locals and consts,
+    // so it could be merged with immediately following load of the same local.
+    queued_store_local: Option<IrSpanned<LocalSlotId>>,
     // Consts are queued after locals
     queued_locals: Vec<IrSpanned<LocalSlotId>>,
     queued_consts: Vec<IrSpanned<FrozenValue>>,
@@ -75,6 +78,7 @@ impl<'f> BcWriter<'f> {
             stack_size: 0,
             local_count,
             max_stack_size: 0,
+            queued_store_local: None,
             queued_consts: Vec::new(),
             queued_locals: Vec::new(),
             heap,
@@ -90,12 +94,14 @@ impl<'f> BcWriter<'f> {
             stack_size,
             local_count,
             max_stack_size,
+            queued_store_local,
             queued_consts,
             queued_locals,
             heap,
         } = self;
         let _ = has_before_instr;
         let _ = heap;
+        assert!(queued_store_local.is_none());
         assert!(queued_locals.is_empty());
         assert!(queued_consts.is_empty());
         assert_eq!(stack_size, 0);
@@ -108,14 +114,21 @@ impl<'f> BcWriter<'f> {
 
     /// Current offset.
     fn ip(&self) -> BcAddr {
+        assert!(self.queued_store_local.is_none());
         assert!(self.queued_consts.is_empty());
         self.instrs.ip()
     }
 
     /// Flush queued consts and locals instructions.
     fn flush_instrs(&mut self) {
+        // Take the queues first, so writing instructions does not see them as pending.
         let mut queued_locals = mem::take(&mut self.queued_locals);
         let mut queued_consts = mem::take(&mut self.queued_consts);
+
+        if let Some(store) = self.queued_store_local.take() {
+            self.do_write_generic::<InstrStoreLocal>(store.span, store.node);
+        }
+
         let mut locals_slice = queued_locals.as_slice();
         let mut consts_slice = queued_consts.as_slice();
 
@@ -295,6 +308,21 @@ impl<'f> BcWriter<'f> {
     pub(crate) fn write_load_local(&mut self, span: FrozenFileSpan, slot: LocalSlotId) {
         assert!(slot.0 < self.local_count);
 
+        if let Some(store) = &self.queued_store_local {
+            if store.node == slot && self.queued_locals.is_empty() && self.queued_consts.is_empty()
+            {
+                // `StoreLocal x; LoadLocal x` is replaced with `Dup; StoreLocal x`:
+                // the value is still on the stack, so there's no need to load it back
+                // (and check the local is assigned).
+                let store = self.queued_store_local.take().unwrap();
+                self.stack_add(2);
+                self.do_write_generic::<InstrDup>(store.span, ());
+                self.do_write_generic::<InstrStoreLocal>(store.span, store.node);
+                self.stack_sub(1);
+                return;
+            }
+        }
+
         // Consts must be queued after locals, so if any consts are queued, flush them.
         if !self.queued_consts.is_empty() {
             self.flush_instrs();
@@ -311,7 +339,10 @@ impl<'f> BcWriter<'f> {
 
     pub(crate) fn write_store_local(&mut self, span: FrozenFileSpan, slot: LocalSlotId) {
         assert!(slot.0 < self.local_count);
-        self.write_instr_ret_arg::<InstrStoreLocal>(span, slot);
+        // Do not write it yet, queue it, so it could be merged with following load.
+        self.flush_instrs();
+        self.stack_sub(1);
+        self.queued_store_local = Some(IrSpanned { node: slot, span });
     }
 
     pub(crate) fn write_store_local_captured(&mut self, span: FrozenFileSpan, slot: LocalSlotId) {
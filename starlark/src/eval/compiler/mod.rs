@@ -31,6 +31,7 @@ use crate::{
         runtime::call_stack::FrozenFileSpan,
         Evaluator, ScopeNames,
     },
+    syntax::DialectFlags,
     values::{FrozenRef, FrozenValue},
 };
 
@@ -79,6 +80,7 @@ pub(crate) struct Compiler<'v, 'a, 'e> {
     pub(crate) constants: Constants,
     pub(crate) has_before_stmt: bool,
     pub(crate) bc_profile: bool,
+    pub(crate) dialect_flags: DialectFlags,
 }
 
 impl Compiler<'_, '_, '_> {
@@ -31,6 +31,7 @@ use crate::{
             AstNoPayload, AstParameterP, AstPayload, AstStmtP, AstString, ClauseP, ExprP,
             ForClauseP, ParameterP, Stmt, StmtP, Visibility,
         },
+        ensure_sufficient_stack,
         payload_map::AstPayloadFunction,
         uniplate::VisitMut,
     },
@@ -229,7 +230,7 @@ impl<'a> Scope<'a> {
         let params = params.iter_mut().filter_map(|p| match &mut p.node {
             ParameterP::Normal(n, ..) => Some(n),
             ParameterP::WithDefaultValue(n, ..) => Some(n),
-            ParameterP::NoArgs => None,
+            ParameterP::NoArgs | ParameterP::PositionalOnly => None,
             ParameterP::Args(n, ..) => Some(n),
             ParameterP::KwArgs(n, ..) => Some(n),
         });
@@ -256,28 +257,32 @@ impl<'a> Scope<'a> {
     }
 
     fn collect_defines_recursively(scope_data: &mut ScopeData, code: &mut CstStmt) {
-        if let StmtP::Def(_name, params, _ret, suite, scope_id) = &mut code.node {
-            // Here we traverse the AST twice: once for this def scope,
-            // second time below for nested defs.
-            Self::collect_defines_in_def(scope_data, *scope_id, params, Some(suite));
-        }
+        ensure_sufficient_stack(|| {
+            if let StmtP::Def(_name, params, _ret, suite, scope_id) = &mut code.node {
+                // Here we traverse the AST twice: once for this def scope,
+                // second time below for nested defs.
+                Self::collect_defines_in_def(scope_data, *scope_id, params, Some(suite));
+            }
 
-        code.visit_children_mut(&mut |visit| match visit {
-            VisitMut::Expr(e) => Self::collect_defines_recursively_in_expr(scope_data, e),
-            VisitMut::Stmt(s) => Self::collect_defines_recursively(scope_data, s),
-        });
+            code.visit_children_mut(&mut |visit| match visit {
+                VisitMut::Expr(e) => Self::collect_defines_recursively_in_expr(scope_data, e),
+                VisitMut::Stmt(s) => Self::collect_defines_recursively(scope_data, s),
+            });
+        })
     }
 
     fn collect_defines_recursively_in_expr(scope_data: &mut ScopeData, code: &mut CstExpr) {
-        if let ExprP::Lambda(params, _expr, scope_id) = &mut code.node {
-            Self::collect_defines_in_def(scope_data, *scope_id, params, None);
-        }
+        ensure_sufficient_stack(|| {
+            if let ExprP::Lambda(params, _expr, scope_id) = &mut code.node {
+                Self::collect_defines_in_def(scope_data, *scope_id, params, None);
+            }
 
-        code.visit_expr_mut(|e| Self::collect_defines_recursively_in_expr(scope_data, e));
+            code.visit_expr_mut(|e| Self::collect_defines_recursively_in_expr(scope_data, e));
+        })
     }
 
     fn resolve_idents(&mut self, code: &mut CstStmt) {
-        match &mut code.node {
+        ensure_sufficient_stack(|| match &mut code.node {
             StmtP::Def(_name, params, ret, body, scope_id) => self.resolve_idents_in_def(
                 *scope_id,
                 params,
@@ -289,7 +294,7 @@ impl<'a> Scope<'a> {
                 VisitMut::Stmt(stmt) => self.resolve_idents(stmt),
                 VisitMut::Expr(expr) => self.resolve_idents_in_expr(expr),
             }),
-        }
+        })
     }
 
     fn resolve_idents_in_assign(&mut self, assign: &mut CstAssign) {
@@ -322,7 +327,7 @@ impl<'a> Scope<'a> {
     }
 
     fn resolve_idents_in_expr(&mut self, expr: &mut CstExpr) {
-        match &mut expr.node {
+        ensure_sufficient_stack(|| match &mut expr.node {
             ExprP::Identifier(ident, slot) => self.resolve_ident(ident, slot),
             ExprP::Lambda(params, body, scope_id) => {
                 self.resolve_idents_in_def(*scope_id, params, None, None, Some(body))
@@ -334,7 +339,7 @@ impl<'a> Scope<'a> {
                 self.resolve_idents_in_compr(&mut [k, v], first_for, clauses)
             }
             _ => expr.visit_expr_mut(|expr| self.resolve_idents_in_expr(expr)),
-        }
+        })
     }
 
     fn current_scope_all_visible_names_for_did_you_mean(&self) -> Vec<String> {
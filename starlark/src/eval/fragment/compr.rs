@@ -142,7 +142,7 @@ impl ComprCompiled {
                 let clauses = clauses.optimize_on_freeze(ctx);
                 ExprCompiled::compr(ComprCompiled::Dict(
                     box (k.optimize_on_freeze(ctx), v.optimize_on_freeze(ctx)),
-                    clauses.optimize_on_freeze(ctx),
+                    clauses,
                 ))
             }
         }
@@ -52,7 +52,7 @@ use crate::{
         },
         Arguments,
     },
-    syntax::ast::ParameterP,
+    syntax::{ast::ParameterP, DialectFlags},
     values::{
         docs,
         docs::{DocItem, DocString, DocStringKind},
@@ -101,6 +101,7 @@ pub(crate) enum ParameterCompiled<T> {
     Normal(ParameterName, Option<T>),
     WithDefaultValue(ParameterName, Option<T>, T),
     NoArgs,
+    PositionalOnly,
     Args(ParameterName, Option<T>),
     KwArgs(ParameterName, Option<T>),
 }
@@ -115,6 +116,7 @@ impl<T> ParameterCompiled<T> {
                 ParameterCompiled::WithDefaultValue(n.clone(), o.as_ref().map(&mut f), f(t))
             }
             ParameterCompiled::NoArgs => ParameterCompiled::NoArgs,
+            ParameterCompiled::PositionalOnly => ParameterCompiled::PositionalOnly,
             ParameterCompiled::Args(n, o) => ParameterCompiled::Args(n.clone(), o.as_ref().map(f)),
             ParameterCompiled::KwArgs(n, o) => {
                 ParameterCompiled::KwArgs(n.clone(), o.as_ref().map(f))
@@ -126,7 +128,7 @@ impl<T> ParameterCompiled<T> {
         match self {
             Self::Normal(x, _) => Some(x),
             Self::WithDefaultValue(x, _, _) => Some(x),
-            Self::NoArgs => None,
+            Self::NoArgs | Self::PositionalOnly => None,
             Self::Args(x, _) => Some(x),
             Self::KwArgs(x, _) => Some(x),
         }
@@ -152,7 +154,7 @@ impl<T> ParameterCompiled<T> {
         match self {
             Self::Normal(_, t) => t.as_ref(),
             Self::WithDefaultValue(_, t, _) => t.as_ref(),
-            Self::NoArgs => None,
+            Self::NoArgs | Self::PositionalOnly => None,
             Self::Args(_, t) => t.as_ref(),
             Self::KwArgs(_, t) => t.as_ref(),
         }
@@ -183,6 +185,8 @@ pub(crate) struct DefInfo {
     /// Globals captured during function or module creation.
     /// Only needed for debugger evaluation.
     pub(crate) globals: FrozenRef<'static, Globals>,
+    /// The dialect of the module where the function is declared.
+    pub(crate) dialect_flags: DialectFlags,
 }
 
 impl DefInfo {
@@ -197,6 +201,7 @@ impl DefInfo {
             stmt_compile_context: StmtCompileContext::default(),
             inline_def_body: None,
             globals: FrozenRef::new(Globals::empty()),
+            dialect_flags: DialectFlags::default(),
         });
         FrozenRef::new(&EMPTY)
     }
@@ -205,6 +210,7 @@ impl DefInfo {
         codemap: FrozenRef<'static, CodeMap>,
         scope_names: ScopeNames,
         globals: FrozenRef<'static, Globals>,
+        dialect_flags: DialectFlags,
     ) -> DefInfo {
         DefInfo {
             codemap,
@@ -215,6 +221,7 @@ impl DefInfo {
             stmt_compile_context: StmtCompileContext::default(),
             inline_def_body: None,
             globals,
+            dialect_flags,
         }
     }
 }
@@ -269,6 +276,7 @@ impl Compiler<'_, '_, '_> {
                     self.expr(*v),
                 ),
                 ParameterP::NoArgs => ParameterCompiled::NoArgs,
+                ParameterP::PositionalOnly => ParameterCompiled::PositionalOnly,
                 ParameterP::Args(x, t) => {
                     ParameterCompiled::Args(self.parameter_name(x), self.expr_opt(t))
                 }
@@ -460,6 +468,7 @@ impl Compiler<'_, '_, '_> {
             inline_def_body,
             stmt_compile_context: self.compile_context(),
             globals: self.globals,
+            dialect_flags: self.dialect_flags,
         });
 
         ExprCompiled::Def(DefCompiled {
@@ -737,6 +746,22 @@ where
 }
 
 impl FrozenDef {
+    /// Disassemble the compiled bytecode of this function,
+    /// one element per instruction: the opcode name followed by its arguments,
+    /// e.g. `ReturnConst 3`. Exposed as
+    /// [`OwnedFrozenValue::disassemble`](crate::values::OwnedFrozenValue::disassemble).
+    pub(crate) fn disassemble(&self) -> Vec<String> {
+        self.bc().instrs.disassemble()
+    }
+
+    /// Human readable dump of the compiled bytecode of this function,
+    /// one instruction per line with its address, arguments and source location.
+    /// Exposed as
+    /// [`OwnedFrozenValue::disassemble_pretty`](crate::values::OwnedFrozenValue::disassemble_pretty).
+    pub(crate) fn disassemble_pretty(&self) -> String {
+        self.bc().instrs.disassemble_pretty()
+    }
+
     pub(crate) fn post_freeze(
         &self,
         module: FrozenRef<FrozenModuleRef>,
@@ -39,10 +39,13 @@ use crate::{
         runtime::{call_stack::FrozenFileSpan, slots::LocalSlotId},
         FrozenDef,
     },
-    syntax::ast::{AstExprP, AstLiteral, AstPayload, AstString, BinOp, ExprP, StmtP},
+    syntax::{
+        ast::{AstExprP, AstLiteral, AstPayload, AstString, BinOp, ExprP, StmtP},
+        ensure_sufficient_stack,
+    },
     values::{
         function::BoundMethodGen,
-        string::{interpolation::parse_percent_s_one, StarlarkStr},
+        string::interpolation::parse_percent_s_one,
         types::{
             bool::StarlarkBool,
             dict::Dict,
@@ -51,6 +54,7 @@ use crate::{
             range::Range,
             string::interpolation::{format_one, percent_s_one},
             tuple::Tuple,
+            type_value::TypeValue,
             unbound::MaybeUnboundValue,
         },
         FrozenHeap, FrozenStringValue, FrozenValue, FrozenValueTyped, Heap, StarlarkValue, Value,
@@ -106,7 +110,7 @@ impl ExprUnOp {
         match self {
             ExprUnOp::Minus => v.minus(heap),
             ExprUnOp::Plus => v.plus(heap),
-            ExprUnOp::BitNot => Ok(Value::new_int(!v.to_int()?)),
+            ExprUnOp::BitNot => v.bit_not(heap),
         }
     }
 }
@@ -114,6 +118,7 @@ impl ExprUnOp {
 #[derive(Copy, Clone, Dupe, Debug)]
 pub(crate) enum ExprBinOp {
     In,
+    Is,
     Sub,
     Add,
     Multiply,
@@ -131,15 +136,16 @@ impl ExprBinOp {
     fn eval<'v>(self, a: Value<'v>, b: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         match self {
             ExprBinOp::In => b.is_in(a).map(Value::new_bool),
+            ExprBinOp::Is => Ok(Value::new_bool(a.ptr_eq(b))),
             ExprBinOp::Sub => a.sub(b, heap),
             ExprBinOp::Add => a.add(b, heap),
             ExprBinOp::Multiply => a.mul(b, heap),
             ExprBinOp::Percent => a.percent(b, heap),
             ExprBinOp::Divide => a.div(b, heap),
             ExprBinOp::FloorDivide => a.floor_div(b, heap),
-            ExprBinOp::BitAnd => a.bit_and(b),
+            ExprBinOp::BitAnd => a.bit_and(b, heap),
             ExprBinOp::BitOr => a.bit_or(b, heap),
-            ExprBinOp::BitXor => a.bit_xor(b),
+            ExprBinOp::BitXor => a.bit_xor(b, heap),
             ExprBinOp::LeftShift => a.left_shift(b),
             ExprBinOp::RightShift => a.right_shift(b),
         }
@@ -277,7 +283,8 @@ impl ExprCompiled {
             | Self::TypeIs(..)
             | Self::Not(..)
             | Self::Compare(..)
-            | Self::Op(ExprBinOp::In, ..) => true,
+            | Self::Op(ExprBinOp::In, ..)
+            | Self::Op(ExprBinOp::Is, ..) => true,
             _ => false,
         }
     }
@@ -347,6 +354,10 @@ impl IrSpanned<ExprCompiled> {
         &self,
         ctx: &OptimizeOnFreezeContext,
     ) -> IrSpanned<ExprCompiled> {
+        ensure_sufficient_stack(|| self.optimize_on_freeze_direct(ctx))
+    }
+
+    fn optimize_on_freeze_direct(&self, ctx: &OptimizeOnFreezeContext) -> IrSpanned<ExprCompiled> {
         let span = self.span;
         let expr = match self.node {
             ref e @ (ExprCompiled::Value(..)
@@ -794,28 +805,23 @@ impl ExprCompiled {
     }
 
     pub(crate) fn typ(v: IrSpanned<ExprCompiled>) -> ExprCompiled {
-        match &v.node {
-            ExprCompiled::Value(v) => ExprCompiled::Value(v.to_value().get_type_value().unpack()),
+        let name = match &v.node {
+            ExprCompiled::Value(v) => v.to_value().get_type_value(),
             ExprCompiled::Tuple(xs) if xs.iter().all(|e| e.is_pure_infallible()) => {
-                ExprCompiled::Value(Tuple::get_type_value_static().unpack())
+                Tuple::get_type_value_static()
             }
             ExprCompiled::List(xs) if xs.iter().all(|e| e.is_pure_infallible()) => {
-                ExprCompiled::Value(List::get_type_value_static().unpack())
-            }
-            ExprCompiled::Dict(xs) if xs.is_empty() => {
-                ExprCompiled::Value(Dict::get_type_value_static().unpack())
-            }
-            ExprCompiled::Type(x) if x.is_pure_infallible() => {
-                ExprCompiled::Value(StarlarkStr::get_type_value_static().unpack())
+                List::get_type_value_static()
             }
+            ExprCompiled::Dict(xs) if xs.is_empty() => Dict::get_type_value_static(),
+            ExprCompiled::Type(x) if x.is_pure_infallible() => TypeValue::get_type_value_static(),
             ExprCompiled::TypeIs(x, _t) if x.is_pure_infallible() => {
-                ExprCompiled::Value(StarlarkBool::get_type_value_static().unpack())
-            }
-            ExprCompiled::Not(x) if x.is_pure_infallible() => {
-                ExprCompiled::Value(StarlarkBool::get_type_value_static().unpack())
+                StarlarkBool::get_type_value_static()
             }
-            _ => ExprCompiled::Type(box v),
-        }
+            ExprCompiled::Not(x) if x.is_pure_infallible() => StarlarkBool::get_type_value_static(),
+            _ => return ExprCompiled::Type(box v),
+        };
+        ExprCompiled::Value(TypeValue::frozen(name))
     }
 
     pub(crate) fn type_is(v: IrSpanned<ExprCompiled>, t: FrozenStringValue) -> ExprCompiled {
@@ -858,7 +864,8 @@ pub(crate) enum EvalError {
     DuplicateDictionaryKey(String),
 }
 
-/// Try fold expression `cmp(l == r)` into `cmp(type(x) == "y")`.
+/// Try fold expression `cmp(l == r)` into `cmp(type(x) == "y")`,
+/// where `r` is either a string or the result of `type()`.
 /// Return original `l` and `r` arguments if fold was unsuccessful.
 fn try_eval_type_is(
     l: IrSpanned<ExprCompiled>,
@@ -875,7 +882,9 @@ fn try_eval_type_is(
                 span: r_span,
             },
         ) => {
-            if let Some(r) = FrozenStringValue::new(r) {
+            let name = FrozenStringValue::new(r)
+                .or_else(|| Some(r.downcast_ref::<TypeValue>()?.name_value()));
+            if let Some(r) = name {
                 Ok(IrSpanned {
                     node: ExprCompiled::type_is(*l, r),
                     span: l_span.merge(&r_span),
@@ -919,10 +928,11 @@ fn eval_equals(l: IrSpanned<ExprCompiled>, r: IrSpanned<ExprCompiled>) -> ExprCo
 }
 
 impl AstLiteral {
-    fn compile(&self, heap: &FrozenHeap) -> FrozenValue {
+    fn compile(&self, heap: &FrozenHeap, intern: bool) -> FrozenValue {
         match self {
             AstLiteral::Int(i) => FrozenValue::new_int(i.node),
             AstLiteral::Float(f) => heap.alloc(f.node),
+            AstLiteral::String(x) if intern => heap.alloc_str_intern(x.node.as_str()).unpack(),
             AstLiteral::String(x) => heap.alloc(x.node.as_str()),
         }
     }
@@ -1080,6 +1090,10 @@ impl Compiler<'_, '_, '_> {
     }
 
     pub(crate) fn expr(&mut self, expr: CstExpr) -> IrSpanned<ExprCompiled> {
+        ensure_sufficient_stack(|| self.expr_direct(expr))
+    }
+
+    fn expr_direct(&mut self, expr: CstExpr) -> IrSpanned<ExprCompiled> {
         // println!("compile {}", expr.node);
         let span = FrozenFileSpan {
             span: expr.span,
@@ -1185,7 +1199,12 @@ impl Compiler<'_, '_, '_> {
                     // Note there's const propagation for `+` on compiled expressions,
                     // but special handling of `+` on AST might be slightly more efficient
                     // (no unnecessary allocations on the heap). So keep it.
-                    let val = self.eval.module_env.frozen_heap().alloc(x);
+                    let frozen_heap = self.eval.module_env.frozen_heap();
+                    let val = if self.eval.intern_string_literals {
+                        frozen_heap.alloc_str_intern(&x).unpack()
+                    } else {
+                        frozen_heap.alloc(x)
+                    };
                     ExprCompiled::Value(val)
                 } else {
                     let right = if op == BinOp::In || op == BinOp::NotIn {
@@ -1239,6 +1258,29 @@ impl Compiler<'_, '_, '_> {
                             )
                             .node
                         }
+                        BinOp::Is => ExprCompiled::bin_op(
+                            ExprBinOp::Is,
+                            l,
+                            r,
+                            self.eval.module_env.heap(),
+                            self.eval.module_env.frozen_heap(),
+                        ),
+                        BinOp::IsNot => {
+                            ExprCompiled::not(
+                                span,
+                                IrSpanned {
+                                    span,
+                                    node: ExprCompiled::bin_op(
+                                        ExprBinOp::Is,
+                                        l,
+                                        r,
+                                        self.eval.module_env.heap(),
+                                        self.eval.module_env.frozen_heap(),
+                                    ),
+                                },
+                            )
+                            .node
+                        }
                         BinOp::Subtract => ExprCompiled::bin_op(
                             ExprBinOp::Sub,
                             l,
@@ -1326,7 +1368,10 @@ impl Compiler<'_, '_, '_> {
                 self.dict_comprehension(k, v, for_, clauses)
             }
             ExprP::Literal(x) => {
-                let val = x.compile(self.eval.module_env.frozen_heap());
+                let val = x.compile(
+                    self.eval.module_env.frozen_heap(),
+                    self.eval.intern_string_literals,
+                );
                 ExprCompiled::Value(val)
             }
         };
@@ -17,6 +17,8 @@
 
 //! Compile and evaluate module top-level statements.
 
+use std::time::Instant;
+
 use crate::{
     environment::EnvironmentError,
     eval::{
@@ -49,7 +51,21 @@ impl<'v> Compiler<'v, '_, '_> {
                     self.eval,
                 ));
             }
-            Some(loader) => expr_throw(loader.load(&name), span, self.eval)?,
+            Some(loader) => {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::info_span!("load", path = %name).entered();
+                let res = match self.eval.load_handler {
+                    None => loader.load(&name),
+                    Some(handler) => {
+                        handler.on_load_begin(&name);
+                        let start = Instant::now();
+                        let res = loader.load(&name);
+                        handler.on_load_end(&name, start.elapsed());
+                        res
+                    }
+                };
+                expr_throw(res, span, self.eval)?
+            }
         };
 
         for (our_name, their_name) in load.node.args {
@@ -47,8 +47,11 @@ use crate::{
             slots::LocalSlotId,
         },
     },
-    syntax::ast::{AssignOp, AssignP, StmtP},
-    values::{dict::Dict, list::List, FrozenHeap, FrozenValue, Heap, Value, ValueError},
+    syntax::{
+        ast::{AssignOp, AssignP, StmtP},
+        ensure_sufficient_stack,
+    },
+    values::{dict::Dict, FrozenHeap, FrozenValue, Heap, Value, ValueError},
 };
 
 #[derive(Clone, Debug)]
@@ -111,7 +114,7 @@ impl AssignModifyLhs {
 impl IrSpanned<StmtCompiled> {
     fn optimize_on_freeze(&self, ctx: &OptimizeOnFreezeContext) -> StmtsCompiled {
         let span = self.span;
-        match self.node {
+        ensure_sufficient_stack(|| match self.node {
             StmtCompiled::Return(ref e) => StmtsCompiled::one(IrSpanned {
                 span,
                 node: StmtCompiled::Return(e.optimize_on_freeze(ctx)),
@@ -154,7 +157,7 @@ impl IrSpanned<StmtCompiled> {
                     rhs.optimize_on_freeze(ctx),
                 ),
             }),
-        }
+        })
     }
 }
 
@@ -185,11 +188,13 @@ impl StmtsCompiled {
         self.0.as_slice()
     }
 
-    /// Last statement in this block is `break`, `continue` or `return`.
+    /// Last statement in this block is `break`, `continue` or `return`,
+    /// or an `if` statement where both branches are terminal.
     fn is_terminal(&self) -> bool {
         if let Some(stmt) = self.last() {
             match &stmt.node {
                 StmtCompiled::Break | StmtCompiled::Continue | StmtCompiled::Return(..) => true,
+                StmtCompiled::If(x) => x.1.is_terminal() && x.2.is_terminal(),
                 _ => false,
             }
         } else {
@@ -466,7 +471,7 @@ impl Compiler<'_, '_, '_> {
 // The purposes are GC, profiling and debugging.
 //
 // This function is called only if `before_stmt` is set before compilation start.
-pub(crate) fn before_stmt(span: FrozenFileSpan, eval: &mut Evaluator) {
+pub(crate) fn before_stmt(span: FrozenFileSpan, eval: &mut Evaluator) -> anyhow::Result<()> {
     assert!(
         eval.before_stmt.enabled(),
         "this code should not be called if `before_stmt` is set"
@@ -486,12 +491,22 @@ pub(crate) fn before_stmt(span: FrozenFileSpan, eval: &mut Evaluator) {
         added.is_empty(),
         "`before_stmt` cannot be modified during evaluation"
     );
+    if let Some(breakpoints) = eval.before_stmt.breakpoints {
+        breakpoints.before_stmt(
+            FileSpanRef {
+                span: span.span,
+                file: &span.file,
+            },
+            eval,
+        )?;
+    }
+    Ok(())
 }
 
 // There are two requirements to perform a GC:
 //
 // 1. We can't be profiling, since profiling relies on the redundant heap
-//    entries. When profiling we set disable_gc.
+//    entries. When profiling we set gc_forbidden.
 // 2. We must be able to access all roots.
 //
 // We track as many roots as possible, and eventually aim to track them all, but
@@ -518,7 +533,7 @@ pub(crate) fn before_stmt(span: FrozenFileSpan, eval: &mut Evaluator) {
 // We also require that `extra_v` is None, since otherwise the user might have
 // additional values stashed somewhere.
 pub(crate) fn possible_gc(eval: &mut Evaluator) {
-    if !eval.disable_gc
+    if !eval.gc_disabled()
         && eval.heap().allocated_bytes() >= eval.next_gc_level
         && eval.extra_v.is_none()
     {
@@ -526,7 +541,7 @@ pub(crate) fn possible_gc(eval: &mut Evaluator) {
         // references to all values, so walking covers everything and the unsafe
         // is satisfied.
         unsafe {
-            eval.garbage_collect()
+            eval.garbage_collect();
         }
         eval.next_gc_level = eval.heap().allocated_bytes() + GC_THRESHOLD;
     }
@@ -587,33 +602,14 @@ pub(crate) fn add_assign<'v>(
         }
     }
 
-    // The Starlark spec says list += mutates, while nothing else does.
-    // When mutating, be careful if they alias, so we don't have `lhs`
-    // mutably borrowed when we iterate over `rhs`, as they might alias.
-
     // In practice, select is the only thing that implements radd.
     // If the users does x += select(...) we don't want an error,
     // we really want to x = x + select, so check radd first.
     if let Some(v) = rhs.get_ref().radd(lhs, heap) {
         v
     } else {
-        let lhs_aref = lhs.get_ref();
-        let lhs_ty = lhs_aref.static_type_of_value();
-
-        if List::is_list_type(lhs_ty) {
-            // If the value is None, that must mean its a FrozenList, thus turn it into an immutable error
-            let list = List::from_value_mut(lhs)?
-                .ok_or_else(|| anyhow!(ValueError::CannotMutateImmutableValue))?;
-            if lhs.ptr_eq(rhs) {
-                list.double(heap);
-            } else {
-                // TODO: if RHS is list, consider calling `List::extend_from_slice`.
-                rhs.with_iterator(heap, |it| list.extend(it, heap))?;
-            }
-            Ok(lhs)
-        } else {
-            lhs_aref.add(rhs, heap)
-        }
+        // Lists override `add_assign` to mutate in place.
+        lhs.get_ref().add_assign(lhs, rhs, heap)
     }
 }
 
@@ -631,7 +627,7 @@ impl Compiler<'_, '_, '_> {
             file: self.codemap,
         };
         let is_statements = matches!(&stmt.node, StmtP::Statements(_));
-        let res = self.stmt_direct(stmt, allow_gc);
+        let res = ensure_sufficient_stack(|| self.stmt_direct(stmt, allow_gc));
         // No point inserting a GC point around statements, since they will contain inner statements we can do
         if allow_gc && !is_statements {
             // We could do this more efficiently by fusing the possible_gc
@@ -26,7 +26,7 @@ use gazebo::prelude::*;
 pub use runtime::{
     arguments::{Arguments, ParametersParser, ParametersSpec},
     evaluator::Evaluator,
-    file_loader::{FileLoader, ReturnFileLoader},
+    file_loader::{CachedFileLoader, FileLoader, FrozenModuleCache, LoadHandler, ReturnFileLoader},
 };
 
 use crate::{
@@ -42,6 +42,10 @@ use crate::{
     syntax::ast::AstModule,
     values::{docs::DocString, Value},
 };
+pub use crate::{
+    debug::breakpoint::{BreakpointAction, Breakpoints},
+    stdlib::glob::{DirEntry, FileSystem},
+};
 
 pub(crate) mod bc;
 mod compiler;
@@ -58,13 +62,21 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     pub fn eval_module(&mut self, ast: AstModule, globals: &Globals) -> anyhow::Result<Value<'v>> {
         let start = Instant::now();
 
-        let AstModule { codemap, statement } = ast;
+        let AstModule {
+            codemap,
+            statement,
+            flags,
+            ..
+        } = ast;
 
         let codemap = self
             .module_env
             .frozen_heap()
             .alloc_any_display_from_debug(codemap.dupe());
 
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("eval_module", file = codemap.filename()).entered();
+
         let globals = self.module_env.frozen_heap().alloc_any(globals.dupe());
 
         let mut scope_data = ScopeData::new();
@@ -103,6 +115,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
                 codemap,
                 scope_names,
                 globals,
+                flags,
             )),
         );
 
@@ -124,6 +137,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             constants: Constants::new(),
             has_before_stmt: self.before_stmt.enabled(),
             bc_profile: self.bc_profile.enabled(),
+            dialect_flags: flags,
             eval: self,
         };
 
@@ -36,8 +36,8 @@ use crate::{
         dict::{Dict, DictRef},
         docs,
         docs::DocString,
-        Freezer, FrozenValue, Heap, StringValue, Trace, Tracer, UnpackValue, Value, ValueError,
-        ValueLike,
+        Freezer, FrozenValue, Heap, StarlarkValue, StringValue, Trace, Tracer, UnpackValue, Value,
+        ValueError, ValueLike,
     },
 };
 
@@ -227,7 +227,7 @@ impl<V> ParametersSpec<V> {
         collector
     }
 
-    /// Figure out the argument name at an index in kinds.
+    /// Figure out the argument name at an index in kinds, without any `$` prefix.
     /// Only called in the error path, so is not optimised.
     pub(crate) fn param_name_at(&self, index: usize) -> String {
         match self.kinds[index] {
@@ -241,6 +241,7 @@ impl<V> ParametersSpec<V> {
             .unwrap()
             .0
             .as_str()
+            .trim_start_match('$')
             .to_owned()
     }
 
@@ -982,6 +983,19 @@ impl Arguments<'_, '_> {
         T::unpack_named_param(this, "this")
     }
 
+    /// Utility for checking a `this` parameter is a `T`, for methods in a `#[starlark_module]`
+    /// with type parameters, which take `this: &T`.
+    pub fn check_this_type<'v, T: StarlarkValue<'v>>(this: Value<'v>) -> anyhow::Result<&'v T> {
+        this.downcast_ref::<T>().ok_or_else(|| {
+            ValueError::IncorrectParameterTypeNamedWithExpected(
+                "this".to_owned(),
+                T::get_type_value_static().as_str().to_owned(),
+                this.get_type().to_owned(),
+            )
+            .into()
+        })
+    }
+
     /// Utility for checking a required parameter matches what you expect.
     pub fn check_required<'v, T: UnpackValue<'v>>(
         name: &str,
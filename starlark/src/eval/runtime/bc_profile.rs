@@ -77,6 +77,11 @@ struct BcPairsProfileData {
     by_instr: HashMap<[BcOpcode; 2], BcInstrPairsStat>,
 }
 
+/// Which opcodes were executed at least once.
+struct BcCoverageProfileData {
+    executed: [bool; BcOpcode::COUNT],
+}
+
 // Derive doesn't work here.
 impl Default for BcProfileData {
     fn default() -> Self {
@@ -87,6 +92,14 @@ impl Default for BcProfileData {
     }
 }
 
+impl Default for BcCoverageProfileData {
+    fn default() -> Self {
+        BcCoverageProfileData {
+            executed: [false; BcOpcode::COUNT],
+        }
+    }
+}
+
 impl BcProfileData {
     fn before_instr(&mut self, opcode: BcOpcode) {
         let now = Instant::now();
@@ -160,9 +173,39 @@ impl BcPairsProfileData {
     }
 }
 
+impl BcCoverageProfileData {
+    fn before_instr(&mut self, opcode: BcOpcode) {
+        self.executed[opcode as usize] = true;
+    }
+
+    fn gen_csv(&self) -> String {
+        let mut by_instr: Vec<_> = self
+            .executed
+            .iter()
+            .enumerate()
+            .map(|(i, executed)| (BcOpcode::by_number(i as u32).unwrap(), *executed))
+            .collect();
+        // Executed opcodes first, otherwise in opcode order.
+        by_instr.sort_by_key(|(opcode, executed)| (!*executed, *opcode as u32));
+        let mut csv = CsvWriter::new(["Opcode", "Executed"]);
+        {
+            csv.write_display("TOTAL");
+            csv.write_value(by_instr.iter().filter(|(_, executed)| *executed).count());
+            csv.finish_row();
+        }
+        for (opcode, executed) in &by_instr {
+            csv.write_debug(opcode);
+            csv.write_display(executed);
+            csv.finish_row();
+        }
+        csv.finish()
+    }
+}
+
 enum BcProfileDataMode {
     Bc(Box<BcProfileData>),
     BcPairs(Box<BcPairsProfileData>),
+    BcCoverage(Box<BcCoverageProfileData>),
     Disabled,
 }
 
@@ -185,10 +228,15 @@ impl BcProfile {
         self.data = BcProfileDataMode::BcPairs(Default::default());
     }
 
+    pub(crate) fn enable_coverage(&mut self) {
+        self.data = BcProfileDataMode::BcCoverage(Default::default());
+    }
+
     pub(crate) fn enabled(&self) -> bool {
         match self.data {
             BcProfileDataMode::Bc(..) => true,
             BcProfileDataMode::BcPairs(..) => true,
+            BcProfileDataMode::BcCoverage(..) => true,
             BcProfileDataMode::Disabled => false,
         }
     }
@@ -197,6 +245,7 @@ impl BcProfile {
         match &self.data {
             BcProfileDataMode::Bc(data) => Ok(data.gen_csv()),
             BcProfileDataMode::BcPairs(data) => Ok(data.gen_csv()),
+            BcProfileDataMode::BcCoverage(data) => Ok(data.gen_csv()),
             BcProfileDataMode::Disabled => Err(EvaluatorError::BcProfilingNotEnabled.into()),
         }
     }
@@ -211,6 +260,7 @@ impl BcProfile {
         match &mut self.data {
             BcProfileDataMode::Bc(data) => data.before_instr(opcode),
             BcProfileDataMode::BcPairs(data) => data.before_instr(opcode),
+            BcProfileDataMode::BcCoverage(data) => data.before_instr(opcode),
             BcProfileDataMode::Disabled => {
                 unreachable!("this code is unreachable when bytecode profiling is not enabled")
             }
@@ -267,4 +317,33 @@ mod tests {
             csv
         );
     }
+
+    #[test]
+    fn test_coverage() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        eval.enable_profile(&ProfileMode::BytecodeCoverage);
+        eval.eval_module(
+            AstModule::parse(
+                "bc.star",
+                "def f(x):\n    return x\nf(1)".to_owned(),
+                &Dialect::Standard,
+            )
+            .unwrap(),
+            &globals,
+        )
+        .unwrap();
+        let csv = eval.bc_profile.gen_csv().unwrap();
+        assert!(
+            csv.contains(&format!("\n{:?},true\n", BcOpcode::Return)),
+            "{:?}",
+            csv
+        );
+        assert!(
+            csv.contains(&format!("\n{:?},false\n", BcOpcode::ComprDictInsert)),
+            "{:?}",
+            csv
+        );
+    }
 }
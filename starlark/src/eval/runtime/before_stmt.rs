@@ -17,7 +17,10 @@
 
 //! Configuration of `BeforeStmt` instrumentation of bytecode.
 
-use crate::{codemap::FileSpanRef, eval::Evaluator};
+use crate::{
+    codemap::FileSpanRef,
+    eval::{Breakpoints, Evaluator},
+};
 
 /// Configuration of `BeforeStmt` instrumentation of bytecode.
 #[derive(Default)]
@@ -28,10 +31,12 @@ pub(crate) struct BeforeStmt<'v, 'a> {
     /// even if no `before_stmt` functions are registered.
     /// This is needed when compiling dependencies of a file to be profiled.
     pub(crate) instrument: bool,
+    /// Locations to pause at, if any.
+    pub(crate) breakpoints: Option<&'a Breakpoints<'a>>,
 }
 
 impl<'v, 'a> BeforeStmt<'v, 'a> {
     pub(crate) fn enabled(&self) -> bool {
-        self.instrument || !self.before_stmt.is_empty()
+        self.instrument || !self.before_stmt.is_empty() || self.breakpoints.is_some()
     }
 }
@@ -32,13 +32,23 @@ use std::{
 
 use gazebo::prelude::*;
 use once_cell::sync::Lazy;
+use thiserror::Error;
 
 use crate::{
     codemap::{CodeMap, FileSpan, Span},
     errors::Frame,
-    values::{ControlError, FrozenRef, Trace, Tracer, Value},
+    values::{FrozenRef, Trace, Tracer, Value},
 };
 
+#[derive(Debug, Error)]
+enum CallStackError {
+    #[error(
+        "Too many recursion levels, exceeded the maximum call depth of {0}, \
+        see `Evaluator::set_max_call_depth`"
+    )]
+    RecursionLimit(usize),
+}
+
 #[derive(Debug, Clone, Copy, Dupe)]
 pub(crate) struct FrozenFileSpan {
     pub(crate) file: FrozenRef<'static, CodeMap>,
@@ -116,41 +126,37 @@ impl Debug for CheapFrame<'_> {
 /// Starlark call stack.
 #[derive(Debug)]
 pub(crate) struct CallStack<'v> {
-    count: usize,
-    stack: [CheapFrame<'v>; MAX_CALLSTACK_RECURSION],
+    stack: Vec<CheapFrame<'v>>,
+    max_depth: usize,
 }
 
 impl<'v> Default for CallStack<'v> {
     fn default() -> Self {
         Self {
-            count: 0,
-            stack: [CheapFrame {
-                function: Value::new_none(),
-                span: None,
-            }; MAX_CALLSTACK_RECURSION],
+            stack: Vec::with_capacity(DEFAULT_MAX_CALL_DEPTH),
+            max_depth: DEFAULT_MAX_CALL_DEPTH,
         }
     }
 }
 
 // At 50 we see the C stack overflowing, so limit to 40 (which seems quite
 // low...)
-const MAX_CALLSTACK_RECURSION: usize = 40;
+const DEFAULT_MAX_CALL_DEPTH: usize = 40;
 
 unsafe impl<'v> Trace<'v> for CallStack<'v> {
     fn trace(&mut self, tracer: &Tracer<'v>) {
-        for x in self.stack[0..self.count].iter_mut() {
+        for x in self.stack.iter_mut() {
             x.function.trace(tracer);
         }
-        // Not required, but since we are chosing not to walk those above
-        // the current stack depth, it's good practice to blank those values out
-        for x in self.stack[self.count..].iter_mut() {
-            x.function = Value::new_none();
-            x.span = None;
-        }
     }
 }
 
 impl<'v> CallStack<'v> {
+    /// Set the number of frames at which [`push`](CallStack::push) fails.
+    pub(crate) fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
     /// Push an element to the stack. It is important the each `push` is paired
     /// with a `pop`.
     pub(crate) fn push(
@@ -158,39 +164,37 @@ impl<'v> CallStack<'v> {
         function: Value<'v>,
         span: Option<FrozenRef<'static, FrozenFileSpan>>,
     ) -> anyhow::Result<()> {
-        if unlikely(self.count >= MAX_CALLSTACK_RECURSION) {
-            return Err(ControlError::TooManyRecursionLevel.into());
+        if unlikely(self.stack.len() >= self.max_depth) {
+            return Err(CallStackError::RecursionLimit(self.max_depth).into());
         }
-        self.stack[self.count] = CheapFrame { function, span };
-        self.count += 1;
+        self.stack.push(CheapFrame { function, span });
         Ok(())
     }
 
     /// Remove the top element from the stack. Called after `push`.
     pub(crate) fn pop(&mut self) {
-        debug_assert!(self.count >= 1);
-        // We could clear the elements, but don't need to bother
-        self.count -= 1;
+        let popped = self.stack.pop();
+        debug_assert!(popped.is_some());
     }
 
     /// The location at the top of the stack. May be `None` if
     /// either there the stack is empty, or the top of the stack lacks location
     /// information (e.g. called from Rust).
     pub(crate) fn top_location(&self) -> Option<FileSpan> {
-        if self.count == 0 {
-            None
-        } else {
-            self.stack[self.count - 1].location()
-        }
+        self.stack.last()?.location()
     }
 
     pub(crate) fn to_diagnostic_frames(&self) -> Vec<Frame> {
         // The first entry is just the entire module, so skip it
-        self.stack[1..self.count].map(CheapFrame::to_frame)
+        self.stack
+            .iter()
+            .skip(1)
+            .map(CheapFrame::to_frame)
+            .collect()
     }
 
     /// List the entries on the stack as values
     pub(crate) fn to_function_values(&self) -> Vec<Value<'v>> {
-        self.stack[1..self.count].map(|x| x.function)
+        self.stack.iter().skip(1).map(|x| x.function).collect()
     }
 }
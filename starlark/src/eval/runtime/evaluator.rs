@@ -37,21 +37,22 @@ use crate::{
             bc_profile::BcProfile,
             before_stmt::BeforeStmt,
             call_stack::{CallStack, FrozenFileSpan},
-            flame_profile::FlameProfile,
+            flame_profile::{FlameProfile, FlameWeight},
             heap_profile::{HeapProfile, HeapProfileFormat},
             profile::ProfileMode,
             slots::LocalSlotId,
             stmt_profile::StmtProfile,
         },
-        FileLoader,
+        FileLoader, LoadHandler,
     },
     stdlib::{
         breakpoint::{BreakpointConsole, RealBreakpointConsole},
         extra::{PrintHandler, StderrPrintHandler},
+        glob::FileSystem,
     },
     values::{
-        value_captured_get, FrozenHeap, FrozenRef, Heap, Trace, Tracer, Value, ValueCaptured,
-        ValueLike,
+        value_captured_get, FrozenHeap, FrozenRef, GcStats, Heap, StarlarkValue, Trace, Tracer,
+        Value, ValueCaptured, ValueLike,
     },
 };
 
@@ -83,6 +84,8 @@ pub struct Evaluator<'v, 'a> {
     pub(crate) current_frame: BcFramePtr<'v>,
     // How we deal with a `load` function.
     pub(crate) loader: Option<&'a dyn FileLoader>,
+    // Callbacks around each call to the loader, if any
+    pub(crate) load_handler: Option<&'a dyn LoadHandler>,
     // `DefInfo` of currently executed function or module.
     pub(crate) def_info: FrozenRef<'static, DefInfo>,
     // Should we enable heap profiling or not
@@ -91,8 +94,11 @@ pub struct Evaluator<'v, 'a> {
     pub(crate) flame_profile: FlameProfile<'v>,
     // Is either heap or flame profiling enabled
     pub(crate) heap_or_flame_profile: bool,
-    // Is GC disabled for some reason
+    // Has GC been disabled by `disable_gc`
     pub(crate) disable_gc: bool,
+    // Is GC disabled for a reason `enable_gc` can't override,
+    // e.g. a heap profile which relies on nothing being freed
+    pub(crate) gc_forbidden: bool,
     // If true, the interpreter prints to stderr on GC.
     // This is used for debugging.
     pub(crate) verbose_gc: bool,
@@ -108,18 +114,24 @@ pub struct Evaluator<'v, 'a> {
     alloca: Alloca,
     // Another stack-like allocation
     pub(crate) string_pool: StringPool,
+    // Are string literals interned when compiled
+    pub(crate) intern_string_literals: bool,
     /// Field that can be used for any purpose you want (can store types you define).
     /// Typically accessed via native functions you also define.
     pub extra: Option<&'a dyn AnyLifetime<'a>>,
     /// Field that can be used for any purpose you want (can store heap-resident [`Value<'v>`]).
     /// If this value is used, garbage collection is disabled.
     pub extra_v: Option<&'a dyn AnyLifetime<'v>>,
+    /// A heap-resident value set by the host, see [`set_extra_value`](Evaluator::set_extra_value).
+    /// Unlike `extra_v` it is a garbage collection root, so GC stays enabled.
+    pub(crate) extra_value: Option<Value<'v>>,
     /// Called to perform console IO each time `breakpoint` function is called.
     pub(crate) breakpoint_handler: Option<Box<dyn Fn() -> Box<dyn BreakpointConsole>>>,
     /// Use in implementation of `print` function.
     pub(crate) print_handler: &'a (dyn PrintHandler + 'a),
+    /// Used in implementation of `glob` function.
+    pub(crate) file_system: Option<&'a (dyn FileSystem + 'a)>,
     // The Starlark-level call-stack of functions.
-    // Must go last because it's quite a big structure
     pub(crate) call_stack: CallStack<'v>,
 }
 
@@ -130,6 +142,7 @@ unsafe impl<'v> Trace<'v> for Evaluator<'v, '_> {
         self.current_frame.trace(tracer);
         self.call_stack.trace(tracer);
         self.flame_profile.trace(tracer);
+        self.extra_value.trace(tracer);
     }
 }
 
@@ -145,10 +158,13 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             module_variables: None,
             current_frame: BcFramePtr::null(),
             loader: None,
+            load_handler: None,
             extra: None,
             extra_v: None,
+            extra_value: None,
             next_gc_level: GC_THRESHOLD,
             disable_gc: false,
+            gc_forbidden: false,
             alloca: Alloca::new(),
             heap_profile: HeapProfile::new(),
             stmt_profile: StmtProfile::new(),
@@ -158,19 +174,66 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             before_stmt: BeforeStmt::default(),
             def_info: DefInfo::empty(), // Will be replaced before it is used
             string_pool: StringPool::default(),
+            intern_string_literals: false,
             breakpoint_handler: None,
             print_handler: &StderrPrintHandler,
+            file_system: None,
             verbose_gc: false,
         }
     }
 
-    /// Disables garbage collection from now onwards. Cannot be re-enabled.
+    /// Disables garbage collection from now onwards, until [`enable_gc`](Evaluator::enable_gc)
+    /// is called.
     /// Usually called because you have captured [`Value`]'s unsafely, either in
-    /// global variables or the [`extra`](Evaluator::extra) field.
+    /// global variables or the [`extra`](Evaluator::extra) field, or to avoid the overhead
+    /// of collecting during an allocation-heavy section.
+    ///
+    /// While disabled nothing is ever freed, so the heap grows with every allocation.
+    /// Keep disabled sections short, or the memory use of a long-running evaluation is unbounded.
     pub fn disable_gc(&mut self) {
         self.disable_gc = true;
     }
 
+    /// Re-enables garbage collection after [`disable_gc`](Evaluator::disable_gc),
+    /// and collects at the next opportunity, freeing anything that became garbage while disabled.
+    /// Garbage collection stays disabled if it was turned off for some other reason, such as
+    /// a heap profile which relies on nothing being freed, or a call to
+    /// [`eval_statements`](Evaluator::eval_statements).
+    pub fn enable_gc(&mut self) {
+        self.disable_gc = false;
+        self.trigger_gc();
+    }
+
+    /// Is garbage collection disabled, either by [`disable_gc`](Evaluator::disable_gc)
+    /// or for a reason [`enable_gc`](Evaluator::enable_gc) can't override.
+    pub(crate) fn gc_disabled(&self) -> bool {
+        self.disable_gc || self.gc_forbidden
+    }
+
+    /// Does int arithmetic promote to a bigint on overflow in the running code,
+    /// following the dialect of the module which defined it.
+    pub(crate) fn bigint_enabled(&self) -> bool {
+        self.def_info.dialect_flags.enable_bigint
+    }
+
+    /// Can the running code create `bytes`, following the dialect of the module which defined it.
+    pub(crate) fn bytes_enabled(&self) -> bool {
+        self.def_info.dialect_flags.enable_bytes
+    }
+
+    /// Can `sorted`, `min` and `max` order values of different types in the running code,
+    /// following the dialect of the module which defined it.
+    pub(crate) fn cross_type_ordering_enabled(&self) -> bool {
+        self.def_info.dialect_flags.enable_cross_type_ordering
+    }
+
+    /// Intern all string literals in code compiled from now onwards, so equal literals
+    /// share a single allocation on the module's frozen heap.
+    /// Useful to reduce memory for programs which repeat many string literals.
+    pub fn enable_string_literal_interning(&mut self) {
+        self.intern_string_literals = true;
+    }
+
     /// Enable GC logging.
     pub fn verbose_gc(&mut self) {
         self.verbose_gc = true;
@@ -183,6 +246,19 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.loader = Some(loader);
     }
 
+    /// Set the [`LoadHandler`] notified before and after each `load()` statement
+    /// asks the [`FileLoader`] for a module.
+    pub fn set_load_handler(&mut self, handler: &'a dyn LoadHandler) {
+        self.load_handler = Some(handler);
+    }
+
+    /// Hint the number of bytes the module heap is expected to grow to,
+    /// so allocations during evaluation grow the heap less often.
+    /// Purely advisory, see [`Heap::set_size_hint`].
+    pub fn set_static_heap_size_hint(&mut self, bytes: usize) {
+        self.heap().set_size_hint(bytes);
+    }
+
     /// Enable profiling, allowing [`Evaluator::write_profile`] to be used.
     /// Profilers add overhead, and while some profilers can be used together,
     /// it's better to run at most one profiler at a time.
@@ -193,14 +269,33 @@ impl<'v, 'a> Evaluator<'v, 'a> {
                 self.heap_or_flame_profile = true;
                 // Disable GC because otherwise why lose the profile records, as we use the heap
                 // to store a complete list of what happened in linear order.
-                self.disable_gc = true;
+                self.gc_forbidden = true;
+            }
+            ProfileMode::HeapSummary => {
+                self.heap_profile.enable_by_type();
+                // Disable GC so the summary includes everything allocated during evaluation.
+                self.gc_forbidden = true;
             }
-            ProfileMode::Stmt => {
-                self.stmt_profile.enable();
-                self.before_stmt(&|span, eval| eval.stmt_profile.before_stmt(span));
+            ProfileMode::Stmt | ProfileMode::HeapStmt => {
+                if let ProfileMode::HeapStmt = mode {
+                    // Disable GC, otherwise a collection would hide the allocations of a statement.
+                    self.gc_forbidden = true;
+                }
+                // Both modes share the same data, so only record it once.
+                if !self.stmt_profile.enabled() {
+                    self.stmt_profile.enable();
+                    self.before_stmt(&|span, eval| {
+                        let allocated = eval.heap().allocated_bytes();
+                        eval.stmt_profile.before_stmt(span, allocated)
+                    });
+                }
             }
             ProfileMode::Flame => {
-                self.flame_profile.enable();
+                self.flame_profile.enable(FlameWeight::Time);
+                self.heap_or_flame_profile = true;
+            }
+            ProfileMode::FlameCalls => {
+                self.flame_profile.enable(FlameWeight::Calls);
                 self.heap_or_flame_profile = true;
             }
             ProfileMode::Bytecode => {
@@ -209,10 +304,12 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             ProfileMode::BytecodePairs => {
                 self.bc_profile.enable_2();
             }
+            ProfileMode::BytecodeCoverage => {
+                self.bc_profile.enable_coverage();
+            }
         }
     }
 
-
     /// Generate instructions to invoke before stmt callbacks when evaluating the module,
     /// even if this module does not use any such callbacks.
     ///
@@ -228,7 +325,7 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     /// does profiling in the given mode.
     pub fn enable_profile_instrumentation(&mut self, mode: &ProfileMode) {
         match mode {
-            ProfileMode::Bytecode | ProfileMode::BytecodePairs => {
+            ProfileMode::Bytecode | ProfileMode::BytecodePairs | ProfileMode::BytecodeCoverage => {
                 self.bc_profile.enable_1();
             }
             _ => {
@@ -257,14 +354,22 @@ impl<'v, 'a> Evaluator<'v, 'a> {
                     HeapProfileFormat::FlameGraph,
                 )
                 .unwrap_or_else(|| Err(EvaluatorError::HeapProfilingNotEnabled.into())),
+            ProfileMode::HeapSummary => self
+                .heap_profile
+                .write(filename.as_ref(), self.heap(), HeapProfileFormat::ByType)
+                .unwrap_or_else(|| Err(EvaluatorError::HeapProfilingNotEnabled.into())),
             ProfileMode::Stmt => self
                 .stmt_profile
                 .write(filename.as_ref())
                 .unwrap_or_else(|| Err(EvaluatorError::StmtProfilingNotEnabled.into())),
-            ProfileMode::Bytecode | ProfileMode::BytecodePairs => {
+            ProfileMode::HeapStmt => self
+                .stmt_profile
+                .write_allocations(filename.as_ref(), self.heap().allocated_bytes())
+                .unwrap_or_else(|| Err(EvaluatorError::StmtProfilingNotEnabled.into())),
+            ProfileMode::Bytecode | ProfileMode::BytecodePairs | ProfileMode::BytecodeCoverage => {
                 self.bc_profile.write_csv(filename.as_ref())
             }
-            ProfileMode::Flame => self
+            ProfileMode::Flame | ProfileMode::FlameCalls => self
                 .flame_profile
                 .write(filename.as_ref())
                 .unwrap_or_else(|| Err(EvaluatorError::FlameProfilingNotEnabled.into())),
@@ -278,6 +383,16 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.breakpoint_handler = Some(RealBreakpointConsole::factory());
     }
 
+    /// Set the maximum depth of nested function calls, including calls to functions
+    /// written in Rust and the module itself. Calls beyond this depth fail with an error,
+    /// rather than overflowing the native stack. Defaults to 40.
+    ///
+    /// Each call uses several kilobytes of native stack, so raising the limit may
+    /// require running the evaluator on a thread with a larger stack.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.call_stack.set_max_depth(depth);
+    }
+
     /// Obtain the current call-stack, suitable for use with [`Diagnostic`].
     pub fn call_stack(&self) -> Vec<Frame> {
         self.call_stack.to_diagnostic_frames()
@@ -292,6 +407,14 @@ impl<'v, 'a> Evaluator<'v, 'a> {
     /// Called before every statement is run with the span and a reference to the containing [`Evaluator`].
     /// A list of all possible statements can be obtained in advance by
     /// [`AstModule::stmt_locations`](crate::syntax::AstModule::stmt_locations).
+    /// From inside the callback, [`local_variables`](Evaluator::local_variables) gives the
+    /// variables of the statement's frame, which is enough to build a step debugger.
+    /// [`breakpoints`](Evaluator::breakpoints) builds such a debugger for a set of lines.
+    ///
+    /// The calls are compiled into the code, so must be registered before the module is
+    /// evaluated. If there are none, and
+    /// [`enable_before_stmt_instrumentation`](Evaluator::enable_before_stmt_instrumentation)
+    /// is not called, evaluation is no slower.
     ///
     /// This function may have no effect is called mid evaluation.
     pub fn before_stmt(&mut self, f: &'a dyn Fn(FileSpanRef, &mut Evaluator<'v, 'a>)) {
@@ -303,6 +426,35 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.print_handler = handler;
     }
 
+    /// Set the files visible to the `glob` function, which fails if none have been set.
+    pub fn set_file_system(&mut self, file_system: &'a (dyn FileSystem + 'a)) {
+        self.file_system = Some(file_system);
+    }
+
+    /// Store a value for native functions to retrieve with
+    /// [`extra_value`](Evaluator::extra_value), typically a host-provided context object.
+    ///
+    /// The value must live as long as the evaluator's module, so must be allocated on the
+    /// module's heap, [`Evaluator::heap`], or be frozen. It is kept alive by garbage collection,
+    /// but is not part of the module, so is dropped when the module is frozen unless
+    /// also assigned to a module variable.
+    pub fn set_extra_value(&mut self, value: Value<'v>) {
+        self.extra_value = Some(value);
+    }
+
+    /// The value stored with [`set_extra_value`](Evaluator::set_extra_value), if any.
+    pub fn extra_value(&self) -> Option<Value<'v>> {
+        self.extra_value
+    }
+
+    /// The value stored with [`set_extra_value`](Evaluator::set_extra_value), if there is one
+    /// and it has type `T`. A value which was frozen has its frozen type, so for a type whose
+    /// frozen form differs, such as those defined with `starlark_complex_value!`,
+    /// ask for the type matching how the value was allocated.
+    pub fn extra_value_downcast<T: StarlarkValue<'v>>(&self) -> Option<&'v T> {
+        self.extra_value?.downcast_ref::<T>()
+    }
+
     pub(crate) fn check_types(&self) -> bool {
         // We currently always check types. We suspect that for performance reasons one day
         // we'll want to make it optional, so guard the relevant places behind this test.
@@ -327,7 +479,17 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             })
         }
 
-        self.call_stack.push(function, span)?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "call",
+            function = %function,
+            location = %span.map_or_else(String::new, |s| s.to_string()),
+        )
+        .entered();
+
+        self.call_stack
+            .push(function, span)
+            .map_err(|e| add_diagnostics(e, self))?;
         if unlikely(self.heap_or_flame_profile) {
             self.heap_profile.record_call_enter(function, self.heap());
             self.flame_profile.record_call_enter(function);
@@ -504,24 +666,25 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         self.next_gc_level = 0;
     }
 
-    /// Perform a garbage collection.
+    /// Perform a garbage collection, returning how much memory was freed.
     /// After this operation all [`Value`]s not reachable from the evaluator will be invalid,
     /// and using them will lead to a segfault.
     /// Do not call during Starlark evaluation.
-    pub unsafe fn garbage_collect(&mut self) {
+    pub unsafe fn garbage_collect(&mut self) -> GcStats {
         if self.verbose_gc {
             eprintln!(
                 "Starlark: allocated bytes: {}, starting GC...",
                 self.heap().allocated_bytes()
             );
         }
-        self.heap().garbage_collect(|tracer| self.trace(tracer));
+        let stats = self.heap().garbage_collect(|tracer| self.trace(tracer));
         if self.verbose_gc {
             eprintln!(
-                "Starlark: GC complete. Allocated bytes: {}.",
-                self.heap().allocated_bytes()
+                "Starlark: GC complete in {:?}. Allocated bytes: {}.",
+                stats.duration, stats.bytes_after
             );
         }
+        stats
     }
 
     /// Note that the `Drop` for the `T` will not be called. That's safe if there is no `Drop`,
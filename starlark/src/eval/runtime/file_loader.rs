@@ -18,12 +18,16 @@
 //! Define variants of the evaluation function with different support
 //! for the `load(...)` statement.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
 
 use anyhow::anyhow;
 use gazebo::prelude::*;
 
-use crate::environment::FrozenModule;
+use crate::{
+    environment::{FrozenModule, Globals, Module},
+    eval::Evaluator,
+    syntax::{AstModule, Dialect},
+};
 
 /// A trait for turning a `path` given by a `load()` statement into a [`FrozenModule`].
 pub trait FileLoader {
@@ -31,6 +35,17 @@ pub trait FileLoader {
     fn load(&self, path: &str) -> anyhow::Result<FrozenModule>;
 }
 
+/// Callbacks invoked around each call to [`FileLoader::load`], e.g. to record timing spans.
+/// Set with [`Evaluator::set_load_handler`](crate::eval::Evaluator::set_load_handler).
+pub trait LoadHandler {
+    /// Called before the loader is asked for the module at `path`.
+    fn on_load_begin(&self, _path: &str) {}
+
+    /// Called after the loader has returned the module at `path` (or failed),
+    /// with the time spent in the loader.
+    fn on_load_end(&self, _path: &str, _duration: Duration) {}
+}
+
 /// [`FileLoader`] that looks up modules by name from a [`HashMap`].
 ///
 /// A list of all load statements can be obtained through
@@ -52,3 +67,206 @@ impl<'a> FileLoader for ReturnFileLoader<'a> {
         }
     }
 }
+
+/// [`FileLoader`] that reads modules with `read`, evaluates them and caches the results
+/// in a [`FrozenModuleCache`], so a file loaded repeatedly is only parsed and evaluated once.
+///
+/// Modules are evaluated with `globals` and `dialect`, and their own `load()` statements
+/// are resolved with this same loader.
+pub struct CachedFileLoader<'a> {
+    /// Cache of the modules evaluated so far.
+    pub cache: &'a FrozenModuleCache,
+    /// Globals available to the loaded modules.
+    pub globals: &'a Globals,
+    /// Dialect used to parse the loaded modules.
+    pub dialect: &'a Dialect,
+    /// Return the source text of the module at `path`.
+    pub read: &'a dyn Fn(&str) -> anyhow::Result<String>,
+}
+
+impl<'a> FileLoader for CachedFileLoader<'a> {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        let source = (self.read)(path)?;
+        self.cache.get_or_insert_with(&source, self.dialect, || {
+            let ast = AstModule::parse(path, source.clone(), self.dialect)?;
+            let module = Module::new();
+            {
+                let mut eval = Evaluator::new(&module);
+                eval.set_loader(self);
+                eval.eval_module(ast, self.globals)?;
+            }
+            module.freeze()
+        })
+    }
+}
+
+/// A thread-safe cache of [`FrozenModule`]s, keyed by the source text and [`Dialect`].
+///
+/// Used by [`CachedFileLoader`], or by a custom [`FileLoader`], so that loading the same file
+/// repeatedly only parses and evaluates it once. The cache doesn't know about the [`Globals`]
+/// or loader used to evaluate a module, so a single cache should only be used with one of each.
+#[derive(Default)]
+pub struct FrozenModuleCache {
+    // Keyed by dialect first, so lookups can borrow the source rather than copy it.
+    modules: Mutex<HashMap<Dialect, HashMap<String, FrozenModule>>>,
+}
+
+impl FrozenModuleCache {
+    /// Create a new empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached module for `source` and `dialect`, or produce one with `f`
+    /// and cache it. Errors from `f` are not cached.
+    ///
+    /// The lock is not held while `f` runs, so `f` may itself use the cache (e.g. to
+    /// evaluate nested `load()` statements). If two threads race to produce the same module,
+    /// both call `f`, but only the first result is kept and returned to both.
+    pub fn get_or_insert_with(
+        &self,
+        source: &str,
+        dialect: &Dialect,
+        f: impl FnOnce() -> anyhow::Result<FrozenModule>,
+    ) -> anyhow::Result<FrozenModule> {
+        // Keep the whole source in the key, so different sources can never share a module.
+        if let Some(module) = self
+            .modules
+            .lock()
+            .unwrap()
+            .get(dialect)
+            .and_then(|modules| modules.get(source))
+        {
+            return Ok(module.dupe());
+        }
+        let module = f()?;
+        Ok(self
+            .modules
+            .lock()
+            .unwrap()
+            .entry(dialect.clone())
+            .or_default()
+            .entry(source.to_owned())
+            .or_insert(module)
+            .dupe())
+    }
+
+    /// Number of modules in the cache.
+    pub fn len(&self) -> usize {
+        self.modules
+            .lock()
+            .unwrap()
+            .values()
+            .map(HashMap::len)
+            .sum()
+    }
+
+    /// Is the cache empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all modules from the cache.
+    pub fn clear(&self) {
+        self.modules.lock().unwrap().clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    fn eval(source: &str, dialect: &Dialect) -> anyhow::Result<FrozenModule> {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let ast = AstModule::parse("cached.star", source.to_owned(), dialect)?;
+        eval.eval_module(ast, &Globals::standard())?;
+        module.freeze()
+    }
+
+    #[test]
+    fn test_frozen_module_cache() {
+        let cache = FrozenModuleCache::new();
+        let evals = Cell::new(0);
+        let load = |source: &str, dialect: &Dialect| {
+            cache
+                .get_or_insert_with(source, dialect, || {
+                    evals.set(evals.get() + 1);
+                    eval(source, dialect)
+                })
+                .unwrap()
+        };
+
+        let a = load("x = [1]", &Dialect::Standard);
+        let b = load("x = [1]", &Dialect::Standard);
+        assert_eq!(evals.get(), 1);
+        assert!(a.frozen_heap() == b.frozen_heap());
+        assert!(a
+            .get("x")
+            .unwrap()
+            .value()
+            .ptr_eq(b.get("x").unwrap().value()));
+
+        let c = load("x = [2]", &Dialect::Standard);
+        assert_eq!(evals.get(), 2);
+        assert!(a.frozen_heap() != c.frozen_heap());
+        assert_eq!(c.get("x").unwrap().value().to_repr(), "[2]");
+
+        load("x = [1]", &Dialect::Extended);
+        assert_eq!(evals.get(), 3);
+        assert_eq!(cache.len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_frozen_module_cache_error_not_cached() {
+        let cache = FrozenModuleCache::new();
+        let source = "x = ";
+        let res = cache.get_or_insert_with(source, &Dialect::Standard, || {
+            eval(source, &Dialect::Standard)
+        });
+        assert!(res.is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_cached_file_loader() {
+        let files = RefCell::new(HashMap::from([
+            ("a.star", "load('b.star', 'y')\nx = y".to_owned()),
+            ("b.star", "y = [1]".to_owned()),
+        ]));
+        let reads = Cell::new(0);
+        let read = |path: &str| -> anyhow::Result<String> {
+            reads.set(reads.get() + 1);
+            Ok(files.borrow()[path].clone())
+        };
+        let cache = FrozenModuleCache::new();
+        let globals = Globals::standard();
+        let loader = CachedFileLoader {
+            cache: &cache,
+            globals: &globals,
+            dialect: &Dialect::Extended,
+            read: &read,
+        };
+
+        let a = loader.load("a.star").unwrap();
+        let b = loader.load("b.star").unwrap();
+        // `b.star` was evaluated once, when `a.star` loaded it.
+        assert_eq!(cache.len(), 2);
+        assert!(a
+            .get("x")
+            .unwrap()
+            .value()
+            .ptr_eq(b.get("y").unwrap().value()));
+
+        files.borrow_mut().insert("b.star", "y = [2]".to_owned());
+        let b2 = loader.load("b.star").unwrap();
+        assert_eq!(b2.get("y").unwrap().value().to_repr(), "[2]");
+        assert_eq!(cache.len(), 3);
+        assert_eq!(reads.get(), 4);
+    }
+}
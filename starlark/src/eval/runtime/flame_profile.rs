@@ -56,6 +56,21 @@ enum Frame {
     Pop,
 }
 
+/// What the number at the end of each folded stack line measures.
+#[derive(Clone, Copy, Dupe)]
+pub(crate) enum FlameWeight {
+    /// Time spent in the stack, in milliseconds.
+    Time,
+    /// Number of times the stack was entered.
+    Calls,
+}
+
+impl Default for FlameWeight {
+    fn default() -> Self {
+        FlameWeight::Time
+    }
+}
+
 #[derive(Trace)]
 pub(crate) struct FlameProfile<'v>(Option<Box<FlameData<'v>>>);
 
@@ -64,6 +79,7 @@ pub(crate) struct FlameProfile<'v>(Option<Box<FlameData<'v>>>);
 /// Whenever we GC, regenerate map.
 #[derive(Default)]
 struct FlameData<'v> {
+    weight: FlameWeight,
     frames: Vec<(Frame, Instant)>,
     values: Vec<Value<'v>>,
     map: HashMap<ValuePtr, ValueIndex>,
@@ -83,6 +99,7 @@ unsafe impl<'v> Trace<'v> for FlameData<'v> {
 struct Stacks<'a> {
     name: &'a str,
     time: Duration,
+    calls: u64,
     children: HashMap<ValueIndex, Stacks<'a>>,
 }
 
@@ -91,6 +108,7 @@ impl<'a> Stacks<'a> {
         Stacks {
             name,
             time: Duration::default(),
+            calls: 0,
             children: HashMap::new(),
         }
     }
@@ -113,37 +131,47 @@ impl<'a> Stacks<'a> {
             *last_time = *time;
             match frame {
                 Frame::Pop => return,
-                Frame::Push(i) => match self.children.entry(*i) {
-                    Entry::Occupied(mut e) => e.get_mut().add(names, frames, last_time),
-                    Entry::Vacant(e) => e
-                        .insert(Stacks::blank(i.lookup(names).as_str()))
-                        .add(names, frames, last_time),
-                },
+                Frame::Push(i) => {
+                    let child = self
+                        .children
+                        .entry(*i)
+                        .or_insert_with(|| Stacks::blank(i.lookup(names).as_str()));
+                    child.calls += 1;
+                    child.add(names, frames, last_time);
+                }
             }
         }
     }
 
-    fn render_with_buffer(&self, file: &mut impl Write, buffer: &mut String) -> io::Result<()> {
+    fn render_with_buffer(
+        &self,
+        file: &mut impl Write,
+        buffer: &mut String,
+        weight: FlameWeight,
+    ) -> io::Result<()> {
         // Reuse the buffer to accumulate the stack name
         let start_len = buffer.len();
         if !buffer.is_empty() {
             buffer.push(';')
         }
         buffer.push_str(self.name);
-        let count = self.time.as_millis();
+        let count = match weight {
+            FlameWeight::Time => self.time.as_millis(),
+            FlameWeight::Calls => self.calls as u128,
+        };
         if count > 0 {
             writeln!(file, "{} {}", buffer, count)?;
         }
         for x in self.children.values() {
-            x.render_with_buffer(file, buffer)?;
+            x.render_with_buffer(file, buffer, weight)?;
         }
         buffer.truncate(start_len);
         Ok(())
     }
 
-    fn render(&self, mut file: impl Write) -> io::Result<()> {
+    fn render(&self, mut file: impl Write, weight: FlameWeight) -> io::Result<()> {
         let mut buffer = String::new();
-        self.render_with_buffer(&mut file, &mut buffer)
+        self.render_with_buffer(&mut file, &mut buffer, weight)
     }
 }
 
@@ -152,8 +180,11 @@ impl<'v> FlameProfile<'v> {
         Self(None)
     }
 
-    pub(crate) fn enable(&mut self) {
-        self.0 = Some(box FlameData::default());
+    pub(crate) fn enable(&mut self, weight: FlameWeight) {
+        self.0 = Some(box FlameData {
+            weight,
+            ..FlameData::default()
+        });
     }
 
     #[cold]
@@ -203,8 +234,45 @@ impl<'v> FlameProfile<'v> {
     fn write_profile_to(x: &FlameData, file: impl Write) -> io::Result<()> {
         // Need to write out lines which look like:
         // root;calls1;calls2 1
-        // All the numbers at the end must be whole numbers (we use milliseconds)
+        // All the numbers at the end must be whole numbers (milliseconds or call counts)
         let names = x.values.map(|x| x.to_repr());
-        Stacks::new(&names, &x.frames).render(file)
+        Stacks::new(&names, &x.frames).render(file, x.weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environment::{Globals, Module},
+        eval::{runtime::flame_profile::FlameProfile, Evaluator, ProfileMode},
+        syntax::{AstModule, Dialect},
+    };
+
+    #[test]
+    fn test_flame_calls() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        eval.enable_profile(&ProfileMode::FlameCalls);
+        let program = r#"
+def c():
+    pass
+def b():
+    c()
+    c()
+def a():
+    b()
+a()
+"#;
+        eval.eval_module(
+            AstModule::parse("flame.star", program.to_owned(), &Dialect::Standard).unwrap(),
+            &globals,
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        FlameProfile::write_profile_to(eval.flame_profile.0.as_ref().unwrap(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("root;a();b();c() 2\n"), "{:?}", out);
+        assert!(out.contains("root;a();b() 1\n"), "{:?}", out);
     }
 }
@@ -34,17 +34,23 @@ use gazebo::{any::AnyLifetime, prelude::*};
 use crate as starlark;
 use crate::{
     eval::runtime::csv::CsvWriter,
-    values::{Freeze, Freezer, Heap, NoSimpleValue, StarlarkValue, Trace, Value, ValueLike},
+    values::{
+        Freeze, Freezer, Heap, HeapSummary, NoSimpleValue, StarlarkValue, Trace, Value, ValueLike,
+    },
 };
 
 #[derive(Copy, Clone, Dupe, Debug)]
 pub(crate) enum HeapProfileFormat {
     Summary,
     FlameGraph,
+    ByType,
 }
 
 pub(crate) struct HeapProfile {
     enabled: bool,
+    /// Record function entry and exit on the heap, which all formats except
+    /// [`ByType`](HeapProfileFormat::ByType) need.
+    record_calls: bool,
 }
 
 /// A type which is either drop or non-drop.
@@ -154,17 +160,26 @@ impl FunctionIds {
 
 impl HeapProfile {
     pub(crate) fn new() -> Self {
-        Self { enabled: false }
+        Self {
+            enabled: false,
+            record_calls: false,
+        }
     }
 
     pub(crate) fn enable(&mut self) {
         self.enabled = true;
+        self.record_calls = true;
+    }
+
+    /// Enable profiling which only summarises the heap by type, so doesn't record calls.
+    pub(crate) fn enable_by_type(&mut self) {
+        self.enabled = true;
     }
 
     #[cold]
     #[inline(never)]
     pub(crate) fn record_call_enter<'v>(&self, function: Value<'v>, heap: &'v Heap) {
-        if self.enabled {
+        if self.record_calls {
             let time = Instant::now();
             assert!(mem::needs_drop::<CallEnter<NeedsDrop>>());
             assert!(!mem::needs_drop::<CallEnter<NoDrop>>());
@@ -184,7 +199,7 @@ impl HeapProfile {
     #[cold]
     #[inline(never)]
     pub(crate) fn record_call_exit<'v>(&self, heap: &'v Heap) {
-        if self.enabled {
+        if self.record_calls {
             let time = Instant::now();
             assert!(mem::needs_drop::<CallExit<NeedsDrop>>());
             assert!(!mem::needs_drop::<CallExit<NoDrop>>());
@@ -225,6 +240,7 @@ impl HeapProfile {
         match format {
             HeapProfileFormat::Summary => Self::write_summarized_heap_profile_to(file, heap),
             HeapProfileFormat::FlameGraph => Self::write_flame_heap_profile_to(file, heap),
+            HeapProfileFormat::ByType => Self::write_by_type_heap_profile_to(file, heap),
         }
         .with_context(|| {
             format!(
@@ -243,6 +259,48 @@ impl HeapProfile {
         Ok(())
     }
 
+    fn write_by_type_heap_profile_to(file: impl Write, heap: &Heap) -> anyhow::Result<()> {
+        Self::write_heap_summary_to(file, heap.allocated_summary())
+    }
+
+    /// Write a [`HeapSummary`] as CSV, one row per type.
+    pub(crate) fn write_heap_summary(filename: &Path, summary: HeapSummary) -> anyhow::Result<()> {
+        let file = File::create(filename).with_context(|| {
+            format!("When creating profile output file `{}`", filename.display())
+        })?;
+        Self::write_heap_summary_to(file, summary).with_context(|| {
+            format!(
+                "When writing to profile output file `{}`",
+                filename.display()
+            )
+        })
+    }
+
+    pub(crate) fn write_heap_summary_to(
+        mut file: impl Write,
+        summary: HeapSummary,
+    ) -> anyhow::Result<()> {
+        let mut summary: Vec<_> = summary.summary.into_iter().collect();
+        summary.sort_by(|(k1, (_, bytes1)), (k2, (_, bytes2))| {
+            bytes2.cmp(bytes1).then_with(|| k1.cmp(k2))
+        });
+        let mut csv = CsvWriter::new(["Type", "Count", "Bytes"]);
+        {
+            csv.write_display("TOTAL");
+            csv.write_value(summary.iter().map(|(_, (count, _))| count).sum::<usize>());
+            csv.write_value(summary.iter().map(|(_, (_, bytes))| bytes).sum::<usize>());
+            csv.finish_row();
+        }
+        for (typ, (count, bytes)) in &summary {
+            csv.write_value(typ.as_str());
+            csv.write_value(count);
+            csv.write_value(bytes);
+            csv.finish_row();
+        }
+        file.write_all(csv.finish().as_bytes())?;
+        Ok(())
+    }
+
     fn write_summarized_heap_profile_to(mut file: impl Write, heap: &Heap) -> anyhow::Result<()> {
         use summary::{FuncInfo, Info};
 
@@ -685,4 +743,58 @@ _ignore = str([1])     # allocate a string in non_drop
         // from drop heap
         assert_eq!(*total.allocs.get("dict").unwrap(), 1);
     }
+
+    #[test]
+    fn test_by_type() {
+        let ast = AstModule::parse(
+            "x.star",
+            "_ignore = [(i, i) for i in range(100)]".to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+
+        let globals = Globals::standard();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.enable_profile(&ProfileMode::HeapSummary);
+        eval.eval_module(ast, &globals).unwrap();
+
+        let mut out = Vec::new();
+        HeapProfile::write_by_type_heap_profile_to(&mut out, module.heap()).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.starts_with("Type,Count,Bytes\nTOTAL,"), "{:?}", csv);
+        assert!(csv.contains("\n\"tuple\",100,"), "{:?}", csv);
+        assert!(csv.contains("\n\"list\","), "{:?}", csv);
+    }
+
+    #[test]
+    fn test_retained() {
+        let ast = AstModule::parse(
+            "x.star",
+            r#"
+def make():
+    xs = [(i,) for i in range(50)]
+    return len(xs)
+n = make()
+kept = tuple([1, 2])
+"#
+            .to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+
+        let globals = Globals::standard();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.eval_module(ast, &globals).unwrap();
+        let frozen = module.freeze().unwrap();
+
+        let mut out = Vec::new();
+        HeapProfile::write_heap_summary_to(&mut out, frozen.frozen_heap().allocated_summary())
+            .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        // Only `kept` survives, the tuples and list made by `make` were garbage.
+        assert!(csv.contains("\n\"tuple\",1,"), "{:?}", csv);
+        assert!(!csv.contains("\"list\""), "{:?}", csv);
+    }
 }
@@ -27,13 +27,29 @@ pub enum ProfileMode {
     /// Like heap profile, but writes output comparible with
     /// [flamegraph.pl](https://github.com/brendangregg/FlameGraph/blob/master/flamegraph.pl).
     HeapFlame,
+    /// Report the number and size of the values on the heap for each type, as CSV.
+    /// Like [`Heap`](ProfileMode::Heap), enabling this mode disables garbage-collection,
+    /// so values which were allocated but are no longer referenced are also reported.
+    /// To see only the values which survive freezing, use
+    /// [`FrozenModule::write_retained_heap_profile`](crate::environment::FrozenModule::write_retained_heap_profile).
+    HeapSummary,
+    /// Report the bytes allocated on the heap by the statements starting on each source line,
+    /// as CSV, to find which lines allocate the most. Allocations made while calling a
+    /// function are attributed to the statements of that function. Like
+    /// [`Heap`](ProfileMode::Heap), enabling this mode disables garbage-collection.
+    HeapStmt,
     /// The statement profile mode provides information about time spent in each statement.
     Stmt,
     /// The bytecode profile mode provides information about bytecode instructions.
     Bytecode,
     /// The bytecode profile mode provides information about bytecode instruction pairs.
     BytecodePairs,
+    /// The bytecode coverage mode records which bytecode instructions were executed at least once.
+    BytecodeCoverage,
     /// Provide output compatible with
     /// [flamegraph.pl](https://github.com/brendangregg/FlameGraph/blob/master/flamegraph.pl).
     Flame,
+    /// Like [`Flame`](ProfileMode::Flame), but each stack is weighted by the number of calls
+    /// rather than the time spent, so the output is deterministic.
+    FlameCalls,
 }
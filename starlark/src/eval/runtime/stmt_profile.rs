@@ -55,10 +55,12 @@ impl FileId {
 #[derive(Clone)]
 struct StmtProfileData {
     files: HashMap<FileId, CodeMap>,
-    stmts: HashMap<(FileId, Span), (usize, Duration)>,
+    // Count, time and bytes allocated for each statement
+    stmts: HashMap<(FileId, Span), (usize, Duration, usize)>,
     next_file: FileId,
     last_span: (FileId, Span),
     last_start: Instant,
+    last_allocated: usize,
 }
 
 impl StmtProfileData {
@@ -69,32 +71,37 @@ impl StmtProfileData {
             next_file: FileId::EMPTY,
             last_span: (FileId::EMPTY, Span::default()),
             last_start: Instant::now(),
+            last_allocated: 0,
         }
     }
 
     // Add the data from last_span into the entries
-    fn add_last(&mut self, now: Instant) {
+    fn add_last(&mut self, now: Instant, allocated: usize) {
         let time = now - self.last_start;
+        // If a GC happened the heap may have shrunk, in which case we can't tell
+        let bytes = allocated.saturating_sub(self.last_allocated);
         match self.stmts.entry(self.last_span) {
             Entry::Occupied(mut x) => {
                 let v = x.get_mut();
                 v.0 += 1;
                 v.1 += time;
+                v.2 += bytes;
             }
             Entry::Vacant(x) => {
-                x.insert((1, time));
+                x.insert((1, time, bytes));
             }
         }
     }
 
-    fn before_stmt(&mut self, span: Span, codemap: &CodeMap) {
+    fn before_stmt(&mut self, span: Span, codemap: &CodeMap, allocated: usize) {
         let now = Instant::now();
-        self.add_last(now);
+        self.add_last(now, allocated);
         if self.last_span.0 != FileId::new(codemap) {
             self.add_codemap(codemap);
         }
         self.last_span = (self.next_file, span);
         self.last_start = now;
+        self.last_allocated = allocated;
     }
 
     fn add_codemap(&mut self, codemap: &CodeMap) {
@@ -133,7 +140,7 @@ impl StmtProfileData {
         // Clone first, since we don't want to impact the real timing with our odd
         // final execution finish.
         let mut data = self.clone();
-        data.add_last(now);
+        data.add_last(now, data.last_allocated);
 
         struct Item {
             span: FileSpan,
@@ -144,7 +151,7 @@ impl StmtProfileData {
         let mut items = Vec::with_capacity(data.stmts.len() - 1);
         let mut total_time = Duration::default();
         let mut total_count = 0;
-        for ((file, span), (count, time)) in data.stmts {
+        for ((file, span), (count, time, _)) in data.stmts {
             // EMPTY represents the first time special-case
             if file != FileId::EMPTY {
                 let span = data.files[&file].file_span(span);
@@ -174,6 +181,69 @@ impl StmtProfileData {
 
         Ok(())
     }
+
+    fn write_allocations(&self, filename: &Path, allocated: usize) -> anyhow::Result<()> {
+        let file = File::create(filename).with_context(|| {
+            format!(
+                "When creating allocation profile output file `{}`",
+                filename.display()
+            )
+        })?;
+        self.write_allocations_to(file, allocated).with_context(|| {
+            format!(
+                "When writing to allocation profile output file `{}`",
+                filename.display()
+            )
+        })
+    }
+
+    // Like `write_to`, but reports the bytes allocated, summed over all the statements
+    // which start on each line.
+    fn write_allocations_to(&self, mut file: impl Write, allocated: usize) -> anyhow::Result<()> {
+        let mut data = self.clone();
+        data.add_last(Instant::now(), allocated);
+
+        let mut lines: HashMap<(FileId, usize), (usize, usize)> = HashMap::new();
+        for ((file, span), (count, _, bytes)) in data.stmts {
+            // EMPTY represents the first time special-case
+            if file != FileId::EMPTY {
+                let line = data.files[&file].resolve_span(span).begin_line;
+                let v = lines.entry((file, line)).or_default();
+                v.0 += bytes;
+                v.1 += count;
+            }
+        }
+        let mut items: Vec<_> = lines.into_iter().collect();
+        items.sort_by_key(|((file, line), (bytes, _))| {
+            (
+                -(*bytes as i128),
+                data.files[file].filename().to_owned(),
+                *line,
+            )
+        });
+        let total_bytes: usize = items.iter().map(|(_, (bytes, _))| bytes).sum();
+        let total_count: usize = items.iter().map(|(_, (_, count))| count).sum();
+
+        let mut csv = CsvWriter::new(["File", "Line", "Allocated(bytes)", "Count"]);
+        csv.write_value("TOTAL");
+        csv.write_value("");
+        csv.write_value(total_bytes);
+        csv.write_value(total_count);
+        csv.finish_row();
+
+        for ((file, line), (bytes, count)) in items {
+            csv.write_value(data.files[&file].filename());
+            // Lines are reported 1-based, like the spans in the other profiles
+            csv.write_value(line + 1);
+            csv.write_value(bytes);
+            csv.write_value(count);
+            csv.finish_row();
+        }
+
+        file.write_all(csv.finish().as_bytes())?;
+
+        Ok(())
+    }
 }
 
 impl StmtProfile {
@@ -185,9 +255,14 @@ impl StmtProfile {
         self.0 = Some(box StmtProfileData::new())
     }
 
-    pub fn before_stmt(&mut self, span: FileSpanRef) {
+    pub fn enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    // `allocated` is the number of bytes currently allocated on the heap
+    pub fn before_stmt(&mut self, span: FileSpanRef, allocated: usize) {
         if let Some(box data) = &mut self.0 {
-            data.before_stmt(span.span, span.file)
+            data.before_stmt(span.span, span.file, allocated)
         }
     }
 
@@ -196,4 +271,56 @@ impl StmtProfile {
         let now = Instant::now();
         self.0.as_ref().map(|data| data.write(filename, now))
     }
+
+    // None = not applicable because not enabled
+    pub fn write_allocations(
+        &self,
+        filename: &Path,
+        allocated: usize,
+    ) -> Option<anyhow::Result<()>> {
+        self.0
+            .as_ref()
+            .map(|data| data.write_allocations(filename, allocated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::{
+        environment::{Globals, Module},
+        eval::{Evaluator, ProfileMode},
+        syntax::{AstModule, Dialect},
+    };
+
+    #[test]
+    fn test_heap_stmt() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        eval.enable_profile(&ProfileMode::HeapStmt);
+        let program = r#"
+x = 1
+big = [str(i) for i in range(1000)]
+y = [x]
+"#;
+        eval.eval_module(
+            AstModule::parse("alloc.star", program.to_owned(), &Dialect::Standard).unwrap(),
+            &globals,
+        )
+        .unwrap();
+        let file = env::temp_dir().join(format!("starlark_heap_stmt_{}.csv", process::id()));
+        eval.write_profile(&ProfileMode::HeapStmt, &file).unwrap();
+        let out = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+        let rows: Vec<Vec<&str>> = out.lines().map(|x| x.split(',').collect()).collect();
+        assert_eq!(rows[0], ["File", "Line", "Allocated(bytes)", "Count"]);
+        assert_eq!(rows[1][0], "\"TOTAL\"");
+        // Rows are sorted by allocation, so the line building the big list comes first
+        assert_eq!(rows[2][..2], ["\"alloc.star\"", "3"], "{:?}", out);
+        let total: usize = rows[1][2].parse().unwrap();
+        let big: usize = rows[2][2].parse().unwrap();
+        assert!(big * 10 > total * 9, "{:?}", out);
+    }
 }
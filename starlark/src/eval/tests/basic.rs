@@ -17,7 +17,9 @@
 
 //! Basic expression tests.
 
-use crate::{assert, assert::Assert, environment::GlobalsBuilder, values::OwnedFrozenValue};
+use crate::{
+    assert, assert::Assert, environment::GlobalsBuilder, syntax::Dialect, values::OwnedFrozenValue,
+};
 
 #[test]
 fn arithmetic_test() {
@@ -46,8 +48,13 @@ fn bitwise_test() {
 111 >> 2 == 27
 ~31 == -32
 ~-31 == 30
+~0 == -1
+~5 == -6
+~~7 == 7
 "#,
     );
+    assert::fail("~'x'", "not supported");
+    assert::fail("~1.5", "not supported");
 
     // For now, we report negative shift amounts as integer overflow
     assert::fail("1 << -13", "overflow");
@@ -194,7 +201,7 @@ xs == ys
 #[test]
 fn test_not_in_unhashable() {
     // Note that [] can't be hashed
-    assert::fail("[] not in {123: 456}", "not hashable");
+    assert::fail("[] not in {123: 456}", "unhashable type: list");
 }
 
 #[test]
@@ -205,7 +212,7 @@ x = {}
 y = {}
 x[y] = 1
 "#,
-        "not hashable",
+        "unhashable type: dict",
     );
     assert::fail(
         r#"
@@ -213,7 +220,7 @@ x = {'x': 1}
 y = {}
 x.get(y)
 "#,
-        "not hashable",
+        "unhashable type: dict",
     );
     assert::fail(
         r#"
@@ -221,6 +228,45 @@ x = {'x': 1}
 y = {}
 x[y]
 "#,
-        "not hashable",
+        "unhashable type: dict",
+    );
+}
+
+#[test]
+fn test_is() {
+    assert::all_true(
+        r#"
+not ([] is [])
+[] is not []
+x = []; x is x
+x = []; not (x is not x)
+None is None
+1 is 1
+True is not False
+"#,
+    );
+
+    let mut a = Assert::new();
+    a.dialect(&Dialect::Standard);
+    a.fail("x = []\nx is x", "`is` is not allowed");
+}
+
+#[test]
+fn test_string_literal_interning() {
+    let mut a = Assert::new();
+    a.setup_eval(|eval| eval.enable_string_literal_interning());
+    a.is_true(
+        r#"
+x = "some literal"
+y = "some literal"
+x is y
+"#,
+    );
+    assert::is_true(
+        r#"
+x = "some literal"
+y = "some literal"
+x is not y
+"#,
     );
 }
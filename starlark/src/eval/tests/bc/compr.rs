@@ -38,6 +38,22 @@ fn test_no_loop_if_top_collection_is_empty_on_freeze() {
     );
 }
 
+#[test]
+fn test_no_loop_if_top_collection_is_empty_dict() {
+    test_instrs(
+        &[BcOpcode::DictNew, BcOpcode::Return],
+        "def test(): return {k: v for k, v in []}",
+    );
+}
+
+#[test]
+fn test_no_loop_if_top_collection_is_empty_dict_on_freeze() {
+    test_instrs(
+        &[BcOpcode::DictNew, BcOpcode::Return],
+        "def test(): return {k: v for k, v in D}\nD = {}",
+    );
+}
+
 #[test]
 fn test_if_true_clause() {
     test_instrs(
@@ -45,8 +61,8 @@ fn test_if_true_clause() {
             BcOpcode::ListNew,
             BcOpcode::LoadLocal,
             BcOpcode::ForLoop,
+            BcOpcode::Dup,
             BcOpcode::StoreLocal,
-            BcOpcode::LoadLocal,
             BcOpcode::ComprListAppend,
             BcOpcode::Continue,
             BcOpcode::Return,
@@ -62,8 +78,8 @@ fn test_if_true_clause_on_freeze() {
             BcOpcode::ListNew,
             BcOpcode::LoadLocal,
             BcOpcode::ForLoop,
+            BcOpcode::Dup,
             BcOpcode::StoreLocal,
-            BcOpcode::LoadLocal,
             BcOpcode::ComprListAppend,
             BcOpcode::Continue,
             BcOpcode::Return,
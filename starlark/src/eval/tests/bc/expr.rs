@@ -81,3 +81,89 @@ fn test_call_maybe_known_method() {
         "def test(x): x.append(1)",
     );
 }
+
+#[test]
+fn test_tuple_of_consts() {
+    // Tuple literals of constants are folded into a single frozen tuple.
+    bc::test_instrs(&[BcOpcode::ReturnConst], "def test(): return (1, 2, 3)");
+    bc::test_instrs(
+        &[BcOpcode::ReturnConst],
+        "def test(): return ((1, 'a'), (None,))",
+    );
+    assert::is_true(
+        r#"
+def test():
+    return (1, 2, 3)
+test() is test()
+"#,
+    );
+}
+
+#[test]
+fn test_store_local_load_local() {
+    // Load of just stored local is replaced with `Dup` before the store.
+    bc::test_instrs(
+        &[
+            BcOpcode::LoadLocal,
+            BcOpcode::Len,
+            BcOpcode::Dup,
+            BcOpcode::StoreLocal,
+            BcOpcode::Return,
+        ],
+        "def test(x):\n  y = len(x)\n  return y",
+    );
+    // Load of different local is not affected.
+    bc::test_instrs(
+        &[
+            BcOpcode::LoadLocal,
+            BcOpcode::StoreLocal,
+            BcOpcode::LoadLocal,
+            BcOpcode::Return,
+        ],
+        "def test(x, z):\n  y = x\n  return z",
+    );
+    assert::pass(
+        r#"
+def test(x):
+    y = x + 1
+    z = y * 2
+    return z + y
+assert_eq(test(1), 6)
+"#,
+    );
+}
+
+#[test]
+fn test_len_of_consts() {
+    bc::test_instrs(
+        &[BcOpcode::ReturnConst],
+        "def test(): return len((1, 2, 3))",
+    );
+    bc::test_instrs(&[BcOpcode::ReturnConst], "def test(): return len('abc')");
+    // `len` is only folded when it refers to the builtin.
+    assert::pass(
+        r#"
+def len(x):
+    return 42
+def test():
+    return len("abc")
+assert_eq(test(), 42)
+"#,
+    );
+}
+
+#[test]
+fn test_store_local_then_const() {
+    // Consts queued after a queued store must not break writing the store.
+    assert::pass(
+        r#"
+def test():
+    a = 1
+    b = 2
+    c = a
+    d = "x"
+    return (a, b, c, d)
+assert_eq(test(), (1, 2, 1, "x"))
+"#,
+    );
+}
@@ -39,3 +39,42 @@ pub(crate) fn test_instrs(expected: &[BcOpcode], def_program: &str) {
     assert_eq!(Some(BcOpcode::End), opcodes.pop());
     assert_eq!(expected, opcodes);
 }
+
+#[test]
+fn test_disassemble() {
+    fn disassemble(program: &str) -> Vec<String> {
+        Assert::new()
+            .module("disassemble.star", program)
+            .get("test")
+            .unwrap()
+            .disassemble()
+            .unwrap()
+    }
+
+    assert_eq!(
+        vec!["ReturnConst 3"],
+        disassemble("def test(): return 1 + 2")
+    );
+    assert_eq!(
+        vec!["LoadLocalAndConst &0 3", "Add", "Return"],
+        disassemble("def test(x): return x + (1 + 2)")
+    );
+    let mut a = Assert::new();
+    let module = a.module("disassemble.star", "x = 1\ntest = len");
+    assert_eq!(None, module.get("x").unwrap().disassemble());
+    assert_eq!(None, module.get("test").unwrap().disassemble());
+}
+
+#[test]
+fn test_disassemble_pretty() {
+    let dump = Assert::new()
+        .module("pretty.star", "def test(x):\n  y = x + 1\n  return y")
+        .get("test")
+        .unwrap()
+        .disassemble_pretty()
+        .unwrap();
+    assert!(dump.starts_with("   0: LoadLocalAndConst &0 1"), "{}", dump);
+    assert!(dump.contains(": Add  # pretty.star:2:"), "{}", dump);
+    assert!(dump.contains(": Return  # pretty.star:3:"), "{}", dump);
+    assert_eq!(5, dump.lines().count(), "{}", dump);
+}
@@ -15,7 +15,9 @@
  * limitations under the License.
  */
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+
+use gazebo::prelude::*;
 
 use crate::{
     codemap::FileSpanRef,
@@ -45,3 +47,39 @@ f()            # 2
     evaluator.eval_module(ast, &globals).unwrap();
     assert_eq!(4, counter.get());
 }
+
+#[test]
+fn before_stmt_spans_and_locals() {
+    let module = Module::new();
+    let globals = Globals::new();
+    let mut evaluator = Evaluator::new(&module);
+    let seen = RefCell::new(Vec::new());
+    let before_stmt = |span: FileSpanRef, eval: &mut Evaluator<'_, '_>| {
+        let mut locals: Vec<String> = eval.local_variables().keys().cloned().collect();
+        locals.sort();
+        seen.borrow_mut()
+            .push((span.resolve_span().begin_line, locals));
+    };
+    evaluator.before_stmt(&before_stmt);
+
+    let program = "\
+x = 1
+def f(y):
+  z = y + x
+  return z
+f(2)
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Extended).unwrap();
+    evaluator.eval_module(ast, &globals).unwrap();
+    let locals = |xs: &[&str]| xs.map(|x| (*x).to_owned());
+    assert_eq!(
+        vec![
+            (0, locals(&[])),
+            (1, locals(&["x"])),
+            (4, locals(&["f", "x"])),
+            (2, locals(&["y"])),
+            (3, locals(&["y", "z"])),
+        ],
+        seen.into_inner()
+    );
+}
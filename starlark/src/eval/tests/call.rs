@@ -17,7 +17,14 @@
 
 //! Test call expression and parameter binding.
 
-use crate::{assert, assert::Assert};
+use crate::{
+    assert,
+    assert::Assert,
+    environment::{Globals, Module},
+    errors::Diagnostic,
+    eval::Evaluator,
+    syntax::{AstModule, Dialect},
+};
 
 #[test]
 fn funcall_test() {
@@ -242,3 +249,72 @@ f(1)
         "Missing parameter `y`",
     );
 }
+
+#[test]
+fn test_keyword_only_arguments() {
+    let f = "def f(a, *, b):\n  return (a, b)\n";
+    assert::pass(&format!("{}assert_eq(f(1, b=2), (1, 2))", f));
+    assert::pass(&format!("{}assert_eq(f(b=2, a=1), (1, 2))", f));
+    assert::fail(&format!("{}f(1, 2)", f), "extra positional");
+    assert::fail(&format!("{}f(1)", f), "Missing parameter `b`");
+    assert::fail(
+        "def bad(a, *):\n  pass",
+        "Bare `*` must be followed by a named parameter",
+    );
+    assert::fail(
+        "def bad(a, *, **kwargs):\n  pass",
+        "Bare `*` must be followed by a named parameter",
+    );
+
+    // Keyword-only arguments are not part of standard Starlark
+    let mut a = Assert::new();
+    a.dialect(&Dialect::Standard);
+    a.parse_fail("def f(a, !*!, b):\n  pass");
+}
+
+#[test]
+fn test_positional_only_arguments() {
+    let f = "def f(a, b=2, /, c=3):\n  return (a, b, c)\n";
+    assert::pass(&format!("{}assert_eq(f(1), (1, 2, 3))", f));
+    assert::pass(&format!("{}assert_eq(f(1, 4, 5), (1, 4, 5))", f));
+    assert::pass(&format!("{}assert_eq(f(1, c=5), (1, 2, 5))", f));
+    assert::fail(&format!("{}f(a=1)", f), "Missing parameter `a`");
+    assert::fail(
+        &format!("{}f(1, b=4)", f),
+        "extra named parameter(s) for call",
+    );
+    // With `**kwargs`, the names of positional-only parameters are free to use
+    assert::pass(
+        r#"
+def g(a, /, **kwargs):
+    return (a, kwargs)
+assert_eq(g(1, a=2), (1, {"a": 2}))
+assert_eq((lambda x, /: x)(3), 3)
+"#,
+    );
+}
+
+#[test]
+fn test_max_call_depth() {
+    fn run(program: &str, depth: Option<usize>) -> anyhow::Result<()> {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        if let Some(depth) = depth {
+            eval.set_max_call_depth(depth);
+        }
+        let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Extended)?;
+        eval.eval_module(ast, &Globals::standard())?;
+        Ok(())
+    }
+
+    // Unbounded recursion stops at the default limit
+    let err = run("def f(n): return f(n + 1)\nf(0)", None).unwrap_err();
+    assert!(err.to_string().contains("maximum call depth of 40"));
+    let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+    assert!(diagnostic.call_stack.len() > 30);
+
+    let countdown = "def f(n): return 0 if n == 0 else f(n - 1)\n";
+    run(&format!("{}f(5)", countdown), Some(10)).unwrap();
+    let err = run(&format!("{}f(20)", countdown), Some(10)).unwrap_err();
+    assert!(err.to_string().contains("maximum call depth of 10"));
+}
@@ -17,7 +17,14 @@
 
 //! Test for `def` and `lambda`.
 
-use crate::{assert, assert::Assert, environment::Module, eval::Evaluator};
+use crate::{
+    assert,
+    assert::Assert,
+    environment::{Globals, Module},
+    errors::Diagnostic,
+    eval::Evaluator,
+    syntax::{AstModule, Dialect},
+};
 
 #[test]
 fn test_lambda() {
@@ -187,3 +194,40 @@ fn test_lambda_errors() {
     // Test from https://github.com/facebookexperimental/starlark-rust/issues/36
     assert::fail("lambda a,a:a", "duplicated parameter name");
 }
+
+#[test]
+fn test_type_annotations_enforced() {
+    let program = r#"
+def f(x: int.type) -> str.type:
+    return str(x)
+"#;
+    let run = |call: &str| {
+        let ast = AstModule::parse(
+            "types.star",
+            format!("{}{}", program, call),
+            &Dialect {
+                enable_types: true,
+                ..Dialect::Standard
+            },
+        )
+        .unwrap();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.eval_module(ast, &Globals::standard())
+            .map(|v| v.to_str())
+    };
+
+    assert_eq!(run("f(1)").unwrap(), "1");
+
+    let err = run("f('x')").unwrap_err();
+    let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+    assert!(
+        diagnostic
+            .message
+            .to_string()
+            .contains("does not match the type annotation `int` for argument `x`"),
+        "{:#}",
+        err
+    );
+    assert!(diagnostic.span.is_some(), "{:#}", err);
+}
@@ -30,7 +30,7 @@ use crate as starlark;
 use crate::{
     assert,
     assert::Assert,
-    environment::{GlobalsBuilder, Module},
+    environment::{Globals, GlobalsBuilder, Module},
     eval::Evaluator,
     syntax::{AstModule, Dialect},
     values::{any::StarlarkAny, none::NoneType, Freeze, NoSerialize, StarlarkValue, Value},
@@ -429,6 +429,147 @@ fn test_derive_attrs() {
     a.eq("example.nested.foo", "\"bar\"");
 }
 
+#[test]
+fn test_derive_record() {
+    use crate::values::{AllocValue, UnpackValue};
+
+    #[derive(Debug, PartialEq, AllocValue, UnpackValue)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn point(x: i32, y: i32) -> anyhow::Result<Point> {
+            Ok(Point { x, y })
+        }
+
+        fn flip(p: Point) -> anyhow::Result<Point> {
+            Ok(Point { x: p.y, y: p.x })
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.eq("point(1, 2)", "{'x': 1, 'y': 2}");
+    a.eq("flip(point(1, 2))", "{'x': 2, 'y': 1}");
+    a.eq("flip({'y': 4, 'x': 3})", "{'x': 4, 'y': 3}");
+    a.eq("flip(struct(x = 5, y = 6))", "{'x': 6, 'y': 5}");
+    const BAD: &str = "dict or struct with fields x, y";
+    a.fail("flip({'x': 1})", BAD);
+    a.fail("flip({'x': 1, 'y': 2, 'z': 3})", BAD);
+    a.fail("flip({'x': 1, 'y': 'a'})", BAD);
+
+    // Round trip through a Starlark dict, from Rust
+    let module = Module::new();
+    let heap = module.heap();
+    let value = heap.alloc(Point { x: 7, y: 8 });
+    assert_eq!(value.to_repr(), "{\"x\": 7, \"y\": 8}");
+    assert_eq!(Point::unpack_value(value), Some(Point { x: 7, y: 8 }));
+}
+
+#[test]
+fn test_generic_methods() {
+    use crate::environment::{Methods, MethodsBuilder, MethodsStatic};
+
+    trait Shape {
+        fn area(&self) -> i32;
+        fn kind(&self) -> String;
+    }
+
+    #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+    #[display(fmt = "square")]
+    struct Square(i32);
+    starlark_simple_value!(Square);
+    impl<'v> StarlarkValue<'v> for Square {
+        starlark_type!("square");
+
+        fn get_methods(&self) -> Option<&'static Methods> {
+            static RES: MethodsStatic = MethodsStatic::new();
+            RES.methods(shape_methods::<Square>)
+        }
+    }
+    impl Shape for Square {
+        fn area(&self) -> i32 {
+            self.0 * self.0
+        }
+        fn kind(&self) -> String {
+            "square".to_owned()
+        }
+    }
+
+    #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+    #[display(fmt = "rect")]
+    struct Rect(i32, i32);
+    starlark_simple_value!(Rect);
+    impl<'v> StarlarkValue<'v> for Rect {
+        starlark_type!("rect");
+
+        fn get_methods(&self) -> Option<&'static Methods> {
+            static RES: MethodsStatic = MethodsStatic::new();
+            RES.methods(shape_methods::<Rect>)
+        }
+    }
+    impl Shape for Rect {
+        fn area(&self) -> i32 {
+            self.0 * self.1
+        }
+        fn kind(&self) -> String {
+            "rect".to_owned()
+        }
+    }
+
+    // The same methods are shared by both types
+    #[starlark_module]
+    fn shape_methods<T: Shape>(builder: &mut MethodsBuilder) {
+        fn area(this: &T) -> anyhow::Result<i32> {
+            Ok(this.area())
+        }
+
+        fn scaled_area(this: &T, factor: i32) -> anyhow::Result<i32> {
+            Ok(this.area() * factor)
+        }
+
+        #[starlark(attribute)]
+        fn kind(this: &T) -> anyhow::Result<String> {
+            Ok(this.kind())
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|gb| {
+        gb.set("square", Square(3));
+        gb.set("rect", Rect(2, 5));
+    });
+    a.eq("square.area()", "9");
+    a.eq("rect.area()", "10");
+    a.eq("square.scaled_area(2)", "18");
+    a.eq("rect.scaled_area(factor = 2)", "20");
+    a.eq("square.kind", "'square'");
+    a.eq("rect.kind", "'rect'");
+    a.eq("dir(rect)", "['area', 'kind', 'scaled_area']");
+}
+
+#[test]
+fn test_rename() {
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        // `self` is a Rust keyword which can't be written even as `r#self`
+        #[starlark(rename = "self")]
+        fn self_(#[starlark(rename = "type")] typ: &str, _x: i32) -> anyhow::Result<String> {
+            Ok(format!("{} {}", typ, _x))
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.eq("self('str', 1)", "'str 1'");
+    a.eq("self(type = 'str', x = 1)", "'str 1'");
+    a.fail("self(typ = 'str', x = 1)", "typ");
+    a.fail("self_('str', 1)", "self_");
+}
+
 #[test]
 fn test_eval_function() {
     let fun = assert::pass(
@@ -446,3 +587,175 @@ fun
         .unwrap();
     assert_eq!(v.unpack_str(), Some("(8, \"hello\", 1)"))
 }
+
+#[test]
+fn test_module_get_typed() {
+    let env = Module::new();
+    let mut eval = Evaluator::new(&env);
+    let ast = AstModule::parse(
+        "typed.star",
+        "answer = 42\nname = 'world'\n_private = 1".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    eval.eval_module(ast, &Globals::standard()).unwrap();
+
+    assert_eq!(env.get_typed::<i32>("answer").unwrap(), Some(42));
+    assert_eq!(
+        env.get_typed::<String>("name").unwrap(),
+        Some("world".to_owned())
+    );
+    assert_eq!(env.get_typed::<i32>("missing").unwrap(), None);
+    assert_eq!(env.get_typed::<i32>("_private").unwrap(), None);
+
+    let err = env.get_typed::<i32>("name").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Module variable `name` has type `string`, but expected `int`"
+    );
+}
+
+#[test]
+fn test_module_set_before_eval() {
+    let env = Module::new();
+    env.set_typed("ports", vec![80, 443]);
+    env.set_typed("name", "server");
+    // Injected values shadow globals of the same name.
+    env.set_typed("len", 7);
+    let mut eval = Evaluator::new(&env);
+    let ast = AstModule::parse(
+        "config.star",
+        "config = {'name': name.upper(), 'ports': [p + 1 for p in ports], 'len': len}".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    eval.eval_module(ast, &Globals::standard()).unwrap();
+    assert_eq!(
+        env.get("config").unwrap().to_repr(),
+        r#"{"name": "SERVER", "ports": [81, 444], "len": 7}"#
+    );
+}
+
+#[test]
+fn test_extra_value() -> anyhow::Result<()> {
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn environment_name() -> anyhow::Result<Value<'v>> {
+            let context = eval.extra_value().unwrap();
+            context.at(Value::new_int(0), heap)
+        }
+    }
+
+    let modu = Module::new();
+    let globals = GlobalsBuilder::extended().with(module).build();
+    let mut eval = Evaluator::new(&modu);
+    assert!(eval.extra_value().is_none());
+    let context = modu.heap().alloc(vec!["prod", "eu-west"]);
+    eval.set_extra_value(context);
+    // The context is a GC root, so survives collections during evaluation
+    eval.trigger_gc();
+    let res = eval.eval_module(
+        AstModule::parse(
+            "a",
+            "x = [str(i) for i in range(100)]\nenvironment_name()".to_owned(),
+            &Dialect::Extended,
+        )?,
+        &globals,
+    )?;
+    assert_eq!(res.unpack_str(), Some("prod"));
+    assert_eq!(
+        eval.extra_value().unwrap().to_repr(),
+        r#"["prod", "eu-west"]"#
+    );
+    Ok(())
+}
+
+#[test]
+fn test_extra_value_downcast() -> anyhow::Result<()> {
+    #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+    #[display(fmt = "host_context")]
+    struct HostContext {
+        environment: String,
+        shards: i32,
+    }
+    starlark_simple_value!(HostContext);
+    impl<'v> StarlarkValue<'v> for HostContext {
+        starlark_type!("host_context");
+    }
+
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn shard_name(index: i32) -> anyhow::Result<String> {
+            let context = eval.extra_value_downcast::<HostContext>().unwrap();
+            Ok(format!(
+                "{}-{}/{}",
+                context.environment, index, context.shards
+            ))
+        }
+    }
+
+    let modu = Module::new();
+    let globals = GlobalsBuilder::extended().with(module).build();
+    let mut eval = Evaluator::new(&modu);
+    assert!(eval.extra_value_downcast::<HostContext>().is_none());
+    eval.set_extra_value(modu.heap().alloc(HostContext {
+        environment: "prod".to_owned(),
+        shards: 4,
+    }));
+    // The wrong type gives `None`
+    assert!(eval.extra_value_downcast::<NoneType>().is_none());
+    let res = eval.eval_module(
+        AstModule::parse("a", "shard_name(2)".to_owned(), &Dialect::Extended)?,
+        &globals,
+    )?;
+    assert_eq!(res.unpack_str(), Some("prod-2/4"));
+    Ok(())
+}
+
+#[test]
+fn test_invoke_custom_type() {
+    use crate::eval::Arguments;
+
+    #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+    #[display(fmt = "adder")]
+    struct Adder(i32);
+    starlark_simple_value!(Adder);
+    impl<'v> StarlarkValue<'v> for Adder {
+        starlark_type!("adder");
+
+        fn invoke(
+            &self,
+            _me: Value<'v>,
+            args: &Arguments<'v, '_>,
+            eval: &mut Evaluator<'v, '_>,
+        ) -> anyhow::Result<Value<'v>> {
+            args.no_named_args()?;
+            let [x, y] = args.positional(eval.heap())?;
+            x.add(y, eval.heap())?
+                .add(Value::new_int(self.0), eval.heap())
+        }
+    }
+
+    #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+    #[display(fmt = "inert")]
+    struct Inert;
+    starlark_simple_value!(Inert);
+    impl<'v> StarlarkValue<'v> for Inert {
+        starlark_type!("inert");
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|gb| {
+        gb.set("add_ten", Adder(10));
+        gb.set("inert", Inert);
+    });
+    a.eq("add_ten(1, 2)", "13");
+    a.eq("[add_ten(x, 1) for x in [1, 2]]", "[12, 13]");
+    a.is_true("def f(g): return g(1, 2)\nf(add_ten) == 13");
+    a.fail("add_ten(1)", "Wrong number of positional parameters");
+    a.fail("add_ten(1, y = 2)", "extra named parameter");
+    a.fail(
+        "inert(1, 2)",
+        "Operation `call()` not supported on type `inert`",
+    );
+}
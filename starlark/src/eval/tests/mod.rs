@@ -233,6 +233,17 @@ x = orig
 x += [3]
 assert_eq(x, [1, 2, 3])
 assert_eq(orig, [1, 2, 3])
+"#,
+    );
+    assert::pass(
+        r#"
+x = [1]
+orig = x
+x += [2]
+assert_true(x is orig)
+x += x
+assert_true(x is orig)
+assert_eq(orig, [1, 2, 1, 2])
 "#,
     );
     assert::pass(
@@ -494,6 +505,33 @@ fn test_load_reexport() {
     );
 }
 
+#[test]
+fn test_load_reexport_frozen_names() {
+    let mut a = Assert::new();
+    a.dialect_set(|d| d.enable_load_reexport = true);
+    a.module("a", "x = 1\ny = 2");
+    let b = a.module("b", "load('a', 'x', z = 'y')\nw = 3");
+    a.pass("load('b', 'x', 'z')\nassert_eq(x, 1)\nassert_eq(z, 2)");
+    let mut names = b.names().collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, vec!["w", "x", "z"]);
+    assert!(b.get("x").is_some());
+
+    let mut a = Assert::new();
+    a.dialect_set(|d| d.enable_load_reexport = false);
+    a.module("a", "x = 1\ny = 2");
+    let b = a.module("b", "load('a', 'x', z = 'y')\nw = 3");
+    a.fail("load('b', 'z')", "Module symbol `z` is not exported");
+    a.pass("load('b', 'w')\nassert_eq(w, 3)");
+    assert_eq!(b.names().collect::<Vec<_>>(), vec!["w"]);
+    assert!(b.get("x").is_none());
+    // Still visible to the code in the module itself
+    assert_eq!(
+        b.get_any_visibility("x").unwrap().0.value().unpack_int(),
+        Some(1)
+    );
+}
+
 #[test]
 fn test_module_visibility_preserved_by_evaluator() -> anyhow::Result<()> {
     // Make sure that when we use a module in the evaluator, the entering / exiting the
@@ -807,14 +845,14 @@ xs == {1: 2}
 xs = {}
 xs[xs]
 "#,
-        "not hashable",
+        "unhashable type: dict",
     );
     assert::fail(
         r#"
 xs = {}
 xs[xs] = 1
 "#,
-        "not hashable",
+        "unhashable type: dict",
     );
     assert::is_true(
         r#"
@@ -861,7 +899,7 @@ def f():
 load('m.star', 'f')
 f()
     ",
-        "Value of type `list` is not hashable",
+        "unhashable type: list",
     );
 }
 
@@ -102,3 +102,25 @@ def test():
 "#,
     );
 }
+
+#[test]
+fn test_code_after_terminal_if_optimized_away() {
+    test_instrs(
+        &[
+            BcOpcode::LoadLocal,
+            BcOpcode::IfNotBr,
+            BcOpcode::ReturnConst,
+            BcOpcode::Br,
+            BcOpcode::ReturnConst,
+            BcOpcode::ReturnConst,
+        ],
+        r#"
+def test(x):
+    if x:
+        return 1
+    else:
+        return 2
+    fail("unreachable")
+"#,
+    );
+}
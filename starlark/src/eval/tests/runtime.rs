@@ -18,8 +18,11 @@
 //! Test of runtime.
 
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     mem,
     sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
 };
 
 use derive_more::Display;
@@ -29,8 +32,10 @@ use crate as starlark;
 use crate::{
     assert,
     assert::Assert,
-    environment::GlobalsBuilder,
-    values::{any::StarlarkAny, FrozenHeap, Heap},
+    environment::{Globals, GlobalsBuilder, Module},
+    eval::{Evaluator, LoadHandler, ProfileMode, ReturnFileLoader},
+    syntax::{AstModule, Dialect},
+    values::{any::StarlarkAny, none::NoneType, FrozenHeap, Heap, Value},
 };
 
 #[test]
@@ -145,7 +150,7 @@ fn test_garbage_collect_happens() {
         }
 
         fn is_gc_disabled() -> anyhow::Result<bool> {
-            Ok(eval.disable_gc)
+            Ok(eval.gc_disabled())
         }
     }
 
@@ -187,6 +192,143 @@ def expensive(n):
     assert!(!a.pass(&code).unpack_bool().unwrap());
 }
 
+#[test]
+fn test_disable_enable_gc() {
+    static DROPPED: Lazy<AtomicUsize> = Lazy::new(|| AtomicUsize::new(0));
+
+    #[derive(Default, Debug, Display)]
+    struct Dealloc;
+
+    impl Drop for Dealloc {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[starlark_module]
+    fn globals(builder: &mut GlobalsBuilder) {
+        fn mk() -> anyhow::Result<StarlarkAny<Dealloc>> {
+            Ok(StarlarkAny::new(Dealloc))
+        }
+
+        fn dropped() -> anyhow::Result<i32> {
+            Ok(DROPPED.load(Ordering::SeqCst) as i32)
+        }
+
+        fn disable_gc() -> anyhow::Result<NoneType> {
+            eval.disable_gc();
+            Ok(NoneType)
+        }
+
+        fn enable_gc() -> anyhow::Result<NoneType> {
+            eval.enable_gc();
+            Ok(NoneType)
+        }
+
+        // Only safe to call at the top-level of a module
+        fn garbage_collect() -> anyhow::Result<NoneType> {
+            eval.trigger_gc();
+            Ok(NoneType)
+        }
+    }
+
+    // Each statement is at the top-level, so GC can happen between any of them
+    let program = r#"
+disable_gc()
+xs = [mk(), mk(), mk()]
+xs = None
+garbage_collect()
+before = dropped()
+enable_gc()
+after = dropped()
+[before, after]
+"#;
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    let globals = GlobalsBuilder::new().with(globals).build();
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Standard).unwrap();
+    let res = eval.eval_module(ast, &globals).unwrap();
+    assert_eq!(res.to_repr(), "[0, 3]");
+}
+
+#[test]
+fn test_enable_gc_overridden() {
+    #[starlark_module]
+    fn globals(builder: &mut GlobalsBuilder) {
+        fn current_usage() -> anyhow::Result<i32> {
+            Ok(heap.allocated_bytes() as i32)
+        }
+
+        fn enable_gc() -> anyhow::Result<NoneType> {
+            eval.enable_gc();
+            Ok(NoneType)
+        }
+
+        // Only safe to call at the top-level of a module
+        fn garbage_collect() -> anyhow::Result<NoneType> {
+            eval.trigger_gc();
+            Ok(NoneType)
+        }
+
+        fn debug_evaluate(code: String) -> anyhow::Result<Value<'v>> {
+            let ast = AstModule::parse("interactive", code, &Dialect::Standard)?;
+            eval.eval_statements(ast)
+        }
+    }
+
+    // Returns whether the garbage was collected after calling `enable_gc`
+    let program = r#"
+xs = [str(i) for i in range(1000)]
+xs = None
+enable_gc()
+before = current_usage()
+garbage_collect()
+after = current_usage()
+after < before
+"#;
+    let run = |setup: &dyn Fn(&mut Evaluator), program: &str| {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        setup(&mut eval);
+        let globals = GlobalsBuilder::standard().with(globals).build();
+        let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Standard).unwrap();
+        let res = eval.eval_module(ast, &globals).unwrap();
+        res.unpack_bool().unwrap()
+    };
+
+    assert!(run(&|_| {}, program));
+    assert!(!run(
+        &|eval| eval.enable_profile(&ProfileMode::Heap),
+        program
+    ));
+    assert!(!run(
+        &|eval| eval.enable_profile(&ProfileMode::HeapStmt),
+        program
+    ));
+    // The debugger's `eval_statements` must never collect, even when asked to
+    assert!(!run(&|_| {}, &format!("debug_evaluate({:?})", program)));
+
+    let mut a = Assert::new();
+    a.disable_gc();
+    a.globals_add(globals);
+    a.is_true(program);
+}
+
+#[test]
+fn test_frozen_heap_size() {
+    let a = Assert::new();
+    let small = a.pass_module("x = 1");
+    let large = a.pass_module("x = ['item ' + str(i) for i in range(1000)]");
+    let small_bytes = small.frozen_heap().allocated_bytes();
+    let large_bytes = large.frozen_heap().allocated_bytes();
+    assert!(large_bytes > small_bytes + 1000 * "item 999".len());
+
+    let summary = large.frozen_heap().allocated_summary().summary;
+    let (count, bytes) = summary["string"];
+    assert!(count >= 1000);
+    assert!(bytes < large_bytes);
+}
+
 #[test]
 fn test_callstack() {
     // Make sure that even for native functions that fail, the
@@ -228,3 +370,112 @@ fn test_display_debug() {
     assert_eq!(format!("{:?}", v), "FrozenValue(\"test\")");
     assert_eq!(format!("{:#?}", v), "FrozenValue(\n    \"test\",\n)");
 }
+
+#[test]
+fn test_load_handler() {
+    #[derive(Default)]
+    struct Handler(RefCell<Vec<String>>);
+
+    impl LoadHandler for Handler {
+        fn on_load_begin(&self, path: &str) {
+            self.0.borrow_mut().push(format!("begin {}", path));
+        }
+
+        fn on_load_end(&self, path: &str, duration: Duration) {
+            assert!(duration < Duration::from_secs(60));
+            self.0.borrow_mut().push(format!("end {}", path));
+        }
+    }
+
+    let mut a = Assert::new();
+    let foo = a.module("foo.star", "x = 1");
+    let bar = a.module("bar.star", "y = 2");
+    let modules = [("foo.star", &foo), ("bar.star", &bar)]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let loader = ReturnFileLoader { modules: &modules };
+    let handler = Handler::default();
+
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    eval.set_loader(&loader);
+    eval.set_load_handler(&handler);
+    let ast = AstModule::parse(
+        "main.star",
+        "load('foo.star', 'x')\nload('bar.star', 'y')\nx + y".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let res = eval.eval_module(ast, &Globals::standard()).unwrap();
+    assert_eq!(res.unpack_int(), Some(3));
+    assert_eq!(
+        *handler.0.borrow(),
+        vec![
+            "begin foo.star",
+            "end foo.star",
+            "begin bar.star",
+            "end bar.star"
+        ]
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_spans() {
+    use std::{
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id},
+        Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        Layer,
+    };
+
+    // Records each new span as its name followed by its fields.
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<Vec<String>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Spans {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut fields = Fields(attrs.metadata().name().to_owned());
+            attrs.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+
+    let spans = Spans::default();
+    let subscriber = tracing_subscriber::registry().with(spans.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        assert::pass(
+            r#"
+def my_function():
+    return 1
+my_function()
+"#,
+        );
+    });
+
+    let spans = spans.0.lock().unwrap();
+    assert!(spans.iter().any(|x| x.starts_with("eval_module")));
+    assert!(
+        spans
+            .iter()
+            .any(|x| x.starts_with("call") && x.contains("my_function")),
+        "no call span in {:?}",
+        spans
+    );
+}
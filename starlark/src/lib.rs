@@ -438,4 +438,7 @@ pub mod __derive_refs {
     pub mod serde {
         pub use serde::{ser::Error, Serialize, Serializer};
     }
+    pub mod record {
+        pub use crate::values::record_derive::{alloc_record, unpack_record};
+    }
 }
@@ -0,0 +1,44 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Methods for the `bytes` type.
+
+use crate as starlark;
+use crate::{environment::MethodsBuilder, values::bytes::Bytes};
+
+#[starlark_module]
+pub(crate) fn bytes_methods(builder: &mut MethodsBuilder) {
+    /// [bytes.decode](
+    /// https://docs.python.org/3/library/stdtypes.html#bytes.decode
+    /// ): decode bytes to a string. _Not part of standard Starlark._
+    ///
+    /// `B.decode(encoding="utf-8")` returns the string encoded by B.
+    /// Only UTF-8 is supported, and invalid UTF-8 is an error.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "hello".encode().decode() == "hello"
+    /// "世界".encode().decode("utf-8") == "世界"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn decode(this: &Bytes, ref encoding: Option<&str>) -> anyhow::Result<String> {
+        Ok(this.decode(encoding)?.to_owned())
+    }
+}
@@ -0,0 +1,65 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of `depset` function, and the methods of the `depset` type.
+use crate as starlark;
+use crate::{
+    environment::{GlobalsBuilder, MethodsBuilder},
+    values::{depset::Depset, Value},
+};
+
+#[starlark_module]
+pub fn global(builder: &mut GlobalsBuilder) {
+    /// Create a set made of the `direct` elements and the elements of the `transitive` depsets,
+    /// which are shared rather than copied. Elements must be hashable. The `order` of the
+    /// elements returned by `to_list()` is one of `"default"`, `"postorder"`, `"preorder"` or
+    /// `"topological"`, and the `transitive` depsets must have the same order, or `"default"`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// depset(["b"], transitive = [depset(["a"])]).to_list() == ["a", "b"]
+    /// depset(["b"], order = "preorder", transitive = [depset(["a"])]).to_list() == ["b", "a"]
+    /// # "#);
+    /// ```
+    #[starlark(type(Depset::TYPE))]
+    fn depset(
+        direct: Option<Vec<Value<'v>>>,
+        order: Option<&str>,
+        transitive: Option<Vec<Value<'v>>>,
+    ) -> anyhow::Result<Depset<'v>> {
+        Depset::new(
+            order.unwrap_or("default"),
+            direct.unwrap_or_default(),
+            transitive.unwrap_or_default(),
+        )
+    }
+}
+
+#[starlark_module]
+pub(crate) fn depset_methods(builder: &mut MethodsBuilder) {
+    /// The elements of the depset and its transitive depsets, without duplicates,
+    /// in the order of the depset.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// depset([1, 2, 1]).to_list() == [1, 2]
+    /// # "#);
+    /// ```
+    fn to_list(this: &Depset) -> anyhow::Result<Vec<Value<'v>>> {
+        Ok(this.to_list())
+    }
+}
@@ -363,7 +363,7 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
 
 #[cfg(test)]
 mod tests {
-    use crate::assert;
+    use crate::{assert, assert::Assert};
 
     #[test]
     fn test_error_codes() {
@@ -371,6 +371,50 @@ mod tests {
         assert::fail("x = {}; x.popitem()", "empty");
     }
 
+    #[test]
+    fn test_dict_get_pop() {
+        assert::all_true(
+            r#"
+{"a": 1}.get("a") == 1
+{"a": 1}.get("a", 2) == 1
+{"a": 1}.get("b") == None
+{"a": 1}.get("b", 2) == 2
+x = {"a": 1}; x.pop("a") == 1 and x == {}
+x = {"a": 1}; x.pop("a", 2) == 1 and x == {}
+x = {"a": 1}; x.pop("b", 2) == 2 and x == {"a": 1}
+x = {"a": 1}; x.pop("b", None) == None
+"#,
+        );
+        assert::fail(r#"{"a": 1}.pop("b")"#, "not found");
+        assert::fail(r#"{"a": 1}.get([])"#, "unhashable type: list");
+
+        let mut a = Assert::new();
+        a.module("d.bzl", "d = {'a': 1}");
+        a.is_true("load('d.bzl', 'd')\nd.get('a') == 1 and d.get('b', 2) == 2");
+        a.fail("load('d.bzl', 'd')\nd.pop('a')", "Immutable");
+        a.fail("load('d.bzl', 'd')\nd.pop('b', 2)", "Immutable");
+    }
+
+    #[test]
+    fn test_dict_update() {
+        assert::all_true(
+            r#"
+x = {"a": 1, "b": 2}; x.update({"b": 3, "c": 4}); x == {"a": 1, "b": 3, "c": 4}
+x = {"a": 1, "b": 2}; x.update(b=3, c=4); x == {"a": 1, "b": 3, "c": 4}
+x = {"a": 1}; x.update({"a": 2, "b": 2}, a=3); x == {"a": 3, "b": 2}
+x = {"a": 1}; x.update([("a", 2)], a=3); x == {"a": 3}
+x = {"a": 1}; x.update(x); x == {"a": 1}
+x = {"a": 1}; x.update() == None
+"#,
+        );
+        assert::fail("{}.update([(1,)])", "non-pairs");
+
+        let mut a = Assert::new();
+        a.module("d.bzl", "d = {'a': 1}");
+        a.fail("load('d.bzl', 'd')\nd.update({'b': 2})", "Immutable");
+        a.fail("load('d.bzl', 'd')\nd.update(b=2)", "Immutable");
+    }
+
     #[test]
     fn test_dict_add() {
         assert::fail("{1: 2} + {3: 4}", "not supported");
@@ -26,15 +26,21 @@ use gazebo::{
     prelude::*,
 };
 use itertools::Itertools;
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+use thiserror::Error;
 
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
 use crate::{
     self as starlark,
     collections::symbol_map::Symbol,
     environment::GlobalsBuilder,
     eval::{Arguments, Evaluator},
     values::{
-        dict::DictRef, function::FUNCTION_TYPE, none::NoneType, tuple::Tuple, Freeze, Freezer,
-        FrozenStringValue, FrozenValue, StarlarkValue, StringValue, StringValueLike, Trace, Value,
+        dict::DictRef, function::FUNCTION_TYPE, int::INT_TYPE, none::NoneType, num::Num,
+        tuple::Tuple, type_value::TypeValue, Freeze, Freezer, FrozenStringValue, FrozenValue, Heap,
+        StarlarkValue, StringValue, StringValueLike, Trace, UnpackValue, Value, ValueError,
         ValueLike,
     },
 };
@@ -69,8 +75,201 @@ pub fn map(builder: &mut GlobalsBuilder) {
     }
 }
 
+#[derive(Debug, Error)]
+enum ReduceError {
+    #[error("reduce() of empty iterable with no initial value")]
+    Empty,
+}
+
+#[starlark_module]
+pub fn reduce(builder: &mut GlobalsBuilder) {
+    /// Combine the elements of `seq` from left to right with the two argument function `func`,
+    /// starting with `initializer` if given, or otherwise the first element.
+    /// Fails if `seq` is empty and there is no `initializer`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// reduce(lambda x, y: x + y, [1, 2, 3]) == 6
+    /// reduce(lambda x, y: x + [y], [1, 2], []) == [1, 2]
+    /// reduce(max, [], 0) == 0
+    /// # "#);
+    /// ```
+    fn reduce(
+        ref func: Value,
+        ref seq: Value,
+        ref initializer: Option<Value>,
+    ) -> anyhow::Result<Value<'v>> {
+        let mut it = seq.iterate(heap)?;
+        let mut res = match initializer.or_else(|| it.next()) {
+            Some(x) => x,
+            None => return Err(ReduceError::Empty.into()),
+        };
+        for v in it {
+            res = func.invoke_pos(&[res, v], eval)?;
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Debug, Error)]
+enum PowError {
+    #[error("pow() 3rd argument cannot be 0")]
+    ZeroModulus,
+    #[error("pow() with a 3rd argument requires int arguments")]
+    NotInt,
+    #[error("pow() with a 3rd argument requires a non-negative exponent")]
+    NegativeExponent,
+    #[error("pow() 0 cannot be raised to a negative power")]
+    ZeroToNegative,
+    #[error("pow() negative number cannot be raised to a fractional power")]
+    NegativeToFractional,
+}
+
+/// `base ** exp % modulus` by square-and-multiply, so the intermediate values never
+/// exceed `modulus` squared, which fits in an `i64`.
+fn mod_pow(base: i32, mut exp: i32, modulus: i32) -> i32 {
+    let m = (modulus as i64).abs();
+    let mut base = (base as i64).rem_euclid(m);
+    let mut res = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = res * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    // Like `%`, the result has the sign of the modulus
+    if modulus < 0 && res != 0 {
+        res -= m;
+    }
+    res as i32
+}
+
+/// `pow` with a `modulus`, where all the arguments must be ints.
+#[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+fn pow_mod<'v>(
+    base: Value<'v>,
+    exp: Value<'v>,
+    modulus: Value<'v>,
+    heap: &'v Heap,
+) -> anyhow::Result<Value<'v>> {
+    if modulus.unpack_int() == Some(0) {
+        return Err(PowError::ZeroModulus.into());
+    }
+    if let (Some(base), Some(exp), Some(modulus)) =
+        (base.unpack_int(), exp.unpack_int(), modulus.unpack_int())
+    {
+        if exp < 0 {
+            return Err(PowError::NegativeExponent.into());
+        }
+        return Ok(Value::new_int(mod_pow(base, exp, modulus)));
+    }
+    #[cfg(feature = "bigint")]
+    if let (Some(base), Some(exp), Some(modulus)) = (
+        StarlarkBigInt::unpack_bigint(base),
+        StarlarkBigInt::unpack_bigint(exp),
+        StarlarkBigInt::unpack_bigint(modulus),
+    ) {
+        if exp < BigInt::from(0) {
+            return Err(PowError::NegativeExponent.into());
+        }
+        // Rounds like Starlark's `%`, so the result has the sign of the modulus.
+        return Ok(StarlarkBigInt::alloc(base.modpow(&exp, &modulus), heap));
+    }
+    Err(PowError::NotInt.into())
+}
+
+/// `x * y`, promoting to a bigint rather than overflowing if `bigint` is set, like `*`
+/// in code with [`enable_bigint`](crate::syntax::Dialect::enable_bigint).
+#[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+fn int_mul<'v>(
+    x: Value<'v>,
+    y: Value<'v>,
+    bigint: bool,
+    heap: &'v Heap,
+) -> anyhow::Result<Value<'v>> {
+    #[cfg(feature = "bigint")]
+    if let (true, Some(a), Some(b)) = (bigint, x.unpack_int(), y.unpack_int()) {
+        return Ok(StarlarkBigInt::alloc_i64(a as i64 * b as i64, heap));
+    }
+    x.mul(y, heap)
+}
+
+#[starlark_module]
+pub fn pow(builder: &mut GlobalsBuilder) {
+    /// `base` raised to the power `exp`, or with `modulus`, `base ** exp % modulus` computed
+    /// without the large intermediate result.
+    ///
+    /// With int arguments and a non-negative `exp` the result is an int, and it is an overflow
+    /// error if it doesn't fit in 32 bits, unless
+    /// [`enable_bigint`](crate::syntax::Dialect::enable_bigint) is set.
+    /// Otherwise the result is a float. With `modulus`, all arguments must be ints,
+    /// which may be bigints, `exp` must not be negative, and the result has the same
+    /// sign as `modulus`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// pow(2, 10) == 1024
+    /// pow(2, -1) == 0.5
+    /// pow(4, 0.5) == 2.0
+    /// pow(3, 200, 7) == 2
+    /// pow(3, 2, -5) == -1
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn pow(
+        ref base: Value,
+        ref exp: Value,
+        ref modulus: Option<Value>,
+    ) -> anyhow::Result<Value<'v>> {
+        if let Some(modulus) = modulus {
+            return pow_mod(base, exp, modulus, heap);
+        }
+        match exp.unpack_int() {
+            // `INT_TYPE` includes bigints
+            Some(mut exp) if exp >= 0 && base.get_type() == INT_TYPE => {
+                // Multiply as values, so overflow is handled like `*`
+                let bigint = eval.bigint_enabled();
+                let mut res = Value::new_int(1);
+                let mut base = base;
+                while exp > 0 {
+                    if exp & 1 == 1 {
+                        res = int_mul(res, base, bigint, heap)?;
+                    }
+                    exp >>= 1;
+                    if exp > 0 {
+                        base = int_mul(base, base, bigint, heap)?;
+                    }
+                }
+                Ok(res)
+            }
+            _ => {
+                let base = Num::unpack_param(base)?.as_float();
+                let exp = Num::unpack_param(exp)?.as_float();
+                if base == 0.0 && exp < 0.0 {
+                    return Err(PowError::ZeroToNegative.into());
+                }
+                if base < 0.0 && exp.fract() != 0.0 {
+                    return Err(PowError::NegativeToFractional.into());
+                }
+                Ok(heap.alloc(base.powf(exp)))
+            }
+        }
+    }
+}
+
 #[starlark_module]
 pub fn partial(builder: &mut GlobalsBuilder) {
+    /// A function which calls `func` with `args` followed by its own positional arguments,
+    /// and with `kwargs` as well as its own named arguments, which take precedence
+    /// over those in `kwargs`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// partial(int, base = 16)("ff") == 255
+    /// partial(max, 1)(5, 3) == 5
+    /// # "#);
+    /// ```
     fn partial(
         ref func: Value,
         args: Value<'v>,
@@ -175,16 +374,40 @@ pub fn pprint(builder: &mut GlobalsBuilder) {
 }
 
 #[starlark_module]
-pub fn json(builder: &mut GlobalsBuilder) {
-    fn json(ref x: Value) -> anyhow::Result<String> {
-        x.to_json()
-    }
-}
-
-#[starlark_module]
-pub fn abs(builder: &mut GlobalsBuilder) {
-    fn abs(ref x: i32) -> anyhow::Result<i32> {
-        Ok(x.abs())
+pub fn is_instance(builder: &mut GlobalsBuilder) {
+    /// Check whether the type of `x` is `t`, where `t` is a type name such as `"int"`,
+    /// a type object as returned by `type()`, or a tuple of those,
+    /// in which case `x` may be any of the types.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// isinstance(1, "int")
+    /// isinstance("a", type(""))
+    /// isinstance([], ("dict", "list"))
+    /// not isinstance(1, "string")
+    /// # "#);
+    /// ```
+    fn isinstance(ref x: Value, ref t: Value) -> anyhow::Result<bool> {
+        let typ = x.get_type();
+        let matches = |t: Value| match TypeValue::unpack_name(t) {
+            Some(name) => Ok(name == typ),
+            None => Err(ValueError::IncorrectParameterTypeNamedWithExpected(
+                "t".to_owned(),
+                "string, type or tuple".to_owned(),
+                t.get_type().to_owned(),
+            )),
+        };
+        match Tuple::from_value(t) {
+            Some(ts) => {
+                for t in ts.content() {
+                    if matches(*t)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            None => Ok(matches(t)?),
+        }
     }
 }
 
@@ -282,9 +505,15 @@ where
 mod tests {
     use std::{cell::RefCell, rc::Rc};
 
-    use gazebo::prelude::*;
+    use derive_more::Display;
+    use gazebo::{any::AnyLifetime, prelude::*};
 
-    use crate::{assert, assert::Assert, stdlib::PrintHandler};
+    use crate::{
+        self as starlark, assert,
+        assert::Assert,
+        stdlib::PrintHandler,
+        values::{NoSerialize, StarlarkValue},
+    };
 
     #[test]
     fn test_filter() {
@@ -322,6 +551,100 @@ assert_eq(["11",8], map(double, ["1",4]))
         );
     }
 
+    #[test]
+    fn test_map_filter_chain() {
+        assert::pass(
+            r#"
+calls = []
+def g(x):
+    calls.append(("g", x))
+    return x % 2 == 1
+def f(x):
+    calls.append(("f", x))
+    return x * 10
+assert_eq([10, 30], list(map(f, filter(g, [1, 2, 3]))))
+assert_eq([("g", 1), ("g", 2), ("g", 3), ("f", 1), ("f", 3)], calls)
+"#,
+        );
+    }
+
+    #[test]
+    fn test_reduce() {
+        assert::pass(
+            r#"
+def add(x, y):
+    return x + y
+
+assert_eq(10, reduce(add, [1, 2, 3, 4]))
+assert_eq(10, reduce(add, range(5)))
+assert_eq(16, reduce(add, [1, 2, 3, 4], 6))
+assert_eq(5, reduce(add, [5]))
+assert_eq("init", reduce(add, [], "init"))
+assert_eq(["c", "b", "a"], reduce(lambda acc, x: [x] + acc, ["a", "b", "c"], []))
+"#,
+        );
+        assert::fail(
+            "reduce(lambda x, y: x + y, [])",
+            "reduce() of empty iterable with no initial value",
+        );
+        assert::fail("reduce(lambda x: x, [1, 2])", "extra positional");
+    }
+
+    #[test]
+    fn test_pow() {
+        assert::all_true(
+            r#"
+pow(4, 13, 497) == 445
+pow(2, 1000000, 1000000007) == 235042059
+pow(2147483647, 2147483646, 2147483629) == 357948888
+pow(123456789, 987654321, 2147483647) == 1077769156
+pow(-2, 3, 5) == 2
+pow(3, 4, -7) == -3
+pow(-3, 3, -7) == -6
+pow(7, 0, 13) == 1
+pow(7, 0, 1) == 0
+pow(2, 30) == 1073741824
+pow(-2, 31) == -2147483647 - 1
+pow(5, 0) == 1
+pow(2, -2) == 0.25
+pow(2.5, 2) == 6.25
+pow(0, 0) == 1
+"#,
+        );
+        assert::fail("pow(2, 31)", "Integer overflow");
+        assert::fail("pow(2, -1, 5)", "non-negative exponent");
+        assert::fail("pow(2, 3, 0)", "3rd argument cannot be 0");
+        assert::fail("pow(2.0, 3, 5)", "requires int arguments");
+        assert::fail("pow(0, -1)", "0 cannot be raised to a negative power");
+        assert::fail("pow(-8, 1.0 / 3)", "fractional power");
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_pow_bigint() {
+        use crate::syntax::Dialect;
+
+        let mut a = Assert::new();
+        a.dialect(&Dialect {
+            enable_bigint: true,
+            ..Dialect::Extended
+        });
+        a.pass(
+            r#"
+big = pow(2, 40)
+assert_eq(pow(big, 3, 7), 1)
+assert_eq(pow(-big, 1, 7), 5)
+assert_eq(pow(2, big, 7), 2)
+assert_eq(pow(3, 2, big), 9)
+assert_eq(pow(2, 100, big), 0)
+assert_eq(pow(3, 3, -big), 27 - big)
+assert_eq(pow(big, 2), big * big)
+"#,
+        );
+        a.fail("pow(2, -pow(2, 40), 7)", "non-negative exponent");
+        a.fail("pow(2.0, 3, pow(2, 40))", "requires int arguments");
+    }
+
     #[test]
     fn test_partial() {
         assert::pass(
@@ -354,6 +677,28 @@ assert_eq(
         );
     }
 
+    #[test]
+    fn test_partial_apply() {
+        assert::pass(
+            r#"
+def add(a, b):
+    return a + b
+
+assert_eq(3, partial(add, 1)(2))
+assert_eq(11, partial(add, b = 10)(1))
+assert_eq("xy", partial(add, a = "x")(b = "y"))
+assert_eq("ab", partial(partial(add, "a"))("b"))
+assert_eq("function", type(partial(add, 1)))
+# Named arguments to the call win over bound ones
+assert_eq(3, partial(add, b = 1)(1, b = 2))
+"#,
+        );
+        assert::fail(
+            "def add(a, b):\n    return a + b\npartial(add, 1)(2, 3)",
+            "extra positional",
+        );
+    }
+
     #[test]
     fn test_debug() {
         assert::pass(
@@ -377,6 +722,37 @@ assert_eq(dedupe([a,b,a]), [a,b])
         );
     }
 
+    #[test]
+    fn test_isinstance() {
+        #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+        #[display(fmt = "widget")]
+        struct Widget;
+        starlark_simple_value!(Widget);
+
+        impl<'v> StarlarkValue<'v> for Widget {
+            starlark_type!("widget");
+        }
+
+        let mut a = Assert::new();
+        a.globals_add(|builder| builder.set("w", Widget));
+        a.all_true(
+            r#"
+isinstance(1, "int")
+isinstance(1, type(2))
+isinstance([], ("dict", "list"))
+isinstance(None, "NoneType")
+isinstance(w, "widget")
+isinstance(w, type(w))
+not isinstance(1, "string")
+not isinstance("1", type(1))
+not isinstance({}, ("list", "tuple"))
+not isinstance(w, "struct")
+"#,
+        );
+        a.fail("isinstance(1, 2)", "expected `string, type or tuple`");
+        a.fail("isinstance(1, ('int', None))", "actual `NoneType`");
+    }
+
     #[test]
     fn test_print() {
         let s = Rc::new(RefCell::new(String::new()));
@@ -0,0 +1,43 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of `frozenset` function.
+use crate as starlark;
+use crate::{
+    environment::GlobalsBuilder,
+    values::{frozenset::FrozenSet, Value},
+};
+
+#[starlark_module]
+pub fn global(builder: &mut GlobalsBuilder) {
+    /// Create an immutable set from the elements of an iterable, dropping duplicates.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// len(frozenset([1, 2, 1])) == 2
+    /// frozenset() == frozenset([])
+    /// # "#);
+    /// ```
+    #[starlark(type(FrozenSet::TYPE))]
+    #[starlark(speculative_exec_safe)]
+    fn frozenset(ref iterable: Option<Value>) -> anyhow::Result<FrozenSet<'v>> {
+        match iterable {
+            None => Ok(FrozenSet::default()),
+            Some(x) => x.with_iterator(heap, |it| FrozenSet::from_values(it))?,
+        }
+    }
+}
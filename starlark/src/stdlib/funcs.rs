@@ -22,18 +22,89 @@ use std::{cmp::Ordering, num::NonZeroI32};
 
 use anyhow::anyhow;
 
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
 use crate::{
     self as starlark,
     collections::SmallMap,
     environment::GlobalsBuilder,
     eval::Arguments,
     values::{
-        bool::BOOL_TYPE, dict::Dict, float::StarlarkFloat, int::INT_TYPE, list::List,
-        none::NoneType, num::Num, range::Range, string::STRING_TYPE, tuple::Tuple, Heap,
-        StringValue, Value, ValueError, ValueLike,
+        bool::BOOL_TYPE, comparison::compare_values, dict::Dict, float::StarlarkFloat,
+        int::INT_TYPE, list::List, none::NoneType, num::Num, range::Range, string::STRING_TYPE,
+        tuple::Tuple, type_value::TypeValue, Heap, StringValue, Value, ValueError, ValueLike,
     },
 };
 
+/// The hash of a string, as required by the Starlark spec:
+///
+/// > the hash function for strings is the same as that implemented by java.lang.String.hashCode,
+/// > a simple polynomial accumulator over the UTF-16 transcoding of the string:
+/// > `s[0]*31^(n-1) + s[1]*31^(n-2) + ... + s[n-1]`
+fn string_hash(a: &str) -> i32 {
+    // Most strings are ASCII strings, try them first.
+    #[allow(clippy::never_loop)]
+    'ascii: loop {
+        let mut hash = 0i32;
+        for &b in a.as_bytes() {
+            if b > 0x7f {
+                break 'ascii;
+            }
+            hash = hash.wrapping_mul(31i32).wrapping_add(b as i32);
+        }
+        return hash;
+    }
+
+    a.encode_utf16().fold(0i32, |hash: i32, c: u16| {
+        31i32.wrapping_mul(hash).wrapping_add(c as i32)
+    })
+}
+
+/// Round half to even, as Python does, rather than half away from zero like [`f64::round`].
+fn round_half_even(x: f64) -> f64 {
+    let r = x.round();
+    if (r - x).abs() == 0.5 {
+        2.0 * (x / 2.0).round()
+    } else {
+        r
+    }
+}
+
+/// Round to a multiple of `10^k`, with values exactly halfway rounding to the even multiple.
+fn round_int_digits(x: i64, k: u32) -> i64 {
+    // `10^10` is more than twice any 32 bit int, so it rounds to zero.
+    if k >= 10 {
+        return 0;
+    }
+    let p = 10i64.pow(k);
+    let (q, r) = (x.div_euclid(p), x.rem_euclid(p));
+    let q = if r * 2 > p || (r * 2 == p && q % 2 != 0) {
+        q + 1
+    } else {
+        q
+    };
+    q * p
+}
+
+/// An int result computed as `i64`, which is only allowed to exceed 32 bits when
+/// [`enable_bigint`](crate::syntax::Dialect::enable_bigint) is set.
+#[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+fn int_from_i64<'v>(x: i64, bigint: bool, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+    #[cfg(feature = "bigint")]
+    if bigint {
+        return Ok(StarlarkBigInt::alloc_i64(x, heap));
+    }
+    match i32::try_from(x) {
+        Ok(x) => Ok(Value::new_int(x)),
+        Err(_) => Err(ValueError::IntegerOverflow.into()),
+    }
+}
+
+fn is_number(x: Value) -> bool {
+    let t = x.get_type();
+    t == INT_TYPE || t == StarlarkFloat::TYPE
+}
+
 fn unpack_pair<'v>(pair: Value<'v>, heap: &'v Heap) -> anyhow::Result<(Value<'v>, Value<'v>)> {
     pair.with_iterator(heap, |it| {
         if let Some(first) = it.next() {
@@ -56,6 +127,38 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     const True: bool = true;
     const False: bool = false;
 
+    /// [abs](
+    /// https://docs.python.org/3/library/functions.html#abs
+    /// ): returns the absolute value of a number.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// abs(-3) == 3
+    /// abs(3) == 3
+    /// abs(-2.5) == 2.5
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn abs(ref x: Value) -> anyhow::Result<Value<'v>> {
+        match x.unpack_num() {
+            Some(Num::Float(f)) => return Ok(heap.alloc(f.abs())),
+            Some(Num::Int(i)) if i < 0 => {
+                return int_from_i64(-(i as i64), eval.bigint_enabled(), heap);
+            }
+            _ => {}
+        }
+        if x.get_type() != INT_TYPE {
+            Err(anyhow!(
+                "abs() argument must be an int or float, not `{}`",
+                x.get_type()
+            ))
+        } else if x.compare(Value::new_int(0))? == Ordering::Less {
+            x.minus(heap)
+        } else {
+            Ok(x)
+        }
+    }
+
     /// fail: fail the execution
     ///
     /// Examples:
@@ -84,6 +187,9 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// ): returns true if any value in the iterable object have a truth value
     /// of true.
     ///
+    /// If `predicate` is given, it is called on each value and the truth value
+    /// of its result is used instead. Iteration stops at the first true value.
+    ///
     /// Examples:
     ///
     /// ```
@@ -93,18 +199,23 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// any([0, 1, True]) == True
     /// any([0, 0]) == False
     /// any([0, False]) == False
+    /// any([1, 2, 3], lambda x: x > 2) == True
+    /// any([1, 2, 3], predicate=lambda x: x > 3) == False
     /// # "#);
     /// ```
-    #[starlark(speculative_exec_safe)]
-    fn any(ref x: Value) -> anyhow::Result<bool> {
+    fn any(ref x: Value, predicate: Option<Value>) -> anyhow::Result<bool> {
         x.with_iterator(heap, |it| {
             for i in it {
-                if i.to_bool() {
-                    return true;
+                let b = match predicate {
+                    None => i.to_bool(),
+                    Some(predicate) => predicate.invoke_pos(&[i], eval)?.to_bool(),
+                };
+                if b {
+                    return Ok(true);
                 }
             }
-            false
-        })
+            Ok(false)
+        })?
     }
 
     /// [all](
@@ -112,6 +223,9 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// ): returns true if all values in the iterable object have a truth value
     /// of true.
     ///
+    /// If `predicate` is given, it is called on each value and the truth value
+    /// of its result is used instead. Iteration stops at the first false value.
+    ///
     /// Examples:
     ///
     /// ```
@@ -124,18 +238,23 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// all([0, False]) == False
     /// all([True, 0]) == False
     /// all([1, False]) == False
+    /// all([1, 2, 3], lambda x: x > 0) == True
+    /// all([1, 2, 3], predicate=lambda x: x > 1) == False
     /// # "#);
     /// ```
-    #[starlark(speculative_exec_safe)]
-    fn all(ref x: Value) -> anyhow::Result<bool> {
+    fn all(ref x: Value, predicate: Option<Value>) -> anyhow::Result<bool> {
         x.with_iterator(heap, |it| {
             for i in it {
-                if !i.to_bool() {
-                    return false;
+                let b = match predicate {
+                    None => i.to_bool(),
+                    Some(predicate) => predicate.invoke_pos(&[i], eval)?.to_bool(),
+                };
+                if !b {
+                    return Ok(false);
                 }
             }
-            true
-        })
+            Ok(true)
+        })?
     }
 
     /// [bool](
@@ -291,6 +410,31 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         Ok(x.dir_attr())
     }
 
+    /// [divmod](
+    /// https://docs.python.org/3/library/functions.html#divmod
+    /// ): returns the quotient and remainder of a division.
+    ///
+    /// `divmod(a, b)` is equivalent to `(a // b, a % b)`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// divmod(7, 3) == (2, 1)
+    /// divmod(-7, 3) == (-3, 2)
+    /// divmod(7.5, 2) == (3.0, 1.5)
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn divmod(ref a: Value, ref b: Value) -> anyhow::Result<(Value<'v>, Value<'v>)> {
+        if !is_number(a) || !is_number(b) {
+            return Err(anyhow!(
+                "divmod() arguments must be int or float, not `{}` and `{}`",
+                a.get_type(),
+                b.get_type()
+            ));
+        }
+        Ok((a.floor_div(b, heap)?, a.percent(b, heap)?))
+    }
+
     /// [enumerate](
     /// https://github.com/google/skylark/blob/a0e5de7e63b47e716cca7226662a4c95d47bf873/doc/spec.md#enumerate
     /// ): return a list of (index, element) from an iterable.
@@ -427,36 +571,23 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// `hash` fails if x, or any value upon which its hash depends, is
     /// unhashable.
     ///
+    /// Strings are hashed as the standard requires. The standard only allows strings,
+    /// but we also accept any other hashable value, giving the hash used by `dict`,
+    /// which is useful when debugging. Those hashes may change between versions.
+    ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// hash("hello") != hash("world")
+    /// hash((1, "a")) == hash((1, "a"))
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
-    fn hash(ref a: &str) -> anyhow::Result<i32> {
-        // From the starlark spec:
-        // > the hash function for strings is the same as that implemented by java.lang.String.hashCode,
-        // > a simple polynomial accumulator over the UTF-16 transcoding of the string:
-        // > `s[0]*31^(n-1) + s[1]*31^(n-2) + ... + s[n-1]`
-        // As per spec the function should only support string and bytes types.
-        // We don't have support for bytes, so parameter is forced to be a string.
-
-        // Most strings are ASCII strings, try them first.
-        #[allow(clippy::never_loop)]
-        'ascii: loop {
-            let mut hash = 0i32;
-            for &b in a.as_bytes() {
-                if b > 0x7f {
-                    break 'ascii;
-                }
-                hash = hash.wrapping_mul(31i32).wrapping_add(b as i32);
-            }
-            return Ok(hash);
+    fn hash(ref a: Value) -> anyhow::Result<i32> {
+        // Type objects are equal to their name, so must hash the same
+        match TypeValue::unpack_name(a) {
+            Some(s) => Ok(string_hash(s)),
+            None => Ok(a.get_hash()?.get() as i32),
         }
-
-        Ok(a.encode_utf16().fold(0i32, |hash: i32, c: u16| {
-            31i32.wrapping_mul(hash).wrapping_add(c as i32)
-        }))
     }
 
     /// [int](
@@ -647,7 +778,8 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
             if let Some(xs) = List::from_value(a) {
                 heap.alloc_list(xs.content())
             } else {
-                a.with_iterator(heap, |it| heap.alloc_list_iter(it))?
+                let capacity = a.length_hint().unwrap_or(0);
+                a.with_iterator(heap, |it| heap.alloc_list_iter_with_capacity(capacity, it))?
             }
         } else {
             heap.alloc_list(&[])
@@ -680,6 +812,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         } else {
             heap.alloc(args)
         };
+        let cross_type = eval.cross_type_ordering_enabled();
         let mut it = args.iterate(heap)?;
         let mut max = match it.next() {
             Some(x) => x,
@@ -692,7 +825,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         match key {
             None => {
                 for i in it {
-                    if max.compare(i)? == Ordering::Less {
+                    if compare_values(max, i, cross_type)? == Ordering::Less {
                         max = i;
                     }
                 }
@@ -701,7 +834,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
                 let mut cached = key.invoke_pos(&[max], eval)?;
                 for i in it {
                     let keyi = key.invoke_pos(&[i], eval)?;
-                    if cached.compare(keyi)? == Ordering::Less {
+                    if compare_values(cached, keyi, cross_type)? == Ordering::Less {
                         max = i;
                         cached = keyi;
                     }
@@ -734,6 +867,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         } else {
             heap.alloc(args)
         };
+        let cross_type = eval.cross_type_ordering_enabled();
         let mut it = args.iterate(heap)?;
         let mut min = match it.next() {
             Some(x) => x,
@@ -746,7 +880,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         match key {
             None => {
                 for i in it {
-                    if min.compare(i)? == Ordering::Greater {
+                    if compare_values(min, i, cross_type)? == Ordering::Greater {
                         min = i;
                     }
                 }
@@ -755,7 +889,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
                 let mut cached = key.invoke_pos(&[min], eval)?;
                 for i in it {
                     let keyi = key.invoke_pos(&[i], eval)?;
-                    if cached.compare(keyi)? == Ordering::Greater {
+                    if compare_values(cached, keyi, cross_type)? == Ordering::Greater {
                         min = i;
                         cached = keyi;
                     }
@@ -894,6 +1028,78 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
         Ok(heap.alloc_list(&v))
     }
 
+    /// [round](
+    /// https://docs.python.org/3/library/functions.html#round
+    /// ): round a number to a given precision.
+    ///
+    /// `round(x)` returns the int nearest to `x`, and `round(x, ndigits)` rounds
+    /// to `ndigits` digits after the decimal point, returning the same type as `x`.
+    /// Values exactly halfway between two choices round to the even choice.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// round(2.5) == 2
+    /// round(3.5) == 4
+    /// round(-2.5) == -2
+    /// round(2.4) == 2
+    /// round(7) == 7
+    /// round(3.14159, 2) == 3.14
+    /// round(1250, -2) == 1200
+    /// round(1350, -2) == 1400
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn round(ref x: Value, ndigits: Option<Value>) -> anyhow::Result<Value<'v>> {
+        let ndigits = match ndigits {
+            Some(n) if !n.is_none() => Some(n.to_int()?),
+            _ => None,
+        };
+        #[cfg(feature = "bigint")]
+        if let Some(b) = x.downcast_ref::<StarlarkBigInt>() {
+            return Ok(match ndigits {
+                Some(n) if n < 0 => StarlarkBigInt::alloc(b.round_digits(n.unsigned_abs()), heap),
+                _ => x,
+            });
+        }
+        match x.unpack_num() {
+            Some(Num::Float(f)) => match ndigits {
+                None => {
+                    if !f.is_finite() {
+                        return Err(anyhow!("cannot round `{}` to an int", f));
+                    }
+                    let r = round_half_even(f);
+                    if r < i32::MIN as f64 || r > i32::MAX as f64 {
+                        return Err(ValueError::IntegerOverflow.into());
+                    }
+                    Ok(Value::new_int(r as i32))
+                }
+                Some(n) => {
+                    let r = if n >= 0 {
+                        let p = 10f64.powi(n);
+                        round_half_even(f * p) / p
+                    } else {
+                        let p = 10f64.powi(-n);
+                        round_half_even(f / p) * p
+                    };
+                    // Too many digits to represent, the value is already rounded.
+                    Ok(heap.alloc(if r.is_finite() { r } else { f }))
+                }
+            },
+            Some(Num::Int(i)) => match ndigits {
+                Some(n) if n < 0 => int_from_i64(
+                    round_int_digits(i as i64, n.unsigned_abs()),
+                    eval.bigint_enabled(),
+                    heap,
+                ),
+                _ => Ok(x),
+            },
+            None => Err(anyhow!(
+                "round() argument must be an int or float, not `{}`",
+                x.get_type()
+            )),
+        }
+    }
+
     /// [sorted](
     /// https://github.com/google/skylark/blob/a0e5de7e63b47e716cca7226662a4c95d47bf873/doc/spec.md#sorted
     /// ): sort a sequence
@@ -937,12 +1143,13 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
 
         let mut compare_ok = Ok(());
 
+        let cross_type = eval.cross_type_ordering_enabled();
         let reverse = reverse.map_or(false, |x| x.to_bool());
         it.sort_by(|x: &(Value, Value), y: &(Value, Value)| {
             let ord_or_err = if reverse {
-                x.1.compare(y.1).map(Ordering::reverse)
+                compare_values(x.1, y.1, cross_type).map(Ordering::reverse)
             } else {
-                x.1.compare(y.1)
+                compare_values(x.1, y.1, cross_type)
             };
             match ord_or_err {
                 Ok(r) => r,
@@ -1005,6 +1212,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     fn tuple(ref a: Option<Value>) -> anyhow::Result<Value<'v>> {
         let mut l = Vec::new();
         if let Some(a) = a {
+            l.reserve(a.length_hint().unwrap_or(0));
             a.with_iterator(heap, |it| {
                 l.extend(it);
             })?;
@@ -1014,7 +1222,11 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
 
     /// [type](
     /// https://github.com/google/skylark/blob/a0e5de7e63b47e716cca7226662a4c95d47bf873/doc/spec.md#type
-    /// ): returns a string describing the type of its operand.
+    /// ): returns the type of its operand.
+    ///
+    /// The result prints as the name of the type, and is equal to other types
+    /// with the same name, and to the name itself as a string. It is not a string,
+    /// so string methods and `+` need `str(type(x))`.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
@@ -1023,11 +1235,13 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
     /// type(1)                 == "int"
     /// type(())                == "tuple"
     /// type("hello")           == "string"
+    /// type(0)                 == type(1)
+    /// str(type(0))            == "int"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn r#type(ref a: Value) -> anyhow::Result<Value<'v>> {
-        Ok(a.get_type_value().unpack().to_value())
+        Ok(TypeValue::frozen(a.get_type_value()).to_value())
     }
 
     /// [zip](
@@ -1072,7 +1286,7 @@ pub(crate) fn global_functions(builder: &mut GlobalsBuilder) {
 
 #[cfg(test)]
 mod tests {
-    use crate::assert;
+    use crate::{assert, assert::Assert, syntax::Dialect};
 
     #[test]
     fn test_constants() {
@@ -1086,6 +1300,107 @@ mod tests {
         assert::fail("chr(0x110000)", "not a valid UTF-8");
     }
 
+    #[test]
+    fn test_any_all() {
+        assert::all_true(
+            r#"
+any([0, "", 1]) == True
+all([1, 0]) == False
+any([], lambda x: True) == False
+all([], lambda x: False) == True
+any(["a", "bb"], lambda x: len(x) == 2)
+not all(["a", "bb"], predicate=lambda x: len(x) == 2)
+"#,
+        );
+        // Iteration stops at the first decisive element, so the failing ones are never reached.
+        assert::all_true(
+            r#"
+any([1, 0], lambda x: 1 // x)
+not all([10, 0], lambda x: 10 // x - 1)
+"#,
+        );
+        assert::fail("any([1, 0], lambda x: not 1 // x)", "by zero");
+    }
+
+    #[test]
+    fn test_abs() {
+        assert::all_true(
+            r#"
+abs(0) == 0
+abs(-1) == 1
+abs(-0.0) == 0.0
+type(abs(-1.5)) == "float"
+"#,
+        );
+        assert::fail("abs(-2147483647 - 1)", "Integer overflow");
+        assert::fail("abs('x')", "must be an int or float");
+    }
+
+    #[test]
+    fn test_divmod() {
+        assert::all_true(
+            r#"
+divmod(7, 3) == (2, 1)
+divmod(7, -3) == (-3, -2)
+divmod(-7, -3) == (2, -1)
+divmod(6, 3) == (2, 0)
+divmod(-7.0, 2) == (-4.0, 1.0)
+"#,
+        );
+        assert::fail("divmod(1, 0)", "by zero");
+        assert::fail("divmod('a', 1)", "must be int or float");
+        assert::fail("divmod(1, [])", "must be int or float");
+    }
+
+    #[test]
+    fn test_reversed() {
+        assert::all_true(
+            r#"
+reversed([1, 2, 3]) == [3, 2, 1]
+reversed((1, 2, 3)) == [3, 2, 1]
+type(reversed((1, 2))) == "list"
+reversed(range(1, 10, 3)) == [7, 4, 1]
+reversed([]) == []
+"#,
+        );
+        assert::fail("reversed(1)", "not supported");
+    }
+
+    #[test]
+    fn test_round() {
+        assert::all_true(
+            r#"
+round(2.5) == 2
+round(1.5) == 2
+round(0.5) == 0
+round(-0.5) == 0
+round(-1.5) == -2
+round(2.6) == 3
+type(round(2.5)) == "int"
+round(2.5, None) == 2
+round(0.125, 2) == 0.12
+round(0.375, 2) == 0.38
+round(2.5, 0) == 2.0
+type(round(2.5, 0)) == "float"
+round(1234.5, -1) == 1230.0
+round(15, -1) == 20
+round(25, -1) == 20
+round(-15, -1) == -20
+round(7, 2) == 7
+round(123, -10) == 0
+round(-5, -19) == 0
+round(2147483647, -1000) == 0
+round(-2147483647 - 1, -2147483647 - 1) == 0
+round(1.5, 400) == 1.5
+"#,
+        );
+        assert::fail("round(2147483647, -1)", "Integer overflow");
+        assert::fail("round(float('inf'))", "cannot round");
+        assert::fail("round(float('nan'))", "cannot round");
+        assert::fail("round(1e20)", "Integer overflow");
+        assert::fail("round('x')", "must be an int or float");
+    }
+
     #[test]
     fn test_hash() {
         assert::eq("0", "hash('')");
@@ -1102,20 +1417,57 @@ hash("te") != hash("st")
 x = "test"; y = "te" + "st"; hash(y) == hash(y)
 "#,
         );
-        assert::fail("hash(None)", "doesn't match");
-        assert::fail("hash(True)", "doesn't match");
-        assert::fail("hash(1)", "doesn't match");
-        assert::fail("hash([])", "doesn't match");
-        assert::fail("hash({})", "doesn't match");
-        assert::fail("hash(range(1))", "doesn't match");
-        assert::fail("hash((1, 2))", "doesn't match");
-        assert::fail(
+        assert::all_true(
+            r#"
+hash(1) == hash(1)
+hash(None) == hash(None)
+hash(True) != hash(False)
+hash((1, "a")) == hash((1, "a"))
+hash((1, "a")) != hash(("a", 1))
+hash(type(1)) == hash("int")
+"#,
+        );
+        assert::pass(
             r#"
 def foo():
     pass
 hash(foo)
 "#,
-            "doesn't match",
         );
+        assert::fail("hash([])", "unhashable type: list");
+        assert::fail("hash({})", "unhashable type: dict");
+        assert::fail("hash(range(1))", "unhashable type: range");
+        assert::fail("hash((1, [2]))", "unhashable type: list");
+    }
+
+    #[test]
+    fn test_cross_type_ordering() {
+        let mut a = Assert::new();
+        a.dialect(&Dialect {
+            enable_cross_type_ordering: true,
+            ..Dialect::Extended
+        });
+        a.all_true(
+            r#"
+sorted([1, "b", 2.5, None, "a", 0]) == [None, 0, 1, 2.5, "a", "b"]
+sorted([(1, "a"), (1, 2), (0, None)]) == [(0, None), (1, 2), (1, "a")]
+sorted([[1], "x", [True]]) == [[True], [1], "x"]
+sorted(["a", 1], reverse = True) == ["a", 1]
+min([1, "a", 0.5]) == 0.5
+max(["a", 2, None]) == "a"
+# Values of different types which can be compared are ordered by value
+sorted(["str", type(1), "a", 1]) == [1, "a", type(1), "str"]
+sorted([type(""), "int", 2.5, 1]) == [1, 2.5, "int", type("")]
+"#,
+        );
+        // Values of the same type still fail if they can't be compared
+        a.fail("sorted([{}, {}])", "not supported");
+        // Comparison operators are unaffected
+        a.fail("1 < 'a'", "not supported");
+
+        // Without the flag, ordering values of different types fails
+        assert::fail("sorted([1, 'a'])", "not supported");
+        assert::fail("min([1, 'a'])", "not supported");
+        assert::fail("sorted([(1, 'a'), (1, 2)])", "not supported");
     }
 }
@@ -0,0 +1,325 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `glob` function, listing files through a [`FileSystem`] provided by the host.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use crate::{self as starlark, environment::GlobalsBuilder};
+
+#[derive(Debug, Error)]
+enum GlobError {
+    #[error("`glob` needs a file system, see `Evaluator::set_file_system`")]
+    NoFileSystem,
+    #[error("Invalid glob pattern `{0}`, {1}")]
+    InvalidPattern(String, &'static str),
+}
+
+/// An entry in a directory, as returned by [`FileSystem::list_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The name of the entry, without any directory.
+    pub name: String,
+    /// Is the entry a directory, rather than a file.
+    pub is_dir: bool,
+}
+
+/// The files visible to the `glob` function, set with
+/// [`Evaluator::set_file_system`](crate::eval::Evaluator::set_file_system).
+/// Paths are relative to the root the host chooses, such as the directory of the
+/// file being evaluated, with `/` separating directories. The root itself is `""`.
+pub trait FileSystem {
+    /// The entries in the directory `path`, in any order.
+    /// Fails if `path` is not a directory.
+    fn list_dir(&self, path: &str) -> anyhow::Result<Vec<DirEntry>>;
+
+    /// Is there a file (not a directory) at `path`.
+    fn exists(&self, path: &str) -> bool;
+}
+
+pub(crate) fn global(builder: &mut GlobalsBuilder) {
+    glob_members(builder)
+}
+
+#[starlark_module]
+fn glob_members(builder: &mut GlobalsBuilder) {
+    /// The files matching any of the `include` patterns, but none of the `exclude` patterns,
+    /// in sorted order. Directories are never returned. In a pattern, `*` matches any part of a
+    /// file or directory name, `?` matches any single character, and a `**` component matches
+    /// any number of directories, including none. So `glob(["**/*.txt"])` is every text file.
+    fn glob(include: Vec<&str>, exclude: Option<Vec<&str>>) -> anyhow::Result<Vec<String>> {
+        let fs = eval.file_system.ok_or(GlobError::NoFileSystem)?;
+        let exclude = exclude.unwrap_or_default();
+        let exclude = exclude
+            .iter()
+            .map(|x| split_pattern(x))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut res = BTreeSet::new();
+        for pattern in include {
+            let segments = split_pattern(pattern)?;
+            if segments.iter().all(|x| !is_wildcard(x)) {
+                if fs.exists(pattern) {
+                    res.insert(pattern.to_owned());
+                }
+            } else {
+                walk(fs, "", &segments, &mut res)?;
+            }
+        }
+        Ok(res
+            .into_iter()
+            .filter(|x| {
+                let path = x.split('/').collect::<Vec<_>>();
+                !exclude.iter().any(|e| matches_path(e, &path))
+            })
+            .collect())
+    }
+}
+
+fn is_wildcard(x: &str) -> bool {
+    x.contains(|c: char| c == '*' || c == '?')
+}
+
+fn split_pattern(pattern: &str) -> anyhow::Result<Vec<&str>> {
+    let err = |msg| -> anyhow::Result<Vec<&str>> {
+        Err(GlobError::InvalidPattern(pattern.to_owned(), msg).into())
+    };
+    if pattern.starts_with('/') {
+        return err("must be relative");
+    }
+    let segments = pattern.split('/').collect::<Vec<_>>();
+    for x in &segments {
+        match *x {
+            "" => return err("must not have empty components"),
+            "." | ".." => return err("must not contain `.` or `..`"),
+            _ if x.contains("**") && *x != "**" => {
+                return err("`**` must be a whole component");
+            }
+            _ => {}
+        }
+    }
+    Ok(segments)
+}
+
+/// Add the files under `dir` matching `pattern` to `res`.
+fn walk(
+    fs: &dyn FileSystem,
+    dir: &str,
+    pattern: &[&str],
+    res: &mut BTreeSet<String>,
+) -> anyhow::Result<()> {
+    let (first, rest) = match pattern.split_first() {
+        None => return Ok(()),
+        Some(x) => x,
+    };
+    if *first == "**" && !rest.is_empty() {
+        // `**` matching no directories
+        walk(fs, dir, rest, res)?;
+    }
+    for entry in fs.list_dir(dir)? {
+        let path = if dir.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", dir, entry.name)
+        };
+        if *first == "**" {
+            if entry.is_dir {
+                walk(fs, &path, pattern, res)?;
+            } else if rest.is_empty() {
+                res.insert(path);
+            }
+        } else if matches_name(first, &entry.name) {
+            if rest.is_empty() {
+                if !entry.is_dir {
+                    res.insert(path);
+                }
+            } else if entry.is_dir {
+                walk(fs, &path, rest, res)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Does the whole path, split into components, match the pattern.
+fn matches_path(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            matches_path(rest, path) || (!path.is_empty() && matches_path(pattern, &path[1..]))
+        }
+        (Some((p, pattern)), Some((x, path))) => matches_name(p, x) && matches_path(pattern, path),
+        _ => false,
+    }
+}
+
+/// Does a single file or directory name match a pattern with `*` and `?`.
+fn matches_name(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    // Standard backtracking match, only ever going back to the most recent `*`.
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        environment::{Globals, LibraryExtension, Module},
+        eval::Evaluator,
+        syntax::{AstModule, Dialect},
+    };
+
+    /// A file system of directories mapping to their entries.
+    struct MemoryFileSystem(HashMap<String, Vec<DirEntry>>);
+
+    impl MemoryFileSystem {
+        fn new(files: &[&str]) -> Self {
+            let mut dirs = HashMap::<String, Vec<DirEntry>>::new();
+            dirs.insert(String::new(), Vec::new());
+            for file in files {
+                let mut dir = String::new();
+                let parts = file.split('/').collect::<Vec<_>>();
+                for (i, name) in parts.iter().enumerate() {
+                    let is_dir = i + 1 < parts.len();
+                    let entries = dirs.entry(dir.clone()).or_default();
+                    if !entries.iter().any(|x| x.name == *name) {
+                        entries.push(DirEntry {
+                            name: (*name).to_owned(),
+                            is_dir,
+                        });
+                    }
+                    if !dir.is_empty() {
+                        dir.push('/');
+                    }
+                    dir.push_str(name);
+                }
+            }
+            Self(dirs)
+        }
+    }
+
+    impl FileSystem for MemoryFileSystem {
+        fn list_dir(&self, path: &str) -> anyhow::Result<Vec<DirEntry>> {
+            self.0
+                .get(path)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Not a directory: {}", path))
+        }
+
+        fn exists(&self, path: &str) -> bool {
+            let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+            self.0
+                .get(dir)
+                .map_or(false, |xs| xs.iter().any(|x| x.name == name && !x.is_dir))
+        }
+    }
+
+    fn glob(fs: Option<&dyn FileSystem>, code: &str) -> anyhow::Result<String> {
+        let globals = Globals::extended_by(&[LibraryExtension::Glob]);
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        if let Some(fs) = fs {
+            eval.set_file_system(fs);
+        }
+        let ast = AstModule::parse("BUILD", code.to_owned(), &Dialect::Standard)?;
+        Ok(eval.eval_module(ast, &globals)?.to_repr())
+    }
+
+    #[test]
+    fn test_glob() {
+        let fs = MemoryFileSystem::new(&[
+            "BUILD",
+            "a.txt",
+            "b.txt",
+            "c.rs",
+            "sub/d.txt",
+            "sub/e.rs",
+            "sub/deep/f.txt",
+            "sub/deep/g.txt",
+            "other/h.txt",
+        ]);
+        let fs = Some(&fs as &dyn FileSystem);
+        let check = |code: &str, want: &str| assert_eq!(glob(fs, code).unwrap(), want);
+        check("glob(['*.txt'])", r#"["a.txt", "b.txt"]"#);
+        check("glob(['?.rs', 'BUILD'])", r#"["BUILD", "c.rs"]"#);
+        check(
+            "glob(['**/*.txt'])",
+            r#"["a.txt", "b.txt", "other/h.txt", "sub/d.txt", "sub/deep/f.txt", "sub/deep/g.txt"]"#,
+        );
+        check(
+            "glob(['sub/**'], exclude = ['**/*.rs', 'sub/deep/g*'])",
+            r#"["sub/d.txt", "sub/deep/f.txt"]"#,
+        );
+        check(
+            "glob(include = ['**/*.txt'], exclude = ['sub/**'])",
+            r#"["a.txt", "b.txt", "other/h.txt"]"#,
+        );
+        // Overlapping patterns give each file once, and directories are never included
+        check("glob(['*.txt', 'a.*', 'sub'])", r#"["a.txt", "b.txt"]"#);
+        check(
+            "glob(['sub/*/f.txt', 'missing.txt', 'x/*'])",
+            r#"["sub/deep/f.txt"]"#,
+        );
+    }
+
+    #[test]
+    fn test_glob_errors() {
+        let fs = MemoryFileSystem::new(&["a.txt"]);
+        let fs = Some(&fs as &dyn FileSystem);
+        let fail = |code: &str, msg: &str| {
+            let err = glob(fs, code).unwrap_err().to_string();
+            assert!(err.contains(msg), "{}", err);
+        };
+        fail("glob(['/a.txt'])", "must be relative");
+        fail("glob(['../a.txt'])", "must not contain");
+        fail("glob(['a//b'])", "empty components");
+        fail("glob(['a**'])", "`**` must be a whole component");
+        let err = glob(None, "glob(['*'])").unwrap_err().to_string();
+        assert!(err.contains("needs a file system"), "{}", err);
+    }
+
+    #[test]
+    fn test_matches_name() {
+        assert!(matches_name("*", ""));
+        assert!(matches_name("a*c", "abbbc"));
+        assert!(matches_name("*.txt", ".txt"));
+        assert!(matches_name("a?c*", "abc"));
+        assert!(!matches_name("a?c", "ac"));
+        assert!(!matches_name("*.txt", "a.txt.rs"));
+    }
+}
@@ -0,0 +1,301 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `json` namespace, for converting between Starlark values and JSON strings.
+
+use std::{convert::TryFrom, fmt};
+
+use derive_more::Display;
+use gazebo::any::AnyLifetime;
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor},
+    Serialize,
+};
+use serde_json::ser::PrettyFormatter;
+use thiserror::Error;
+
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
+use crate::{
+    self as starlark,
+    collections::SmallMap,
+    environment::GlobalsBuilder,
+    eval::{Arguments, Evaluator},
+    values::{dict::Dict, FrozenStringValue, FrozenValue, Heap, StarlarkValue, Value},
+};
+
+#[derive(Debug, Error)]
+enum JsonError {
+    #[error("Invalid JSON, {0}")]
+    Decode(serde_json::Error),
+}
+
+pub(crate) fn global(builder: &mut GlobalsBuilder) {
+    let members = builder.namespace_fields(json_members);
+    builder.set("json", JsonNamespace(members))
+}
+
+/// The `json` global, a namespace of functions like a struct, which can also be called.
+/// Calling `json(x)` is a deprecated alias for `json.encode(x)`, kept for existing code.
+#[derive(Debug, Display, AnyLifetime, NoSerialize)]
+#[display(fmt = "json")]
+struct JsonNamespace(SmallMap<FrozenStringValue, FrozenValue>);
+starlark_simple_value!(JsonNamespace);
+
+impl<'v> StarlarkValue<'v> for JsonNamespace {
+    starlark_type!("json");
+
+    fn invoke(
+        &self,
+        _me: Value<'v>,
+        args: &Arguments<'v, '_>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<Value<'v>> {
+        args.no_named_args()?;
+        let x = args.positional1(eval.heap())?;
+        Ok(eval.heap().alloc(x.to_json()?))
+    }
+
+    fn get_attr(&self, attribute: &str, _heap: &'v Heap) -> Option<Value<'v>> {
+        self.0.get(attribute).map(|x| x.to_value())
+    }
+
+    fn has_attr(&self, attribute: &str) -> bool {
+        self.0.contains_key(attribute)
+    }
+
+    fn dir_attr(&self) -> Vec<String> {
+        self.0.keys().map(|x| x.as_str().to_owned()).collect()
+    }
+}
+
+#[starlark_module]
+fn json_members(builder: &mut GlobalsBuilder) {
+    /// Convert a value to a JSON string, failing if it contains a value with
+    /// no JSON representation, such as a function.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// json.encode([1, "a", None]) == '[1,"a",null]'
+    /// json.encode({"x": (True, 1.5)}) == '{"x":[true,1.5]}'
+    /// # "#);
+    /// ```
+    fn encode(ref x: Value) -> anyhow::Result<String> {
+        x.to_json()
+    }
+
+    /// Convert a JSON string to a value. Objects become dictionaries, arrays become lists,
+    /// and `null` becomes `None`. Integers which don't fit in 32 bits become bigints if
+    /// enabled, otherwise floats, as do all numbers written with a fraction or exponent.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// json.decode('[1, "a", null]') == [1, "a", None]
+    /// json.decode('{"x": [true, 1.5]}') == {"x": [True, 1.5]}
+    /// # "#);
+    /// ```
+    fn decode(ref x: &str) -> anyhow::Result<Value<'v>> {
+        decode_value(x, eval.bigint_enabled(), heap)
+    }
+
+    /// Reformat a JSON string, putting each element of an array or object on its own line,
+    /// with nested elements prefixed by `indent` once more per level.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// json.indent('{"x": [1]}') == '{\n  "x": [\n    1\n  ]\n}'
+    /// json.indent('[1]', indent = "\t") == '[\n\t1\n]'
+    /// # "#);
+    /// ```
+    fn indent(ref x: &str, indent: Option<&str>) -> anyhow::Result<String> {
+        let x = decode_value(x, eval.bigint_enabled(), heap)?;
+        let indent = indent.unwrap_or("  ");
+        let mut res = Vec::new();
+        let mut ser = serde_json::Serializer::with_formatter(
+            &mut res,
+            PrettyFormatter::with_indent(indent.as_bytes()),
+        );
+        x.serialize(&mut ser)?;
+        // Everything we wrote came from valid strings, so the result is valid UTF-8.
+        Ok(String::from_utf8(res)?)
+    }
+}
+
+fn decode_value<'v>(x: &str, bigint: bool, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+    let mut de = serde_json::Deserializer::from_str(x);
+    let res = JsonValue(heap, bigint)
+        .deserialize(&mut de)
+        .map_err(JsonError::Decode)?;
+    de.end().map_err(JsonError::Decode)?;
+    Ok(res)
+}
+
+/// Deserialize JSON directly into values on the heap, keeping the order of object keys.
+/// Large integers become bigints if the flag is set.
+#[derive(Clone, Copy)]
+struct JsonValue<'v>(&'v Heap, bool);
+
+impl<'de, 'v> DeserializeSeed<'de> for JsonValue<'v> {
+    type Value = Value<'v>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value<'v>, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'v> Visitor<'de> for JsonValue<'v> {
+    type Value = Value<'v>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value<'v>, E> {
+        Ok(Value::new_none())
+    }
+
+    fn visit_bool<E>(self, x: bool) -> Result<Value<'v>, E> {
+        Ok(Value::new_bool(x))
+    }
+
+    fn visit_i64<E>(self, x: i64) -> Result<Value<'v>, E> {
+        Ok(match i32::try_from(x) {
+            Ok(x) => Value::new_int(x),
+            Err(_) => large_int(x, x as f64, self.1, self.0),
+        })
+    }
+
+    fn visit_u64<E>(self, x: u64) -> Result<Value<'v>, E> {
+        Ok(match i32::try_from(x) {
+            Ok(x) => Value::new_int(x),
+            Err(_) => large_int(x, x as f64, self.1, self.0),
+        })
+    }
+
+    fn visit_f64<E>(self, x: f64) -> Result<Value<'v>, E> {
+        Ok(self.0.alloc(x))
+    }
+
+    fn visit_str<E>(self, x: &str) -> Result<Value<'v>, E> {
+        Ok(self.0.alloc(x))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value<'v>, A::Error> {
+        let mut res = Vec::new();
+        while let Some(x) = seq.next_element_seed(self)? {
+            res.push(x);
+        }
+        Ok(self.0.alloc_list(&res))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value<'v>, A::Error> {
+        let mut res = SmallMap::new();
+        // serde_json only produces string keys, so hashing can't fail
+        while let Some(k) = map.next_key::<String>()? {
+            let k = self.0.alloc(k).get_hashed().map_err(de::Error::custom)?;
+            let v = map.next_value_seed(self)?;
+            res.insert_hashed(k, v);
+        }
+        Ok(self.0.alloc(Dict::new(res)))
+    }
+}
+
+/// An integer which doesn't fit in 32 bits, as a bigint if enabled, otherwise as the float `f`.
+#[cfg(feature = "bigint")]
+fn large_int<'v>(x: impl Into<BigInt>, f: f64, bigint: bool, heap: &'v Heap) -> Value<'v> {
+    if bigint {
+        StarlarkBigInt::alloc(x.into(), heap)
+    } else {
+        heap.alloc(f)
+    }
+}
+
+#[cfg(not(feature = "bigint"))]
+fn large_int<'v, T>(_x: T, f: f64, _bigint: bool, heap: &'v Heap) -> Value<'v> {
+    heap.alloc(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_round_trip() {
+        assert::all_true(
+            r#"
+json.decode(json.encode([1, -2, 1.5, "x", True, False, None])) == [1, -2, 1.5, "x", True, False, None]
+json.decode(json.encode({"b": {"c": []}, "a": (1,)})) == {"b": {"c": []}, "a": [1]}
+list(json.decode('{"z": 1, "a": 2}').keys()) == ["z", "a"]
+json.decode('"\\u00e9"') == "é"
+json.decode('3000000000') == 3000000000.0
+json.decode(' 1 ') == 1
+json.decode(json.indent('{"x": [1, {}]}')) == {"x": [1, {}]}
+json.indent('[]') == "[]"
+"#,
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert::fail("json.decode('[1,')", "Invalid JSON");
+        assert::fail("json.decode('[1] 2')", "Invalid JSON");
+        assert::fail("json.decode('{1: 2}')", "Invalid JSON");
+        assert::fail("json.indent('x')", "Invalid JSON");
+        assert::fail("json.encode(len)", "not supported");
+    }
+
+    #[test]
+    fn test_deprecated_call() {
+        assert::all_true(
+            r#"
+json([1, "a", None]) == '[1,"a",null]'
+json({"x": (True, 1.5)}) == json.encode({"x": (True, 1.5)})
+type(json) == "json"
+"encode" in dir(json)
+hasattr(json, "decode")
+not hasattr(json, "loads")
+"#,
+        );
+        assert::fail("json()", "Wrong number of positional parameters");
+        assert::fail("json(1, 2)", "Wrong number of positional parameters");
+        assert::fail("json(x = 1)", "extra named parameter");
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_decode_bigint() {
+        use crate::{assert::Assert, syntax::Dialect};
+
+        let mut a = Assert::new();
+        a.dialect(&Dialect {
+            enable_bigint: true,
+            ..Dialect::Extended
+        });
+        a.all_true(
+            r#"
+json.decode('3000000000') == 3000 * 1000000
+type(json.decode('3000000000')) == "int"
+json.decode('-9223372036854775808') == -pow(2, 63)
+json.decode('18446744073709551615') == pow(2, 64) - 1
+json.decode('[2147483647, 2147483648]') == [2147483647, 2147483647 + 1]
+json.decode('1e10') == 10000000000.0
+"#,
+        );
+    }
+}
@@ -0,0 +1,168 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `math` namespace, with functions on ints and floats.
+
+use thiserror::Error;
+
+use crate::{self as starlark, environment::GlobalsBuilder, values::num::Num};
+
+#[derive(Debug, Error)]
+enum MathError {
+    #[error("math.{0}() domain error, got {1}")]
+    Domain(&'static str, String),
+    #[error("math.{0}() cannot convert float to integer: {1}")]
+    ToInt(&'static str, f64),
+}
+
+pub(crate) fn global(builder: &mut GlobalsBuilder) {
+    builder.struct_("math", math_members)
+}
+
+/// Round an int or float using `f`, failing if the result doesn't fit in an int.
+fn to_int(name: &'static str, x: Num, f: fn(f64) -> f64) -> anyhow::Result<i32> {
+    match x {
+        Num::Int(i) => Ok(i),
+        Num::Float(x) => match Num::from(f(x)).as_int() {
+            Some(i) => Ok(i),
+            None => Err(MathError::ToInt(name, x).into()),
+        },
+    }
+}
+
+#[starlark_module]
+fn math_members(builder: &mut GlobalsBuilder) {
+    /// The square root of `x`, failing if `x` is negative.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// math.sqrt(4) == 2.0
+    /// math.sqrt(2.25) == 1.5
+    /// # "#);
+    /// ```
+    fn sqrt(ref x: Num) -> anyhow::Result<f64> {
+        let x = x.as_float();
+        if x < 0.0 {
+            return Err(MathError::Domain("sqrt", x.to_string()).into());
+        }
+        Ok(x.sqrt())
+    }
+
+    /// The largest int less than or equal to `x`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// math.floor(1.5) == 1
+    /// math.floor(-1.5) == -2
+    /// math.floor(3) == 3
+    /// # "#);
+    /// ```
+    fn floor(ref x: Num) -> anyhow::Result<i32> {
+        to_int("floor", x, f64::floor)
+    }
+
+    /// The smallest int greater than or equal to `x`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// math.ceil(1.5) == 2
+    /// math.ceil(-1.5) == -1
+    /// math.ceil(3) == 3
+    /// # "#);
+    /// ```
+    fn ceil(ref x: Num) -> anyhow::Result<i32> {
+        to_int("ceil", x, f64::ceil)
+    }
+
+    /// `x` raised to the power `y`, as a float. Fails if `x` is negative and `y` is not
+    /// a whole number, or if `x` is zero and `y` is negative.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// math.pow(2, 10) == 1024.0
+    /// math.pow(4, 0.5) == 2.0
+    /// math.pow(-2, 3) == -8.0
+    /// # "#);
+    /// ```
+    fn pow(ref x: Num, ref y: Num) -> anyhow::Result<f64> {
+        let (x, y) = (x.as_float(), y.as_float());
+        if (x < 0.0 && y.fract() != 0.0) || (x == 0.0 && y < 0.0) {
+            return Err(MathError::Domain("pow", format!("{}, {}", x, y)).into());
+        }
+        Ok(x.powf(y))
+    }
+
+    /// The logarithm of `x` to the given `base`, or the natural logarithm if no base is given.
+    /// Fails unless `x` is positive and `base` is positive and not 1.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// math.log(1) == 0.0
+    /// math.log(8, 2) == 3.0
+    /// math.log(100, base = 10) == 2.0
+    /// # "#);
+    /// ```
+    fn log(ref x: Num, base: Option<Num>) -> anyhow::Result<f64> {
+        let x = x.as_float();
+        if x <= 0.0 {
+            return Err(MathError::Domain("log", x.to_string()).into());
+        }
+        match base {
+            None => Ok(x.ln()),
+            Some(base) => {
+                let base = base.as_float();
+                if base <= 0.0 || base == 1.0 {
+                    return Err(MathError::Domain("log", format!("base {}", base)).into());
+                }
+                Ok(x.log(base))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_math() {
+        assert::all_true(
+            r#"
+math.sqrt(0) == 0.0
+type(math.sqrt(4)) == "float"
+type(math.floor(1.5)) == "int"
+math.floor(-0.5) == -1
+math.ceil(0.1) == 1
+math.pow(0, 0) == 1.0
+math.pow(2, -1) == 0.5
+math.log(1024, 2) == 10.0
+"#,
+        );
+    }
+
+    #[test]
+    fn test_domain_errors() {
+        assert::fail("math.sqrt(-1)", "math.sqrt() domain error, got -1");
+        assert::fail("math.pow(-8, 0.5)", "math.pow() domain error");
+        assert::fail("math.pow(0, -1)", "math.pow() domain error");
+        assert::fail("math.log(0)", "math.log() domain error");
+        assert::fail("math.log(8, 1)", "math.log() domain error, got base 1");
+        assert::fail("math.floor(1e100)", "cannot convert float to integer");
+        assert::fail("math.ceil(-1e100)", "cannot convert float to integer");
+        assert::fail("math.sqrt('4')", "Type of parameter `x` doesn't match");
+    }
+}
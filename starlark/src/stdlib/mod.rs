@@ -21,13 +21,20 @@
 use crate::environment::GlobalsBuilder;
 
 pub(crate) mod breakpoint;
+pub(crate) mod bytes;
+pub(crate) mod depset;
 pub(crate) mod dict;
 pub(crate) mod enumeration;
 pub(crate) mod extra;
+pub(crate) mod frozenset;
 mod funcs;
+pub(crate) mod glob;
+pub(crate) mod json;
 use gazebo::prelude::*;
 pub(crate) mod list;
+pub(crate) mod math;
 pub(crate) mod record;
+pub(crate) mod select;
 pub(crate) mod string;
 pub(crate) mod structs;
 pub(crate) mod util;
@@ -60,6 +67,12 @@ pub enum LibraryExtension {
     /// Partially apply a function, `partial(f, *args, **kwargs)` will create a function where those `args` `kwargs`
     /// are already applied to `f`.
     Partial,
+    /// A function `reduce(f, xs, initializer)` which combines the elements of `xs` from left
+    /// to right with the two argument function `f`, starting from `initializer` if given.
+    Reduce,
+    /// A function `pow(base, exp, modulus)` which raises `base` to the power `exp`, and if `modulus`
+    /// is given, computes `pow(base, exp) % modulus` without the large intermediate result.
+    Pow,
     /// Remove duplicate entries in the list, using pointer-based equality always.
     Dedupe,
     /// Add a function `debug(x)` which shows the Rust [`Debug`](std::fmt::Debug) representation of a value.
@@ -71,24 +84,65 @@ pub enum LibraryExtension {
     Pprint,
     /// Add a function `breakpoint()` which will drop into a console-module evaluation prompt.
     Breakpoint,
-    /// Add a function `json()` which will generate JSON for a module.
+    /// Add a namespace `json`, with functions `json.encode(x)` to convert a value to JSON,
+    /// `json.decode(x)` to convert JSON to a value, and `json.indent(x)` to pretty-print JSON.
+    /// Calling `json(x)` directly still works, as a deprecated alias for `json.encode(x)`.
     Json,
-    /// Add a function `abs()` which will take the absolute value of an int.
+    /// Definitions to support the `frozenset` type, the `frozenset()` constructor.
+    FrozenSetType,
+    /// Previously added a function `abs()`, which is now always available as a global.
+    /// Kept for compatibility, adds nothing.
+    #[deprecated(note = "`abs` is always available as a global")]
     Abs,
+    /// Add a function `isinstance(x, t)` which checks whether `type(x)` is `t`, where `t`
+    /// is a type name, a type object, or a tuple of either.
+    IsInstance,
+    /// Add a namespace `math`, with functions `math.sqrt`, `math.floor`, `math.ceil`,
+    /// `math.pow` and `math.log`.
+    Math,
+    /// Add a function `glob(include, exclude)` which lists the files matching some patterns,
+    /// using the [`FileSystem`](crate::eval::FileSystem) given to
+    /// [`Evaluator::set_file_system`](crate::eval::Evaluator::set_file_system).
+    Glob,
+    /// Definitions to support the `select` type, the `select()` constructor.
+    SelectType,
+    /// Definitions to support the `depset` type, the `depset()` constructor.
+    DepsetType,
     // Make sure if you add anything new, you add it to `all` below.
 }
 
 impl LibraryExtension {
     /// A list of all extensions that will be updated as new methods are added.
+    #[allow(deprecated)]
     pub fn all() -> &'static [Self] {
         use LibraryExtension::*;
         &[
-            StructType, RecordType, EnumType, Map, Filter, Partial, Dedupe, Debug, Print, Pprint,
-            Breakpoint, Json, Abs,
+            StructType,
+            RecordType,
+            EnumType,
+            Map,
+            Filter,
+            Partial,
+            Reduce,
+            Pow,
+            Dedupe,
+            Debug,
+            Print,
+            Pprint,
+            Breakpoint,
+            Json,
+            FrozenSetType,
+            Abs,
+            IsInstance,
+            Math,
+            Glob,
+            SelectType,
+            DepsetType,
         ]
     }
 
     /// Add a specific extension to a [`GlobalsBuilder`].
+    #[allow(deprecated)]
     pub fn add(self, builder: &mut GlobalsBuilder) {
         use LibraryExtension::*;
         match self {
@@ -98,13 +152,21 @@ impl LibraryExtension {
             Map => extra::map(builder),
             Filter => extra::filter(builder),
             Partial => extra::partial(builder),
+            Reduce => extra::reduce(builder),
+            Pow => extra::pow(builder),
             Dedupe => extra::dedupe(builder),
             Debug => extra::debug(builder),
             Print => extra::print(builder),
             Pprint => extra::pprint(builder),
             Breakpoint => breakpoint::global(builder),
-            Json => extra::json(builder),
-            Abs => extra::abs(builder),
+            Json => json::global(builder),
+            FrozenSetType => frozenset::global(builder),
+            Abs => {}
+            IsInstance => extra::is_instance(builder),
+            Math => math::global(builder),
+            Glob => glob::global(builder),
+            SelectType => select::global(builder),
+            DepsetType => depset::global(builder),
         }
     }
 }
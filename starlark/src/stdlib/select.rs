@@ -0,0 +1,43 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of `select` function.
+use crate as starlark;
+use crate::{
+    collections::SmallMap,
+    environment::GlobalsBuilder,
+    values::{select::Select, Value},
+};
+
+#[starlark_module]
+pub fn global(builder: &mut GlobalsBuilder) {
+    /// Create a value which depends on configuration, mapping conditions to values.
+    /// The host picks the value once evaluation is over, using the first condition which holds,
+    /// or `"//conditions:default"` if none do. Selects can be added to each other,
+    /// and to other values, such as lists.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// type(select({"linux": ["a.c"], "//conditions:default": []})) == "select"
+    /// type(["b.c"] + select({"linux": ["a.c"]})) == "select"
+    /// # "#);
+    /// ```
+    #[starlark(type(Select::TYPE))]
+    fn select(ref conditions: SmallMap<String, Value<'v>>) -> anyhow::Result<Select<'v>> {
+        Select::new(conditions)
+    }
+}
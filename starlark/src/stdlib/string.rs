@@ -17,17 +17,20 @@
 
 //! Methods for the `string` type.
 
-use std::cmp;
+use std::{cmp, iter};
 
 use anyhow::anyhow;
 use gazebo::prelude::*;
 
 use crate as starlark;
 use crate::{
+    collections::SmallMap,
     environment::MethodsBuilder,
     eval::Arguments,
     stdlib::string::fast_string::convert_str_indices,
     values::{
+        bytes::Bytes,
+        dict::{Dict, DictRef},
         none::NoneOr,
         string::{fast_string, interpolation},
         tuple::Tuple,
@@ -94,6 +97,42 @@ fn rsplitn_whitespace(s: &str, maxsplit: usize) -> Vec<String> {
     v
 }
 
+/// Pad `s` with `fillchar` to `width` characters, putting `left(padding)` of the padding
+/// characters before `s` and the rest after. Returns `None` if `s` is already long enough.
+fn pad(
+    s: &str,
+    width: i32,
+    fillchar: Option<&str>,
+    left: impl Fn(usize) -> usize,
+) -> anyhow::Result<Option<String>> {
+    let fill = match fillchar {
+        None => ' ',
+        Some(fillchar) => {
+            let mut cs = fillchar.chars();
+            match (cs.next(), cs.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(anyhow!(
+                        "The fill character must be exactly one character long, got `{}`",
+                        fillchar
+                    ));
+                }
+            }
+        }
+    };
+    let len = s.chars().count();
+    if width <= 0 || width as usize <= len {
+        return Ok(None);
+    }
+    let padding = width as usize - len;
+    let left = left(padding);
+    let mut res = String::with_capacity(s.len() + padding * fill.len_utf8());
+    res.extend(iter::repeat(fill).take(left));
+    res.push_str(s);
+    res.extend(iter::repeat(fill).take(padding - left));
+    Ok(Some(res))
+}
+
 enum StringOrTuple<'v> {
     String(&'v str),
     Tuple(Vec<&'v str>),
@@ -181,6 +220,38 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         Ok(result)
     }
 
+    /// [string.center](
+    /// https://docs.python.org/3/library/stdtypes.html#str.center
+    /// ): centre a string in a given width. _Not part of standard Starlark._
+    ///
+    /// `S.center(width[, fillchar])` returns S padded on both sides with `fillchar`
+    /// (a space by default) to be `width` characters long. When the padding can't be split
+    /// evenly, the extra character goes on the same side as Python would put it.
+    /// If S is already at least `width` characters long, it is returned unchanged.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "abc".center(7) == "  abc  "
+    /// "abc".center(6, "*") == "*abc**"
+    /// "abc".center(2) == "abc"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn center(
+        this: Value<'v>,
+        ref width: i32,
+        ref fillchar: Option<&str>,
+    ) -> anyhow::Result<Value<'v>> {
+        // Matches CPython, which rounds the left padding up when `width` is odd
+        let left = |padding: usize| padding / 2 + (padding & width as usize & 1);
+        match pad(this.unpack_str().unwrap(), width, fillchar, left)? {
+            Some(res) => Ok(heap.alloc(res)),
+            None => Ok(this),
+        }
+    }
+
     /// [string.codepoints](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·codepoints
     /// ): returns an iterable of the unicode codepoint of a string.
@@ -223,6 +294,9 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// for `[aba]a[aba]`, not counting the middle occurence: `ab[aba]ba`
     /// (this is following Python behavior).
     ///
+    /// As in Python, the empty string occurs before and after every character,
+    /// so counting it gives one more than the number of characters searched.
+    ///
     /// Examples:
     ///
     /// ```
@@ -230,6 +304,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "hello, world!".count("o") == 2
     /// "abababa".count("aba") == 2
     /// "hello, world!".count("o", 7, 12) == 1  # in "world"
+    /// "abc".count("") == 4
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -268,6 +343,54 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         }
     }
 
+    /// [string.expandtabs](
+    /// https://docs.python.org/3/library/stdtypes.html#str.expandtabs
+    /// ): replace tabs with spaces. _Not part of standard Starlark._
+    ///
+    /// `S.expandtabs(tabsize=8)` returns a copy of S where each tab is replaced by
+    /// enough spaces to reach the next column that is a multiple of `tabsize`.
+    /// The column goes back to zero after each `\n` or `\r`.
+    /// If `tabsize` is zero or negative, tabs are removed.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "a\tb".expandtabs() == "a       b"
+    /// "ab\tc".expandtabs(4) == "ab  c"
+    /// "a\n\tb".expandtabs(2) == "a\n  b"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn expandtabs(this: Value<'v>, tabsize @ 8: i32) -> anyhow::Result<Value<'v>> {
+        let x = this.unpack_str().unwrap();
+        if !x.contains('\t') {
+            return Ok(this);
+        }
+        let mut res = String::with_capacity(x.len());
+        let mut column = 0;
+        for c in x.chars() {
+            match c {
+                '\t' => {
+                    if tabsize > 0 {
+                        let spaces = tabsize as usize - column % tabsize as usize;
+                        res.extend(iter::repeat(' ').take(spaces));
+                        column += spaces;
+                    }
+                }
+                '\n' | '\r' => {
+                    res.push(c);
+                    column = 0;
+                }
+                _ => {
+                    res.push(c);
+                    column += 1;
+                }
+            }
+        }
+        Ok(heap.alloc(res))
+    }
+
     /// [string.find](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·find
     /// ): find a substring in a string.
@@ -364,7 +487,34 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         interpolation::format(
             this,
             iter,
-            args.names()?,
+            &args.names()?,
+            &mut eval.string_pool,
+            eval.module_env.heap(),
+        )
+    }
+
+    /// [string.format_map](
+    /// https://docs.python.org/3/library/stdtypes.html#str.format_map
+    /// ): format a string, taking the replacement fields from a dictionary.
+    ///
+    /// `S.format_map(mapping)` is like `S.format(**mapping)`, except the
+    /// dictionary is used directly. Each replacement field must be a name,
+    /// and it is an error if the name is not a key of the dictionary.
+    ///
+    /// Examples:
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// "{a} and {b}".format_map({"a": 1, "b": "two"}) == "1 and two"
+    /// "Is {x!r}?".format_map({"x": "y"}) == "Is \"y\"?"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn format_map(this: &str, ref mapping: DictRef) -> anyhow::Result<StringValue<'v>> {
+        interpolation::format(
+            this,
+            iter::empty(),
+            &mapping,
             &mut eval.string_pool,
             eval.module_env.heap(),
         )
@@ -700,6 +850,35 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         })?
     }
 
+    /// [string.ljust](
+    /// https://docs.python.org/3/library/stdtypes.html#str.ljust
+    /// ): left-justify a string in a given width. _Not part of standard Starlark._
+    ///
+    /// `S.ljust(width[, fillchar])` returns S padded on the right with `fillchar`
+    /// (a space by default) to be `width` characters long.
+    /// If S is already at least `width` characters long, it is returned unchanged.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "abc".ljust(5) == "abc  "
+    /// "abc".ljust(5, "-") == "abc--"
+    /// "abc".ljust(2) == "abc"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn ljust(
+        this: Value<'v>,
+        ref width: i32,
+        ref fillchar: Option<&str>,
+    ) -> anyhow::Result<Value<'v>> {
+        match pad(this.unpack_str().unwrap(), width, fillchar, |_| 0)? {
+            Some(res) => Ok(heap.alloc(res)),
+            None => Ok(this),
+        }
+    }
+
     /// [string.lstrip](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·lstrip
     /// ): trim leading whitespaces.
@@ -727,7 +906,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·partition
     /// ): partition a string in 3 components
     ///
-    /// `S.partition(x = " ")` splits string S into three parts and returns them
+    /// `S.partition(x)` splits string S into three parts and returns them
     /// as a tuple: the portion before the first occurrence of string `x`,
     /// `x` itself, and the portion following it.
     /// If S does not contain `x`, `partition` returns `(S, "", "")`.
@@ -865,11 +1044,42 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         Err(anyhow!("Substring '{}' not found in '{}'", needle, this))
     }
 
+    /// [string.rjust](
+    /// https://docs.python.org/3/library/stdtypes.html#str.rjust
+    /// ): right-justify a string in a given width. _Not part of standard Starlark._
+    ///
+    /// `S.rjust(width[, fillchar])` returns S padded on the left with `fillchar`
+    /// (a space by default) to be `width` characters long.
+    /// If S is already at least `width` characters long, it is returned unchanged.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "abc".rjust(5) == "  abc"
+    /// "7".rjust(3, "0") == "007"
+    /// "abc".rjust(2) == "abc"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn rjust(
+        this: Value<'v>,
+        ref width: i32,
+        ref fillchar: Option<&str>,
+    ) -> anyhow::Result<Value<'v>> {
+        match pad(this.unpack_str().unwrap(), width, fillchar, |padding| {
+            padding
+        })? {
+            Some(res) => Ok(heap.alloc(res)),
+            None => Ok(this),
+        }
+    }
+
     /// [string.rpartition](
     /// https://github.com/google/skylark/blob/3705afa472e466b8b061cce44b47c9ddc6db696d/doc/spec.md#string·rpartition
     /// ): partition a string in 3 elements.
     ///
-    /// `S.rpartition([x = ' '])` is like `partition`, but splits `S` at the
+    /// `S.rpartition(x)` is like `partition`, but splits `S` at the
     /// last occurrence of `x`.
     ///
     /// Examples:
@@ -1072,7 +1282,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
-    fn splitlines(this: &str, ref keepends @ false: bool) -> anyhow::Result<Value<'v>> {
+    fn splitlines(this: &str, keepends @ false: bool) -> anyhow::Result<Value<'v>> {
         let mut s = this;
         let mut lines = Vec::new();
         loop {
@@ -1262,6 +1472,158 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
             Ok(this)
         }
     }
+
+    /// [string.encode](
+    /// https://docs.python.org/3/library/stdtypes.html#str.encode
+    /// ): encode a string as bytes. _Not part of standard Starlark._
+    ///
+    /// `S.encode(encoding="utf-8")` returns the bytes of S in the given encoding,
+    /// which must be UTF-8. Use `decode` to turn the bytes back into a string.
+    /// Fails unless [`enable_bytes`](crate::syntax::Dialect::enable_bytes) is set.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "hello".encode().decode() == "hello"
+    /// type("hello".encode("utf-8")) == "bytes"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn encode(this: &str, ref encoding: Option<&str>) -> anyhow::Result<Bytes> {
+        Bytes::encode(this, encoding, eval.bytes_enabled())
+    }
+
+    /// [string.maketrans](
+    /// https://docs.python.org/3/library/stdtypes.html#str.maketrans
+    /// ): build a translation table for `translate`. _Not part of standard Starlark._
+    ///
+    /// `S.maketrans(x[, y[, z]])` ignores S and returns a dictionary mapping codepoints
+    /// to their replacement. Given only `x`, it must be a dictionary whose keys are
+    /// single characters or codepoints. Otherwise, `x` and `y` must be strings of the
+    /// same length, and each character in `x` is replaced by the character at the same
+    /// position in `y`. The characters in the optional string `z` are mapped to `None`,
+    /// so `translate` deletes them.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "".maketrans("ab", "xy") == {97: 120, 98: 121}
+    /// "".maketrans("a", "x", "c") == {97: 120, 99: None}
+    /// "".maketrans({"a": "xx", 98: None}) == {97: "xx", 98: None}
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn maketrans(
+        _this: &str,
+        ref x: Value,
+        ref y: Option<&str>,
+        ref z: Option<&str>,
+    ) -> anyhow::Result<Value<'v>> {
+        let mut res = SmallMap::new();
+        match y {
+            None => {
+                if z.is_some() {
+                    return Err(anyhow!(
+                        "maketrans() with three arguments requires strings for the first two"
+                    ));
+                }
+                let x = match DictRef::from_value(x) {
+                    Some(x) => x,
+                    None => {
+                        return Err(anyhow!(
+                            "maketrans() with one argument requires a dict, got `{}`",
+                            x.get_type()
+                        ));
+                    }
+                };
+                for (k, v) in x.iter() {
+                    let k = if let Some(k) = k.unpack_int() {
+                        k
+                    } else {
+                        match k.unpack_str().map(|k| k.chars().collect::<Vec<_>>()) {
+                            Some(cs) if cs.len() == 1 => cs[0] as i32,
+                            _ => {
+                                return Err(anyhow!(
+                                    "maketrans() keys must be characters or codepoints, got `{}`",
+                                    k.to_repr()
+                                ));
+                            }
+                        }
+                    };
+                    res.insert_hashed(Value::new_int(k).get_hashed()?, v);
+                }
+            }
+            Some(y) => {
+                let x = match x.unpack_str() {
+                    Some(x) => x,
+                    None => {
+                        return Err(anyhow!(
+                            "maketrans() with two arguments requires strings, got `{}`",
+                            x.get_type()
+                        ));
+                    }
+                };
+                if x.chars().count() != y.chars().count() {
+                    return Err(anyhow!("maketrans() arguments must have the same length"));
+                }
+                for (a, b) in x.chars().zip(y.chars()) {
+                    res.insert_hashed(
+                        Value::new_int(a as i32).get_hashed()?,
+                        Value::new_int(b as i32),
+                    );
+                }
+                for c in z.unwrap_or_default().chars() {
+                    res.insert_hashed(Value::new_int(c as i32).get_hashed()?, Value::new_none());
+                }
+            }
+        }
+        Ok(heap.alloc(Dict::new(res)))
+    }
+
+    /// [string.translate](
+    /// https://docs.python.org/3/library/stdtypes.html#str.translate
+    /// ): replace or delete characters using a table. _Not part of standard Starlark._
+    ///
+    /// `S.translate(table)` returns a copy of S where each character whose codepoint
+    /// is a key of the dictionary `table` is replaced by the corresponding value,
+    /// which may be a string, a codepoint, or `None` to delete the character.
+    /// Characters which aren't in the table are unchanged.
+    /// The table is usually created with `maketrans`.
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "hello".translate("".maketrans("el", "ip")) == "hippo"
+    /// "a-b_c".translate("".maketrans("", "", "-_")) == "abc"
+    /// "abc".translate({97: "AA", 98: None, 99: 100}) == "AAd"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn translate(this: &str, ref table: DictRef) -> anyhow::Result<String> {
+        let mut res = String::with_capacity(this.len());
+        for c in this.chars() {
+            match table.get(Value::new_int(c as i32))? {
+                None => res.push(c),
+                Some(v) if v.is_none() => {}
+                Some(v) => {
+                    if let Some(s) = v.unpack_str() {
+                        res.push_str(s);
+                    } else if let Some(c) = v.unpack_int().and_then(|i| char::from_u32(i as u32)) {
+                        res.push(c);
+                    } else {
+                        return Err(anyhow!(
+                            "translate() table values must be strings, codepoints or None, got `{}`",
+                            v.to_repr()
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
 }
 
 #[cfg(test)]
@@ -1278,6 +1640,43 @@ mod tests {
     #[test]
     fn test_count() {
         assert::eq("'abc'.count('a', 10, -10)", "0");
+        assert::all_true(
+            r#"
+"banana".count("a") == 3
+"banana".count("ana") == 1
+"aaaa".count("aa") == 2
+"banana".count("a", 2) == 2
+"banana".count("a", 2, 4) == 1
+"banana".count("a", -2) == 1
+"banana".count("a", 4, 2) == 0
+"banana".count("x") == 0
+"banana".count("") == 7
+"".count("") == 1
+"héllo".count("") == 6
+"banana".count("", 2, 4) == 3
+"banana".count("", 6) == 1
+"banana".count("", 7) == 0
+"#,
+        );
+    }
+
+    #[test]
+    fn test_expandtabs() {
+        assert::all_true(
+            r#"
+"\t".expandtabs() == " " * 8
+"abc\tdef".expandtabs() == "abc     def"
+"12345678\tx".expandtabs() == "12345678        x"
+"a\tbc\td".expandtabs(4) == "a   bc  d"
+"a\tb".expandtabs(tabsize=1) == "a b"
+"line1\n\tx\r\ty".expandtabs(4) == "line1\n    x\r    y"
+"ab\n12345\tz".expandtabs(4) == "ab\n12345   z"
+"é\tx".expandtabs(4) == "é   x"
+"a\tb".expandtabs(0) == "ab"
+"a\tb".expandtabs(-1) == "ab"
+"no tabs".expandtabs() == "no tabs"
+"#,
+        );
     }
 
     #[test]
@@ -1285,6 +1684,89 @@ mod tests {
         assert::eq("'Троянская война окончена'.find('война')", "10");
     }
 
+    #[test]
+    fn test_format_map() {
+        assert::all_true(
+            r#"
+"{a}-{b}-{a}".format_map({"a": 1, "b": [2]}) == "1-[2]-1"
+"no fields".format_map({}) == "no fields"
+"{{a}}".format_map({"a": 1}) == "{a}"
+"#,
+        );
+        assert::fail(r#""{a} {c}".format_map({"a": 1})"#, "not found");
+    }
+
+    #[test]
+    fn test_partition() {
+        assert::all_true(
+            r#"
+"key=value=more".partition("=") == ("key", "=", "value=more")
+"key=value=more".rpartition("=") == ("key=value", "=", "more")
+"no separator".partition("=") == ("no separator", "", "")
+"no separator".rpartition("=") == ("", "", "no separator")
+"a::b::c".partition("::") == ("a", "::", "b::c")
+"a::b::c".rpartition("::") == ("a::b", "::", "c")
+"=".partition("=") == ("", "=", "")
+"".rpartition("=") == ("", "", "")
+"#,
+        );
+        assert::fail(r#""abc".partition("")"#, "Empty separator");
+        assert::fail(r#""abc".rpartition("")"#, "Empty separator");
+    }
+
+    #[test]
+    fn test_splitlines() {
+        assert::all_true(
+            r#"
+"a\nb\r\nc\rd".splitlines() == ["a", "b", "c", "d"]
+"a\nb\r\nc\rd".splitlines(keepends=True) == ["a\n", "b\r\n", "c\r", "d"]
+"a\n\r\nb\n".splitlines() == ["a", "", "b"]
+"a\r\n".splitlines(keepends=True) == ["a\r\n"]
+"\n".splitlines() == [""]
+"".splitlines() == []
+"#,
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        assert::all_true(
+            r#"
+"abc".translate("".maketrans("abc", "xyz")) == "xyz"
+"hello world".translate("".maketrans("lo", "01")) == "he001 w1r0d"
+"a,b;c d".translate("".maketrans("", "", ",; ")) == "abcd"
+"abc".translate("".maketrans("a", "A", "c")) == "Ab"
+"aàb".translate("".maketrans({"à": "a", "b": None})) == "aa"
+"abc".translate({}) == "abc"
+"#,
+        );
+        assert::fail(r#""".maketrans("ab", "x")"#, "same length");
+        assert::fail(r#""".maketrans("ab")"#, "requires a dict");
+        assert::fail(r#""".maketrans({"ab": "x"})"#, "characters or codepoints");
+        assert::fail(r#""a".translate({97: [1]})"#, "table values must be");
+    }
+
+    #[test]
+    fn test_justify() {
+        assert::all_true(
+            r#"
+"ab".center(5) == "  ab "
+"ab".center(6, "-") == "--ab--"
+"abc".center(6, "-") == "-abc--"
+"é".center(3, "·") == "·é·"
+"left".ljust(8, ".") == "left...."
+"right".rjust(8, ".") == "...right"
+"toolong".center(3) == "toolong"
+"toolong".ljust(7) == "toolong"
+"toolong".rjust(-1) == "toolong"
+"".center(2) == "  "
+"#,
+        );
+        assert::fail(r#""a".center(5, "ab")"#, "exactly one character");
+        assert::fail(r#""a".ljust(5, "")"#, "exactly one character");
+        assert::fail(r#""a".rjust(5, "--")"#, "exactly one character");
+    }
+
     #[test]
     fn test_opaque_iterator() {
         assert::is_true("type('foo'.elems()) != type([])");
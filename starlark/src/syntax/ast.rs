@@ -26,7 +26,10 @@ use derivative::Derivative;
 use gazebo::prelude::*;
 use static_assertions::assert_eq_size;
 
-use crate::codemap::{CodeMap, Pos, Span, Spanned};
+use crate::{
+    codemap::{CodeMap, Pos, Span, Spanned},
+    syntax::dialect::DialectFlags,
+};
 
 /// Payload types attached to AST nodes.
 pub trait AstPayload: Debug {
@@ -95,6 +98,11 @@ pub struct AstModule {
     #[derivative(Debug = "ignore")]
     pub(crate) codemap: CodeMap,
     pub(crate) statement: AstStmt,
+    /// The comments in the source, in order, including the leading `#`.
+    /// They are kept out of the statements so most passes can ignore them.
+    pub(crate) comments: Vec<Spanned<String>>,
+    /// The parts of the [`Dialect`](crate::syntax::Dialect) needed by the compiled code.
+    pub(crate) flags: DialectFlags,
 }
 
 // A trait rather than a function to allow .ast() chaining in the parser.
@@ -126,6 +134,8 @@ pub enum ParameterP<P: AstPayload> {
         Box<AstExprP<P>>,
     ),
     NoArgs,
+    /// A `/`, making the parameters before it positional-only.
+    PositionalOnly,
     Args(AstAssignIdentP<P>, Option<Box<AstExprP<P>>>),
     KwArgs(AstAssignIdentP<P>, Option<Box<AstExprP<P>>>),
 }
@@ -215,6 +225,8 @@ pub enum BinOp {
     GreaterOrEqual,
     In,
     NotIn,
+    Is,
+    IsNot,
     Subtract,
     Add,
     Multiply,
@@ -319,6 +331,8 @@ impl Display for BinOp {
             BinOp::GreaterOrEqual => f.write_str(" >= "),
             BinOp::In => f.write_str(" in "),
             BinOp::NotIn => f.write_str(" not in "),
+            BinOp::Is => f.write_str(" is "),
+            BinOp::IsNot => f.write_str(" is not "),
             BinOp::Subtract => f.write_str(" - "),
             BinOp::Add => f.write_str(" + "),
             BinOp::Multiply => f.write_str(" * "),
@@ -338,7 +352,7 @@ impl Display for AssignOp {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match *self {
             AssignOp::Add => f.write_str(" += "),
-            AssignOp::Subtract => f.write_str(" += "),
+            AssignOp::Subtract => f.write_str(" -= "),
             AssignOp::Multiply => f.write_str(" *= "),
             AssignOp::Divide => f.write_str(" /= "),
             AssignOp::FloorDivide => f.write_str(" //= "),
@@ -391,7 +405,8 @@ impl Display for AstLiteral {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             AstLiteral::Int(i) => write!(f, "{}", &i.node),
-            AstLiteral::Float(n) => write!(f, "{}", &n.node),
+            // Debug keeps the decimal point, so `1.0` doesn't turn into the int `1`.
+            AstLiteral::Float(n) => write!(f, "{:?}", &n.node),
             AstLiteral::String(s) => fmt_string_literal(f, &s.node),
         }
     }
@@ -425,19 +440,18 @@ impl Display for Expr {
             }
             Expr::ArrayIndirection(box (e, i)) => write!(f, "{}[{}]", e.node, i.node),
             Expr::Slice(e, i1, i2, i3) => {
-                write!(f, "{}[]", e.node)?;
+                write!(f, "{}[", e.node)?;
                 if let Some(x) = i1 {
-                    write!(f, "{}:", x.node)?
-                } else {
-                    f.write_str(":")?
+                    write!(f, "{}", x.node)?
                 }
+                f.write_str(":")?;
                 if let Some(x) = i2 {
                     write!(f, "{}", x.node)?
                 }
                 if let Some(x) = i3 {
                     write!(f, ":{}", x.node)?
                 }
-                Ok(())
+                f.write_str("]")
             }
             Expr::Identifier(s, _) => write!(f, "{}", s.node),
             Expr::Not(e) => write!(f, "(not {})", e.node),
@@ -472,7 +486,7 @@ impl Display for Expr {
                 for x in c {
                     write!(f, "{}", x)?;
                 }
-                f.write_str("}}")
+                f.write_str("}")
             }
             Expr::Literal(x) => write!(f, "{}", x),
         }
@@ -517,6 +531,7 @@ impl Display for Parameter {
             Parameter::Normal(s, t) => ("", s, t, None),
             Parameter::WithDefaultValue(s, t, e) => ("", s, t, Some(e)),
             Parameter::NoArgs => return write!(f, "*"),
+            Parameter::PositionalOnly => return write!(f, "/"),
             Parameter::Args(s, t) => ("*", s, t, None),
             Parameter::KwArgs(s, t) => ("**", s, t, None),
         };
@@ -590,15 +605,13 @@ impl Stmt {
             Stmt::Load(load) => {
                 write!(f, "{}load(", tab)?;
                 fmt_string_literal(f, &load.node.module.node)?;
-                comma_separated_fmt(
-                    f,
-                    &load.node.args,
-                    |x, f| {
-                        write!(f, "{} = ", x.0.node)?;
-                        fmt_string_literal(f, &(x.1.node))
-                    },
-                    false,
-                )?;
+                for (local, their) in &load.node.args {
+                    f.write_str(", ")?;
+                    if local.node.0 != their.node {
+                        write!(f, "{} = ", local.node)?;
+                    }
+                    fmt_string_literal(f, &their.node)?;
+                }
                 f.write_str(")\n")
             }
         }
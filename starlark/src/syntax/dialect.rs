@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use gazebo::prelude::*;
 use thiserror::Error;
 
 use crate::{
@@ -33,14 +34,19 @@ enum DialectError {
     Load,
     #[error("* keyword-only-arguments is not allowed in this dialect")]
     KeywordOnlyArguments,
+    #[error("/ positional-only-arguments is not allowed in this dialect")]
+    PositionalOnlyArguments,
     #[error("type annotations are not allowed in this dialect")]
     Types,
+    #[error("`is` is not allowed in this dialect")]
+    Is,
 }
 
 /// Starlark language features to enable, e.g. [`Standard`](Dialect::Standard) to follow the Starlark standard.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Dialect {
-    /// Are `def` statements permitted.
+    /// Are `def` statements permitted. If not, using `def` is a parse error pointing at the keyword,
+    /// which is useful for locking down configuration files. See also [`enable_lambda`](Dialect::enable_lambda).
     /// Enabled in both [`Standard`](Dialect::Standard) and [`Extended`](Dialect::Extended).
     pub enable_def: bool,
     /// Are `lambda` expressions permitted.
@@ -52,19 +58,56 @@ pub struct Dialect {
     /// Are `*` keyword-only arguments allowed as per [PEP 3102](https://www.python.org/dev/peps/pep-3102/).
     /// Only enabled in [`Extended`](Dialect::Extended).
     pub enable_keyword_only_arguments: bool,
+    /// Is `/` allowed to make the parameters before it positional-only, as per
+    /// [PEP 570](https://www.python.org/dev/peps/pep-0570/).
+    /// Only enabled in [`Extended`](Dialect::Extended).
+    pub enable_positional_only_arguments: bool,
     /// Are expressions allowed in type positions as per [PEP 484](https://www.python.org/dev/peps/pep-0484/).
+    /// The annotations on `def` parameters and return values are enforced at runtime,
+    /// so calling a function with an argument that doesn't match its annotation,
+    /// or returning a value that doesn't match, is an error.
     /// Only enabled in [`Extended`](Dialect::Extended).
     pub enable_types: bool,
     /// Are tabs permitted for indentation. If permitted, tabs are equivalent to 8 spaces.
     /// Enabled in both [`Standard`](Dialect::Standard) and [`Extended`](Dialect::Extended).
     pub enable_tabs: bool,
-    /// Do `load()` statements reexport their definition.
+    /// Do `load()` statements reexport their definition. If not, names bound by `load` are
+    /// private to the module: they can't be loaded from it, and are missing from
+    /// [`FrozenModule::get`](crate::environment::FrozenModule::get) and
+    /// [`names`](crate::environment::FrozenModule::names) once it is frozen.
+    /// Names starting with `_` are never reexported.
     /// Enabled in both [`Standard`](Dialect::Standard) and [`Extended`](Dialect::Extended),
     /// but may change in future definitions of the standard.
     pub enable_load_reexport: bool,
     /// Are `for`, `if` and other statements allowed at the top level.
     /// Only enabled in [`Extended`](Dialect::Extended).
     pub enable_top_level_stmt: bool,
+    /// Does integer arithmetic that overflows 32 bits promote to arbitrary precision,
+    /// rather than failing. Has no effect unless the `bigint` feature is enabled.
+    /// Applies to the code in modules parsed with this dialect, wherever it is called from.
+    /// Disabled in both [`Standard`](Dialect::Standard) and [`Extended`](Dialect::Extended).
+    pub enable_bigint: bool,
+    /// Are the `is` and `is not` identity operators permitted.
+    /// `x is y` is true if `x` and `y` are the same value, not merely equal ones,
+    /// so `[] is []` is false. Small ints, `None` and booleans are not allocated on the heap,
+    /// so equal ones are always identical. Whether equal strings are identical
+    /// depends on whether they were interned, so shouldn't be relied upon.
+    /// Only enabled in [`Extended`](Dialect::Extended).
+    pub enable_is_operator: bool,
+    /// Is the `bytes` type available, created with `str.encode()`.
+    /// Bytes support `len`, indexing (giving ints in the range 0-255), slicing and `decode()`.
+    /// Applies to the code in modules parsed with this dialect, wherever it is called from.
+    /// Only enabled in [`Extended`](Dialect::Extended).
+    pub enable_bytes: bool,
+    /// Can `sorted`, `min` and `max` order values of different types, such as `[1, "a"]`.
+    /// Values of different types which can be compared, such as ints and floats, are ordered
+    /// by value. Otherwise they are ordered by type name, so sorting is stable but the order
+    /// between types is arbitrary. The same applies to the elements of lists and tuples.
+    /// Values of the same type which can't be compared, such as dicts, still fail, as do
+    /// comparison operators such as `<`.
+    /// Applies to the code in modules parsed with this dialect, wherever it is called from.
+    /// Disabled in both [`Standard`](Dialect::Standard) and [`Extended`](Dialect::Extended).
+    pub enable_cross_type_ordering: bool,
 }
 
 // These are morally enumerations, so give them enumeration-like names
@@ -77,10 +120,15 @@ impl Dialect {
         enable_lambda: true,
         enable_load: true,
         enable_keyword_only_arguments: false,
+        enable_positional_only_arguments: false,
         enable_types: false,
         enable_tabs: true,
         enable_load_reexport: true, // But they plan to change it
         enable_top_level_stmt: false,
+        enable_bigint: false,
+        enable_is_operator: false,
+        enable_bytes: false,
+        enable_cross_type_ordering: false,
     };
 
     /// A superset of [`Standard`](Dialect::Standard), including extra features (types, top-level statements etc).
@@ -89,13 +137,43 @@ impl Dialect {
         enable_lambda: true,
         enable_load: true,
         enable_keyword_only_arguments: true,
+        enable_positional_only_arguments: true,
         enable_types: true,
         enable_tabs: true,
         enable_load_reexport: true,
         enable_top_level_stmt: true,
+        enable_bigint: false,
+        enable_is_operator: true,
+        enable_bytes: true,
+        enable_cross_type_ordering: false,
     };
 }
 
+/// The parts of a [`Dialect`] which change how code behaves once it is running,
+/// rather than what the parser accepts.
+///
+/// They are copied into each function as it is compiled, so code follows the dialect
+/// of the module which defined it, whichever module calls it.
+#[derive(Debug, Clone, Copy, Dupe, Default)]
+pub(crate) struct DialectFlags {
+    /// See [`Dialect::enable_bigint`].
+    pub(crate) enable_bigint: bool,
+    /// See [`Dialect::enable_bytes`].
+    pub(crate) enable_bytes: bool,
+    /// See [`Dialect::enable_cross_type_ordering`].
+    pub(crate) enable_cross_type_ordering: bool,
+}
+
+impl Dialect {
+    pub(crate) fn flags(&self) -> DialectFlags {
+        DialectFlags {
+            enable_bigint: self.enable_bigint,
+            enable_bytes: self.enable_bytes,
+            enable_cross_type_ordering: self.enable_cross_type_ordering,
+        }
+    }
+}
+
 fn err<T>(codemap: &CodeMap, span: Span, err: DialectError) -> anyhow::Result<T> {
     Err(Diagnostic::new(err, span, codemap))
 }
@@ -152,6 +230,21 @@ impl Dialect {
         }
     }
 
+    pub(crate) fn check_positional_only_arguments<T>(
+        &self,
+        codemap: &CodeMap,
+        begin: usize,
+        end: usize,
+        x: T,
+    ) -> anyhow::Result<T> {
+        let span = Span::new(Pos::new(begin as u32), Pos::new(end as u32));
+        if self.enable_positional_only_arguments {
+            Ok(x)
+        } else {
+            err(codemap, span, DialectError::PositionalOnlyArguments)
+        }
+    }
+
     pub(crate) fn check_type<T>(
         &self,
         codemap: &CodeMap,
@@ -164,6 +257,18 @@ impl Dialect {
         }
     }
 
+    pub(crate) fn check_is<T>(
+        &self,
+        codemap: &CodeMap,
+        x: Spanned<T>,
+    ) -> anyhow::Result<Spanned<T>> {
+        if self.enable_is_operator {
+            Ok(x)
+        } else {
+            err(codemap, x.span, DialectError::Is)
+        }
+    }
+
     pub(crate) fn load_visibility(&self) -> Visibility {
         if self.enable_load_reexport {
             Visibility::Public
@@ -0,0 +1,1029 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! An opinionated formatter for Starlark source, in the style of Buildifier.
+//!
+//! The output uses four space indentation, a single space around binary operators and `=`,
+//! only the parentheses that are required, and double quoted strings.
+//! Bracketed lists which don't fit in 79 columns are split one element per line,
+//! each followed by a trailing comma. Comments are kept, as are single blank lines
+//! between statements. A comment inside brackets stays with the element it follows.
+
+use gazebo::prelude::*;
+
+use crate::{
+    codemap::{CodeMap, Pos, Span, Spanned},
+    syntax::{
+        ast::{
+            Argument, Assign, AstArgument, AstAssign, AstExpr, AstLiteral, AstParameter, AstStmt,
+            BinOp, Clause, Expr, ForClause, Parameter, Stmt,
+        },
+        ensure_sufficient_stack, AstModule, Dialect,
+    },
+};
+
+/// Lines longer than this have their outermost brackets split.
+const WIDTH: usize = 79;
+
+/// Spaces per level of indentation.
+const INDENT: usize = 4;
+
+/// Parse `src` and print it back out in the canonical layout.
+/// Formatting the result again gives the same output.
+///
+/// ```
+/// use starlark::syntax::{format_source, Dialect};
+///
+/// let src = "def f(x) :\n  return (x+1)  # one more\n";
+/// assert_eq!(
+///     format_source(src, &Dialect::Standard).unwrap(),
+///     "def f(x):\n    return x + 1  # one more\n"
+/// );
+/// ```
+pub fn format_source(src: &str, dialect: &Dialect) -> anyhow::Result<String> {
+    let module = AstModule::parse("<source>", src.to_owned(), dialect)?;
+    Ok(module.print(Layout::Format))
+}
+
+/// How statements are laid out. Comments are placed the same way in both.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Layout {
+    /// The layout of [`format_source`].
+    Format,
+    /// The layout of [`AstModule::unparse`], which writes expressions with their `Display`,
+    /// indents by two spaces, and drops blank lines.
+    Unparse,
+}
+
+impl AstModule {
+    /// Print the module, with its comments, in the given layout.
+    pub(crate) fn print(&self, layout: Layout) -> String {
+        let mut f = Formatter {
+            codemap: &self.codemap,
+            comments: &self.comments,
+            next_comment: 0,
+            out: String::new(),
+            last_line: None,
+            block_start: true,
+            layout,
+        };
+        f.stmts(&self.statement, 0);
+        f.remaining_comments();
+        f.out
+    }
+}
+
+/// A piece of output which may be laid out on one line, or split over several.
+enum Doc {
+    Text(String),
+    Concat(Vec<Doc>),
+    /// Comma separated items between brackets.
+    Group {
+        open: &'static str,
+        items: Vec<Item>,
+        close: &'static str,
+        /// Write a trailing comma after a single item, as a one element tuple needs.
+        single_comma: bool,
+        /// Where the group ends in the source.
+        end: Pos,
+        /// Comments on lines of their own after the last item.
+        comments: Vec<String>,
+    },
+}
+
+/// An element of a [`Doc::Group`], along with the comments next to it in the source.
+struct Item {
+    span: Span,
+    doc: Doc,
+    /// Comments on lines of their own before the item.
+    leading: Vec<String>,
+    /// The comment at the end of the line the item finishes on.
+    trailing: Option<String>,
+}
+
+impl Item {
+    fn new(span: Span, doc: Doc) -> Item {
+        Item {
+            span,
+            doc,
+            leading: Vec::new(),
+            trailing: None,
+        }
+    }
+}
+
+impl Doc {
+    fn text(x: impl Into<String>) -> Doc {
+        Doc::Text(x.into())
+    }
+
+    fn group(open: &'static str, items: Vec<Item>, close: &'static str, end: Pos) -> Doc {
+        Doc::Group {
+            open,
+            items,
+            close,
+            single_comma: false,
+            end,
+            comments: Vec::new(),
+        }
+    }
+
+    fn tuple(items: Vec<Item>, end: Pos) -> Doc {
+        Doc::Group {
+            open: "(",
+            items,
+            close: ")",
+            single_comma: true,
+            end,
+            comments: Vec::new(),
+        }
+    }
+
+    /// Does this contain any comments, which can only be written if it is split over lines.
+    fn has_comments(&self) -> bool {
+        match self {
+            Doc::Text(_) => false,
+            Doc::Concat(xs) => xs.iter().any(|x| x.has_comments()),
+            Doc::Group {
+                items, comments, ..
+            } => {
+                !comments.is_empty()
+                    || items.iter().any(|x| {
+                        !x.leading.is_empty() || x.trailing.is_some() || x.doc.has_comments()
+                    })
+            }
+        }
+    }
+
+    fn flat(&self, out: &mut String) {
+        ensure_sufficient_stack(|| match self {
+            Doc::Text(x) => out.push_str(x),
+            Doc::Concat(xs) => xs.iter().for_each(|x| x.flat(out)),
+            Doc::Group {
+                open,
+                items,
+                close,
+                single_comma,
+                ..
+            } => {
+                out.push_str(open);
+                for (i, x) in items.iter().enumerate() {
+                    if i != 0 {
+                        out.push_str(", ");
+                    }
+                    x.doc.flat(out);
+                }
+                if *single_comma && items.len() == 1 {
+                    out.push(',');
+                }
+                out.push_str(close);
+            }
+        })
+    }
+
+    fn render(&self, out: &mut String, indent: usize) {
+        ensure_sufficient_stack(|| self.render_direct(out, indent))
+    }
+
+    fn render_direct(&self, out: &mut String, indent: usize) {
+        match self {
+            Doc::Text(x) => out.push_str(x),
+            Doc::Concat(xs) => xs.iter().for_each(|x| x.render(out, indent)),
+            Doc::Group {
+                open,
+                items,
+                close,
+                comments,
+                ..
+            } => {
+                if !self.has_comments() {
+                    let mut flat = String::new();
+                    self.flat(&mut flat);
+                    let column = out.len() - out.rfind('\n').map_or(0, |i| i + 1);
+                    if items.is_empty() || column + flat.len() <= WIDTH {
+                        out.push_str(&flat);
+                        return;
+                    }
+                }
+                out.push_str(open);
+                for x in items {
+                    for c in &x.leading {
+                        new_line(out, indent + INDENT);
+                        out.push_str(c);
+                    }
+                    new_line(out, indent + INDENT);
+                    x.doc.render(out, indent + INDENT);
+                    out.push(',');
+                    if let Some(c) = &x.trailing {
+                        out.push_str("  ");
+                        out.push_str(c);
+                    }
+                }
+                for c in comments {
+                    new_line(out, indent + INDENT);
+                    out.push_str(c);
+                }
+                new_line(out, indent);
+                out.push_str(close);
+            }
+        }
+    }
+}
+
+/// The first line of a statement in the [`Layout::Unparse`] layout, which is all of
+/// a simple statement.
+fn unparse_line(x: &Stmt) -> String {
+    match x {
+        Stmt::If(cond, _) | Stmt::IfElse(cond, _) => format!("if {}:", cond.node),
+        Stmt::For(var, box (over, _)) => format!("for {} in {}:", var.node, over.node),
+        Stmt::Def(name, params, ret, _, _) => {
+            let params = params.map(|p| p.node.to_string()).join(", ");
+            match ret {
+                None => format!("def {}({}):", name.node, params),
+                Some(ret) => format!("def {}({}) -> {}:", name.node, params, ret.node),
+            }
+        }
+        _ => x.to_string().trim_end_matches('\n').to_owned(),
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.extend(std::iter::repeat(' ').take(indent));
+}
+
+fn new_line(out: &mut String, indent: usize) {
+    out.push('\n');
+    push_indent(out, indent);
+}
+
+/// Binding strength, from loosest to tightest. Parentheses are needed around
+/// an expression whose precedence is lower than its position requires.
+mod prec {
+    pub(super) const LAMBDA: u8 = 0;
+    pub(super) const IF: u8 = 1;
+    pub(super) const OR: u8 = 2;
+    pub(super) const AND: u8 = 3;
+    pub(super) const NOT: u8 = 4;
+    pub(super) const COMPARE: u8 = 5;
+    pub(super) const BIT_OR: u8 = 6;
+    pub(super) const BIT_XOR: u8 = 7;
+    pub(super) const BIT_AND: u8 = 8;
+    pub(super) const SHIFT: u8 = 9;
+    pub(super) const SUM: u8 = 10;
+    pub(super) const PRODUCT: u8 = 11;
+    pub(super) const UNARY: u8 = 12;
+    pub(super) const PRIMARY: u8 = 13;
+}
+
+fn binop_prec(op: BinOp) -> u8 {
+    match op {
+        BinOp::Or => prec::OR,
+        BinOp::And => prec::AND,
+        BinOp::Equal
+        | BinOp::NotEqual
+        | BinOp::Less
+        | BinOp::Greater
+        | BinOp::LessOrEqual
+        | BinOp::GreaterOrEqual
+        | BinOp::In
+        | BinOp::NotIn
+        | BinOp::Is
+        | BinOp::IsNot => prec::COMPARE,
+        BinOp::BitOr => prec::BIT_OR,
+        BinOp::BitXor => prec::BIT_XOR,
+        BinOp::BitAnd => prec::BIT_AND,
+        BinOp::LeftShift | BinOp::RightShift => prec::SHIFT,
+        BinOp::Add | BinOp::Subtract => prec::SUM,
+        BinOp::Multiply | BinOp::Percent | BinOp::Divide | BinOp::FloorDivide => prec::PRODUCT,
+    }
+}
+
+fn string_literal(x: &str) -> String {
+    // Multi-line strings, mostly docstrings, read better triple quoted,
+    // as long as nothing in them needs escaping.
+    let plain = |c: char| c == '\n' || c == '\t' || (!c.is_control() && c != '\\');
+    if x.contains('\n') && x.chars().all(plain) && !x.contains("\"\"\"") && !x.ends_with('"') {
+        format!("\"\"\"{}\"\"\"", x)
+    } else {
+        AstLiteral::String(Spanned {
+            node: x.to_owned(),
+            span: Default::default(),
+        })
+        .to_string()
+    }
+}
+
+fn expr(x: &AstExpr, min: u8) -> Doc {
+    let (doc, p) = ensure_sufficient_stack(|| match &x.node {
+        Expr::Tuple(xs) => (Doc::tuple(items(xs), x.span.end()), prec::PRIMARY),
+        Expr::Dot(e, s) => {
+            // `1.x` would lex as a float.
+            let lhs_min = match &e.node {
+                Expr::Literal(AstLiteral::Int(_)) => prec::PRIMARY + 1,
+                _ => prec::PRIMARY,
+            };
+            (
+                Doc::Concat(vec![expr(e, lhs_min), Doc::text(format!(".{}", s.node))]),
+                prec::PRIMARY,
+            )
+        }
+        Expr::Call(f, args) => (
+            Doc::Concat(vec![
+                expr(f, prec::PRIMARY),
+                Doc::group(
+                    "(",
+                    args.map(|a| Item::new(a.span, argument(a))),
+                    ")",
+                    x.span.end(),
+                ),
+            ]),
+            prec::PRIMARY,
+        ),
+        Expr::ArrayIndirection(box (e, i)) => (
+            Doc::Concat(vec![
+                expr(e, prec::PRIMARY),
+                Doc::text("["),
+                expr(i, prec::LAMBDA),
+                Doc::text("]"),
+            ]),
+            prec::PRIMARY,
+        ),
+        Expr::Slice(e, i1, i2, i3) => {
+            let mut xs = vec![expr(e, prec::PRIMARY), Doc::text("[")];
+            if let Some(i1) = i1 {
+                xs.push(expr(i1, prec::LAMBDA));
+            }
+            xs.push(Doc::text(":"));
+            if let Some(i2) = i2 {
+                xs.push(expr(i2, prec::LAMBDA));
+            }
+            if let Some(i3) = i3 {
+                xs.push(Doc::text(":"));
+                xs.push(expr(i3, prec::LAMBDA));
+            }
+            xs.push(Doc::text("]"));
+            (Doc::Concat(xs), prec::PRIMARY)
+        }
+        Expr::Identifier(s, _) => (Doc::text(&s.node), prec::PRIMARY),
+        Expr::Lambda(params, body, _) => {
+            let mut xs = vec![Doc::text("lambda")];
+            for (i, p) in params.iter().enumerate() {
+                xs.push(Doc::text(if i == 0 { " " } else { ", " }));
+                xs.push(parameter(p));
+            }
+            xs.push(Doc::text(": "));
+            xs.push(expr(body, prec::LAMBDA));
+            (Doc::Concat(xs), prec::LAMBDA)
+        }
+        Expr::Literal(AstLiteral::String(s)) => (Doc::Text(string_literal(&s.node)), prec::PRIMARY),
+        Expr::Literal(x) => (Doc::Text(x.to_string()), prec::PRIMARY),
+        Expr::Not(e) => (
+            Doc::Concat(vec![Doc::text("not "), expr(e, prec::NOT)]),
+            prec::NOT,
+        ),
+        Expr::Minus(e) => unary("-", e),
+        Expr::Plus(e) => unary("+", e),
+        Expr::BitNot(e) => unary("~", e),
+        Expr::Op(l, op, r) => {
+            let p = binop_prec(*op);
+            // Comparisons don't chain, everything else is left associative.
+            let l_min = if p == prec::COMPARE { p + 1 } else { p };
+            (
+                Doc::Concat(vec![
+                    expr(l, l_min),
+                    Doc::text(op.to_string()),
+                    expr(r, p + 1),
+                ]),
+                p,
+            )
+        }
+        Expr::If(box (cond, then, els)) => (
+            Doc::Concat(vec![
+                expr(then, prec::OR),
+                Doc::text(" if "),
+                expr(cond, prec::OR),
+                Doc::text(" else "),
+                expr(els, prec::LAMBDA),
+            ]),
+            prec::IF,
+        ),
+        Expr::List(xs) => (Doc::group("[", items(xs), "]", x.span.end()), prec::PRIMARY),
+        Expr::Dict(xs) => (
+            Doc::group(
+                "{",
+                xs.map(|(k, v)| {
+                    let doc = Doc::Concat(vec![
+                        expr(k, prec::LAMBDA),
+                        Doc::text(": "),
+                        expr(v, prec::LAMBDA),
+                    ]);
+                    Item::new(k.span.merge(v.span), doc)
+                }),
+                "}",
+                x.span.end(),
+            ),
+            prec::PRIMARY,
+        ),
+        Expr::ListComprehension(e, first, clauses) => {
+            let mut xs = vec![Doc::text("["), expr(e, prec::LAMBDA)];
+            comprehension(&mut xs, first, clauses);
+            xs.push(Doc::text("]"));
+            (Doc::Concat(xs), prec::PRIMARY)
+        }
+        Expr::DictComprehension(box (k, v), first, clauses) => {
+            let mut xs = vec![
+                Doc::text("{"),
+                expr(k, prec::LAMBDA),
+                Doc::text(": "),
+                expr(v, prec::LAMBDA),
+            ];
+            comprehension(&mut xs, first, clauses);
+            xs.push(Doc::text("}"));
+            (Doc::Concat(xs), prec::PRIMARY)
+        }
+    });
+    if p < min {
+        Doc::Concat(vec![Doc::text("("), doc, Doc::text(")")])
+    } else {
+        doc
+    }
+}
+
+/// The elements of a bracketed list of expressions.
+fn items(xs: &[AstExpr]) -> Vec<Item> {
+    xs.map(|x| Item::new(x.span, expr(x, prec::LAMBDA)))
+}
+
+fn unary(op: &str, e: &AstExpr) -> (Doc, u8) {
+    (
+        Doc::Concat(vec![Doc::text(op), expr(e, prec::UNARY)]),
+        prec::UNARY,
+    )
+}
+
+fn comprehension(xs: &mut Vec<Doc>, first: &ForClause, clauses: &[Clause]) {
+    let for_clause = |xs: &mut Vec<Doc>, x: &ForClause| {
+        xs.push(Doc::text(" for "));
+        xs.push(assign(&x.var, true));
+        xs.push(Doc::text(" in "));
+        xs.push(expr(&x.over, prec::OR));
+    };
+    for_clause(xs, first);
+    for c in clauses {
+        match c {
+            Clause::For(x) => for_clause(xs, x),
+            Clause::If(x) => {
+                xs.push(Doc::text(" if "));
+                xs.push(expr(x, prec::OR));
+            }
+        }
+    }
+}
+
+/// An expression in a position which accepts a tuple without parentheses.
+/// Tuples of a single element keep them, as `x = (1,)` is clearer than `x = 1,`.
+fn expr_list(x: &AstExpr) -> Doc {
+    match &x.node {
+        Expr::Tuple(xs) if xs.len() > 1 => bare_tuple(xs.map(|x| expr(x, prec::LAMBDA))),
+        _ => expr(x, prec::LAMBDA),
+    }
+}
+
+fn bare_tuple(xs: Vec<Doc>) -> Doc {
+    let mut res = Vec::new();
+    for (i, x) in xs.into_iter().enumerate() {
+        if i != 0 {
+            res.push(Doc::text(", "));
+        }
+        res.push(x);
+    }
+    Doc::Concat(res)
+}
+
+fn assign(x: &AstAssign, bare: bool) -> Doc {
+    match &x.node {
+        Assign::Tuple(xs) => {
+            let items = xs.map(|x| Item::new(x.span, assign(x, false)));
+            if bare && items.len() > 1 {
+                bare_tuple(items.into_map(|x| x.doc))
+            } else {
+                Doc::tuple(items, x.span.end())
+            }
+        }
+        Assign::ArrayIndirection(box (e, i)) => Doc::Concat(vec![
+            expr(e, prec::PRIMARY),
+            Doc::text("["),
+            expr(i, prec::LAMBDA),
+            Doc::text("]"),
+        ]),
+        Assign::Dot(e, s) => Doc::Concat(vec![
+            expr(e, prec::PRIMARY),
+            Doc::text(format!(".{}", s.node)),
+        ]),
+        Assign::Identifier(x) => Doc::text(&x.node.0),
+    }
+}
+
+fn argument(x: &AstArgument) -> Doc {
+    match &x.node {
+        Argument::Positional(e) => expr(e, prec::LAMBDA),
+        Argument::Named(name, e) => Doc::Concat(vec![
+            Doc::text(format!("{} = ", name.node)),
+            expr(e, prec::LAMBDA),
+        ]),
+        Argument::Args(e) => Doc::Concat(vec![Doc::text("*"), expr(e, prec::LAMBDA)]),
+        Argument::KwArgs(e) => Doc::Concat(vec![Doc::text("**"), expr(e, prec::LAMBDA)]),
+    }
+}
+
+fn parameter(x: &AstParameter) -> Doc {
+    let (prefix, name, typ, default) = match &x.node {
+        Parameter::Normal(n, t) => ("", n, t, None),
+        Parameter::WithDefaultValue(n, t, d) => ("", n, t, Some(d)),
+        Parameter::NoArgs => return Doc::text("*"),
+        Parameter::PositionalOnly => return Doc::text("/"),
+        Parameter::Args(n, t) => ("*", n, t, None),
+        Parameter::KwArgs(n, t) => ("**", n, t, None),
+    };
+    let mut xs = vec![Doc::text(format!("{}{}", prefix, name.node.0))];
+    if let Some(t) = typ {
+        xs.push(Doc::text(": "));
+        xs.push(expr(t, prec::LAMBDA));
+    }
+    if let Some(d) = default {
+        xs.push(Doc::text(" = "));
+        xs.push(expr(d, prec::LAMBDA));
+    }
+    Doc::Concat(xs)
+}
+
+struct Formatter<'a> {
+    codemap: &'a CodeMap,
+    comments: &'a [Spanned<String>],
+    /// Index of the first comment not yet written.
+    next_comment: usize,
+    out: String,
+    /// The source line of the last thing written, used to preserve blank lines.
+    last_line: Option<usize>,
+    /// Nothing has been written in the current block yet, so no blank line is needed.
+    block_start: bool,
+    layout: Layout,
+}
+
+impl<'a> Formatter<'a> {
+    fn line(&self, pos: Pos) -> usize {
+        self.codemap.find_line(pos)
+    }
+
+    /// Spaces per level of indentation.
+    fn indent(&self) -> usize {
+        match self.layout {
+            Layout::Format => INDENT,
+            Layout::Unparse => 2,
+        }
+    }
+
+    /// Is there a blank line in the source between `last` and `line`, which should be kept.
+    fn blank_line(&self, last: usize, line: usize) -> bool {
+        self.layout == Layout::Format && line > last + 1
+    }
+
+    /// Write a blank line if there was one in the source before `line`.
+    fn separate(&mut self, line: usize) {
+        if !self.block_start {
+            if let Some(last) = self.last_line {
+                if self.blank_line(last, line) {
+                    self.out.push('\n');
+                }
+            }
+        }
+        self.block_start = false;
+    }
+
+    fn write_doc(&mut self, indent: usize, doc: Doc) {
+        push_indent(&mut self.out, indent);
+        doc.render(&mut self.out, indent);
+        self.out.push('\n');
+    }
+
+    /// Write the comments which start before `pos` on lines of their own, ahead of
+    /// whatever starts on `stmt_line`.
+    fn comments_before(&mut self, pos: Pos, stmt_line: usize, indent: usize) {
+        while let Some(c) = self.comments.get(self.next_comment) {
+            if c.span.begin() >= pos {
+                break;
+            }
+            self.next_comment += 1;
+            let line = self.line(c.span.begin());
+            self.separate(line.min(stmt_line));
+            self.write_doc(indent, Doc::text(&c.node));
+            self.last_line = Some(line);
+        }
+    }
+
+    /// Remove the comments which start before `pos`.
+    fn take_comments(&mut self, pos: Pos) -> Vec<String> {
+        let mut res = Vec::new();
+        while let Some(c) = self.comments.get(self.next_comment) {
+            if c.span.begin() >= pos {
+                break;
+            }
+            self.next_comment += 1;
+            res.push(c.node.clone());
+        }
+        res
+    }
+
+    /// Attach the comments inside the brackets of `doc` to the items they are next to.
+    fn attach_comments(&mut self, doc: &mut Doc) {
+        match doc {
+            Doc::Text(_) => {}
+            Doc::Concat(xs) => xs.iter_mut().for_each(|x| self.attach_comments(x)),
+            Doc::Group {
+                items,
+                end,
+                comments,
+                ..
+            } => {
+                for i in 0..items.len() {
+                    let next = items.get(i + 1).map_or(*end, |x| x.span.begin());
+                    let x = &mut items[i];
+                    x.leading = self.take_comments(x.span.begin());
+                    self.attach_comments(&mut x.doc);
+                    // Comments within the item, but not within its brackets, can only go above it.
+                    x.leading.extend(self.take_comments(x.span.end()));
+                    if let Some(c) = self.comments.get(self.next_comment) {
+                        let line = self.line(c.span.begin());
+                        if c.span.begin() < next && line == self.line(x.span.end()) {
+                            self.next_comment += 1;
+                            x.trailing = Some(c.node.clone());
+                        }
+                    }
+                }
+                *comments = self.take_comments(*end);
+            }
+        }
+    }
+
+    /// If the next comment is on `line`, append it to the line just written.
+    fn trailing_comment(&mut self, line: usize) {
+        if let Some(c) = self.comments.get(self.next_comment) {
+            if self.line(c.span.begin()) == line {
+                self.next_comment += 1;
+                self.out.pop();
+                self.out.push_str("  ");
+                self.out.push_str(&c.node);
+                self.out.push('\n');
+            }
+        }
+    }
+
+    fn remaining_comments(&mut self) {
+        for c in &self.comments[self.next_comment..] {
+            let line = self.line(c.span.begin());
+            if let Some(last) = self.last_line {
+                if self.blank_line(last, line) {
+                    self.out.push('\n');
+                }
+            }
+            self.out.push_str(&c.node);
+            self.out.push('\n');
+            self.last_line = Some(line);
+        }
+        self.next_comment = self.comments.len();
+    }
+
+    fn stmts(&mut self, x: &AstStmt, indent: usize) {
+        ensure_sufficient_stack(|| match &x.node {
+            Stmt::Statements(xs) => xs.iter().for_each(|x| self.stmts(x, indent)),
+            _ => self.stmt(x, indent),
+        })
+    }
+
+    /// Write a simple statement, or the first line of a compound statement,
+    /// which ends at `end` in the source.
+    fn header(&mut self, begin: Pos, end: Pos, indent: usize, mut doc: Doc) {
+        let begin_line = self.line(begin);
+        self.comments_before(begin, begin_line, indent);
+        self.attach_comments(&mut doc);
+        // Any comments left inside the statement are not within brackets, so go above it.
+        self.comments_before(end, begin_line, indent);
+        self.separate(begin_line);
+        self.write_doc(indent, doc);
+        let line = self.line(end);
+        self.last_line = Some(line);
+        self.trailing_comment(line);
+    }
+
+    fn suite(&mut self, x: &AstStmt, indent: usize) {
+        self.block_start = true;
+        self.stmts(x, indent + self.indent());
+    }
+
+    fn stmt(&mut self, x: &AstStmt, indent: usize) {
+        let begin = x.span.begin();
+        match &x.node {
+            Stmt::If(cond, box then) => {
+                self.if_header("if ", x, cond, indent);
+                self.suite(then, indent);
+            }
+            Stmt::IfElse(cond, box (then, els)) => {
+                self.if_header("if ", x, cond, indent);
+                self.suite(then, indent);
+                self.else_branch(els, indent);
+            }
+            _ if self.layout == Layout::Unparse => {
+                let end = match &x.node {
+                    Stmt::For(_, box (over, _)) => over.span.end(),
+                    Stmt::Def(name, params, ret, _, _) => match (ret, params.last()) {
+                        (Some(ret), _) => ret.span.end(),
+                        (None, Some(param)) => param.span.end(),
+                        (None, None) => name.span.end(),
+                    },
+                    _ => x.span.end(),
+                };
+                self.header(begin, end, indent, Doc::Text(unparse_line(&x.node)));
+                match &x.node {
+                    Stmt::For(_, box (_, body)) | Stmt::Def(_, _, _, box body, _) => {
+                        self.suite(body, indent)
+                    }
+                    _ => {}
+                }
+            }
+            Stmt::For(var, box (over, body)) => {
+                let doc = Doc::Concat(vec![
+                    Doc::text("for "),
+                    assign(var, true),
+                    Doc::text(" in "),
+                    expr(over, prec::LAMBDA),
+                    Doc::text(":"),
+                ]);
+                self.header(begin, over.span.end(), indent, doc);
+                self.suite(body, indent);
+            }
+            Stmt::Def(name, params, ret, body, _) => {
+                let mut end = params.last().map_or(name.span.end(), |p| p.span.end());
+                let mut doc = vec![
+                    Doc::text(format!("def {}", name.node.0)),
+                    Doc::group(
+                        "(",
+                        params.map(|p| Item::new(p.span, parameter(p))),
+                        ")",
+                        end,
+                    ),
+                ];
+                if let Some(ret) = ret {
+                    end = ret.span.end();
+                    doc.push(Doc::text(" -> "));
+                    doc.push(expr(ret, prec::LAMBDA));
+                }
+                doc.push(Doc::text(":"));
+                self.header(begin, end, indent, Doc::Concat(doc));
+                self.suite(body, indent);
+            }
+            _ => {
+                let doc = match &x.node {
+                    Stmt::Break => Doc::text("break"),
+                    Stmt::Continue => Doc::text("continue"),
+                    Stmt::Pass => Doc::text("pass"),
+                    Stmt::Return(None) => Doc::text("return"),
+                    Stmt::Return(Some(e)) => Doc::Concat(vec![Doc::text("return "), expr_list(e)]),
+                    Stmt::Expression(e) => expr_list(e),
+                    Stmt::Assign(l, r) => {
+                        Doc::Concat(vec![assign(l, true), Doc::text(" = "), expr_list(r)])
+                    }
+                    Stmt::AssignModify(l, op, r) => Doc::Concat(vec![
+                        assign(l, true),
+                        Doc::text(op.to_string()),
+                        expr_list(r),
+                    ]),
+                    Stmt::Load(load) => {
+                        let module = &load.node.module;
+                        let mut args = vec![Item::new(
+                            module.span,
+                            Doc::Text(string_literal(&module.node)),
+                        )];
+                        for (local, their) in &load.node.args {
+                            let doc = Doc::Text(if local.node.0 == their.node {
+                                string_literal(&their.node)
+                            } else {
+                                format!("{} = {}", local.node.0, string_literal(&their.node))
+                            });
+                            args.push(Item::new(local.span.merge(their.span), doc));
+                        }
+                        Doc::Concat(vec![
+                            Doc::text("load"),
+                            Doc::group("(", args, ")", x.span.end()),
+                        ])
+                    }
+                    _ => unreachable!("compound statements are handled above"),
+                };
+                self.header(begin, x.span.end(), indent, doc);
+            }
+        }
+    }
+
+    /// Write the first line of `x`, an `if` statement with condition `cond`.
+    fn if_header(&mut self, keyword: &str, x: &AstStmt, cond: &AstExpr, indent: usize) {
+        let doc = match self.layout {
+            Layout::Format => Doc::Concat(vec![
+                Doc::text(keyword),
+                expr(cond, prec::LAMBDA),
+                Doc::text(":"),
+            ]),
+            Layout::Unparse => Doc::Text(unparse_line(&x.node)),
+        };
+        self.header(x.span.begin(), cond.span.end(), indent, doc);
+    }
+
+    /// The line holding the `else` or `elif` keyword for an `else` branch starting at `pos`.
+    /// Only blank lines and comments can come between the keyword and the branch.
+    fn else_line(&self, pos: Pos) -> usize {
+        let mut line = self.line(pos);
+        loop {
+            let text = self.codemap.source_line(line).trim_start();
+            let keyword = text.starts_with("else") || text.starts_with("elif");
+            if keyword && !text[4..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                return line;
+            }
+            line -= 1;
+        }
+    }
+
+    fn else_branch(&mut self, els: &AstStmt, indent: usize) {
+        let else_line = self.else_line(els.span.begin());
+        // Comments above the `else` belong to the end of the previous branch.
+        let else_pos = self.codemap.line_span(else_line).begin();
+        self.comments_before(else_pos, else_line, indent + self.indent());
+        self.block_start = true;
+        // The unparsed form has no `elif`, so nests an `if` inside the `else`.
+        match &els.node {
+            Stmt::If(cond, box then) if self.layout == Layout::Format => {
+                self.if_header("elif ", els, cond, indent);
+                self.suite(then, indent);
+            }
+            Stmt::IfElse(cond, box (then, next)) if self.layout == Layout::Format => {
+                self.if_header("elif ", els, cond, indent);
+                self.suite(then, indent);
+                self.else_branch(next, indent);
+            }
+            _ => {
+                self.write_doc(indent, Doc::text("else:"));
+                self.last_line = Some(else_line);
+                self.trailing_comment(else_line);
+                self.suite(els, indent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::{format_source, Dialect};
+
+    fn format(src: &str) -> String {
+        let res = format_source(src, &Dialect::Extended).unwrap();
+        assert_eq!(
+            res,
+            format_source(&res, &Dialect::Extended).unwrap(),
+            "Formatting is not idempotent"
+        );
+        res
+    }
+
+    #[test]
+    fn test_format_layout() {
+        let src = r#"
+load('a.bzl', 'x', y='z')
+def f(a,b=1,*args,**kwargs):
+  '''Doc'''
+  if not a: return (a+b)*2
+  elif a: pass
+  else:
+    x = [i*2 for i in a if i%2==0]
+  return -(a or b).c, {'k' : lambda x : x}
+"#;
+        let want = r#"load("a.bzl", "x", y = "z")
+def f(a, b = 1, *args, **kwargs):
+    "Doc"
+    if not a:
+        return (a + b) * 2
+    elif a:
+        pass
+    else:
+        x = [i * 2 for i in a if i % 2 == 0]
+    return -(a or b).c, {"k": lambda x: x}
+"#;
+        assert_eq!(format(src), want);
+    }
+
+    #[test]
+    fn test_format_precedence() {
+        assert_eq!(format("x = (1 + 2) + (3 + 4)"), "x = 1 + 2 + (3 + 4)\n");
+        assert_eq!(format("x = (a if b else c)(d)"), "x = (a if b else c)(d)\n");
+        assert_eq!(format("x = (1 < 2) == (not c)"), "x = (1 < 2) == (not c)\n");
+        assert_eq!(format("x = (-1).y + (1).y"), "x = (-1).y + (1).y\n");
+        assert_eq!(format("x = a, ; y = (b,)"), "x = (a,)\ny = (b,)\n");
+        assert_eq!(format("a, b = (1, 2)"), "a, b = 1, 2\n");
+    }
+
+    #[test]
+    fn test_format_long_lines() {
+        let src = "foo(aaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbbbbbbbbbb, [cccccccccccccccccccc, ddddddddddddddddddddd])";
+        let want = r#"foo(
+    aaaaaaaaaaaaaaaaaaaa,
+    bbbbbbbbbbbbbbbbbbbbbbbbbbbb,
+    [cccccccccccccccccccc, ddddddddddddddddddddd],
+)
+"#;
+        assert_eq!(format(src), want);
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let src = r#"
+# Header
+
+# About x
+x = 1 # trailing
+def f(): # on def
+    # inside
+    if x:
+        pass
+        # end of if
+    else: # on else
+        y = [
+            1, # one
+            2,
+        ]
+# last
+"#;
+        let want = r#"# Header
+
+# About x
+x = 1  # trailing
+def f():  # on def
+    # inside
+    if x:
+        pass
+        # end of if
+    else:  # on else
+        y = [
+            1,  # one
+            2,
+        ]
+# last
+"#;
+        assert_eq!(format(src), want);
+    }
+
+    #[test]
+    fn test_format_comments_in_brackets() {
+        let src = r#"
+foo(
+    # first
+    a, # after a
+    [1, # one
+     2],
+    {'k': 'v'}, # dict
+    # at end
+)
+x = (1 + # not in brackets
+     2)
+"#;
+        let want = r#"foo(
+    # first
+    a,  # after a
+    [
+        1,  # one
+        2,
+    ],
+    {"k": "v"},  # dict
+    # at end
+)
+# not in brackets
+x = 1 + 2
+"#;
+        assert_eq!(format(src), want);
+    }
+
+    #[test]
+    fn test_format_docstring() {
+        let src = "def f():\n    \"\"\"Line one.\n\n    Line two.\n    \"\"\"\n    pass\n";
+        assert_eq!(format(src), src);
+    }
+}
@@ -17,7 +17,11 @@
 
 use gazebo::prelude::*;
 
-use crate::{assert, assert::Assert, syntax::ast::Stmt};
+use crate::{
+    assert,
+    assert::Assert,
+    syntax::{ast::Stmt, AstModule, Dialect, ParseLimits},
+};
 
 #[test]
 fn test_empty() {
@@ -37,7 +41,7 @@ fn test_top_level_comment() {
 
 #[test]
 fn test_top_level_load() {
-    let want = "load(\"//top/level/load.bzl\"top-level = \"top-level\")\n";
+    let want = "load(\"//top/level/load.bzl\", \"top-level\")\n";
     assert_eq!(
         assert::parse("\nload(\"//top/level/load.bzl\", \"top-level\")\n"),
         want
@@ -76,7 +80,10 @@ fn test_top_level_def() {
     );
     let mut a = Assert::new();
     a.dialect_set(|x| x.enable_def = false);
-    a.parse_fail("!def toto():\n  pass\n!");
+    a.parse_fail("!def! toto():\n  pass\n");
+    a.parse_fail("x = 1\nif x:\n  !def! toto():\n    pass\n");
+    // `lambda` is controlled separately by `enable_lambda`.
+    assert_eq!(a.parse("f = lambda: 1"), "f = (lambda : 1)\n");
     // no new line at end of file
     assert_eq!(
         assert::parse("def toto():\n  pass"),
@@ -112,6 +119,21 @@ fn test_top_level_statements() {
     assert_eq!(assert::parse("pass"), "pass\n");
 }
 
+#[test]
+fn test_top_level_statements_dialects() {
+    // Standard Starlark is a configuration language, so `for` and `if` must be inside a `def`.
+    let mut a = Assert::new();
+    a.dialect(&Dialect::Standard);
+    a.parse_fail("!for x in [1]:\n  pass\n!");
+    a.parse_fail("!if True:\n  pass\n!");
+    a.pass("def f():\n  for x in [1]:\n    pass\nf()");
+
+    // Extended allows using Starlark as a scripting language.
+    a.dialect(&Dialect::Extended);
+    a.pass("xs = []\nfor x in [1, 2]:\n  xs.append(x)\nassert_eq(xs, [1, 2])");
+    a.pass("x = 1\nif x == 1:\n  x = 2\nassert_eq(x, 2)");
+}
+
 #[test]
 fn test_top_level_def_with_docstring() {
     assert_eq!(
@@ -142,6 +164,27 @@ fn test_kwargs_passing() {
     );
 }
 
+#[test]
+fn test_positional_only_parameters() {
+    assert_eq!(
+        assert::parse("def f(a, b=1, /, c=2, *, d):\n  pass"),
+        "def f(a, b = 1, /, c = 2, *, d):\n  pass\n"
+    );
+    assert_eq!(
+        assert::parse("f = lambda x, /: x"),
+        "f = (lambda x, /: x)\n"
+    );
+    assert::parse_fail("def f(!/!, a):\n  pass");
+    assert::parse_fail("def f(a, /, !/!):\n  pass");
+    assert::parse_fail("def f(a, *args, !/!):\n  pass");
+    assert::parse_fail("def f(a, **kwargs, !/!):\n  pass");
+
+    // Positional-only parameters are not part of standard Starlark
+    let mut a = Assert::new();
+    a.dialect(&Dialect::Standard);
+    a.parse_fail("def f(a, !/!):\n  pass");
+}
+
 #[test]
 fn test_unary_op() {
     assert_eq!(assert::parse("a = -1"), "a = -1\n");
@@ -299,3 +342,112 @@ fn test_bad_assignment() {
     assert::parse_fail("[!x or y!] = 1");
     assert::parse_fail("![x]! += 1");
 }
+
+#[test]
+fn test_unparse_round_trip() {
+    let program = r#"
+load("a.bzl", "x", y = "z")
+def f(a, b: "int" = 1, *args, c, **kwargs) -> "list":
+    """Doc"""
+    s = a[1:] + a[:-1] + a[::2] + a[1:2:3]
+    s -= 2.0
+    t = -1.5e3
+    for i, j in [(1, 2)]:
+        if i and not j:
+            continue
+        elif i in s:
+            break
+        else:
+            pass
+    return [x * 2 for x in s if x], {k: v for k, v in kwargs.items()}
+g = lambda x, *y: x if y else None
+h = f(1, b = 2, *[], **{})
+print("tab\t\"quote\"\n", x.y[0], ~1, +2, 3 // 4 % 5)
+"#;
+    let once = assert::parse_ast(program).unparse();
+    let twice = assert::parse_ast(&once).unparse();
+    assert_eq!(once, twice);
+    assert!(once.contains("a[1:]"), "{}", once);
+    assert!(once.contains(" -= 2.0"), "{}", once);
+    assert!(once.contains(r#"load("a.bzl", "x", y = "z")"#), "{}", once);
+}
+
+#[test]
+fn test_unparse_keeps_comments() {
+    let module =
+        assert::parse_ast("# header\nx = 1  # one\ndef f():\n    # body\n    return x\n# end\n");
+    let want = "# header\nx = 1  # one\ndef f():\n  # body\n  return x\n# end\n";
+    assert_eq!(module.unparse(), want);
+    assert_eq!(assert::parse_ast(want).unparse(), want);
+    assert_eq!(
+        module
+            .comments()
+            .map(|(span, c)| (span.resolve_span().begin_line, c))
+            .collect::<Vec<_>>(),
+        vec![(0, "# header"), (1, "# one"), (3, "# body"), (5, "# end")]
+    );
+}
+
+#[test]
+fn test_deep_nesting() {
+    // Deep enough to overflow the stack if anything recursed over the whole expression
+    let n = 100_000;
+    assert::fail(&format!("1{}", "+1".repeat(n)), "nested too deeply");
+    assert::fail(
+        &format!("{}{}", "[".repeat(n), "]".repeat(n)),
+        "nested too deeply",
+    );
+    assert::fail(
+        &format!("x = {}True", "not ".repeat(n)),
+        "nested too deeply",
+    );
+    // Including assignment targets
+    assert::fail(
+        &format!("{}x{} = 1", "[".repeat(n), "]".repeat(n)),
+        "nested too deeply",
+    );
+    assert::fail(
+        &format!("{}x{} = 1", "(".repeat(n), ",)".repeat(n)),
+        "nested too deeply",
+    );
+    assert::fail(
+        &format!("[1 for {}x{} in []]", "[".repeat(n), "]".repeat(n)),
+        "nested too deeply",
+    );
+
+    // Moderately deep code still works
+    assert::eq(&format!("1{}", "+1".repeat(100)), "101");
+    assert::eq(&format!("len({}{})", "[".repeat(50), "]".repeat(50)), "1");
+    assert::pass(&format!(
+        "{}x{} = {}1{}\nassert_eq(x, 1)",
+        "(".repeat(50),
+        ",)".repeat(50),
+        "(".repeat(50),
+        ",)".repeat(50)
+    ));
+}
+
+#[test]
+fn test_parse_with_limits() {
+    let limits = ParseLimits {
+        max_depth: 10,
+        max_nodes: 100,
+    };
+    let parse = |x: String| AstModule::parse_with_limits("x.star", x, &Dialect::Standard, &limits);
+    assert!(parse("x = [[[1]]]".to_owned()).is_ok());
+    assert!(parse("x = 1\n".repeat(10)).is_ok());
+
+    let err = parse(format!("x = {}{}", "[".repeat(20), "]".repeat(20))).unwrap_err();
+    assert!(err.to_string().contains("maximum depth of 10"), "{}", err);
+    let err = parse(format!("{}x{} = 1", "[".repeat(20), "]".repeat(20))).unwrap_err();
+    assert!(err.to_string().contains("maximum depth of 10"), "{}", err);
+    // Within the limit for the target alone, but not within the statement
+    let err = parse("[[[[[[[[[x]]]]]]]]] = 1".to_owned()).unwrap_err();
+    assert!(err.to_string().contains("maximum depth of 10"), "{}", err);
+    let err = parse("x = 1\n".repeat(100)).unwrap_err();
+    assert!(
+        err.to_string().contains("maximum of 100 statements"),
+        "{}",
+        err
+    );
+}
@@ -21,7 +21,7 @@ use logos::Logos;
 use thiserror::Error;
 
 use crate::{
-    codemap::{CodeMap, Pos, Span},
+    codemap::{CodeMap, Pos, Span, Spanned},
     errors::Diagnostic,
     syntax::{
         cursors::{CursorBytes, CursorChars},
@@ -62,6 +62,8 @@ pub(crate) struct Lexer<'a> {
     lexer: logos::Lexer<'a, Token>,
     done: bool,
     dialect_allow_tabs: bool,
+    /// Comments seen so far, which the parser never sees.
+    comments: Vec<Spanned<String>>,
 }
 
 impl<'a> Lexer<'a> {
@@ -76,6 +78,7 @@ impl<'a> Lexer<'a> {
             parens: 0,
             done: false,
             dialect_allow_tabs: dialect.enable_tabs,
+            comments: Vec::new(),
         };
         if let Err(e) = lexer2.calculate_indent() {
             lexer2.buffer.push_back(Err(e));
@@ -83,6 +86,22 @@ impl<'a> Lexer<'a> {
         lexer2
     }
 
+    /// The comments lexed so far, in source order.
+    pub fn into_comments(self) -> Vec<Spanned<String>> {
+        self.comments
+    }
+
+    fn add_comment(&mut self, start: usize, end: usize) {
+        let text = self.lexer.source()[start..end].trim_end();
+        self.comments.push(Spanned {
+            node: text.to_owned(),
+            span: Span::new(
+                Pos::new(start as u32),
+                Pos::new((start + text.len()) as u32),
+            ),
+        });
+    }
+
     fn err_pos<T>(&self, msg: LexemeError, pos: usize) -> anyhow::Result<T> {
         self.err_span(msg, pos, pos)
     }
@@ -138,15 +157,22 @@ impl<'a> Lexer<'a> {
                     // Remove skip now, so we can freely add it on later
                     spaces = 0;
                     tabs = 0;
-                    loop {
+                    let comment_start = it.pos() - 1;
+                    let comment_end = loop {
                         match it.next_char() {
-                            None => {
-                                self.lexer.bump(it.pos());
-                                return Ok(());
-                            }
-                            Some('\n') => break, // only the inner loop
+                            None => break None,
+                            Some('\n') => break Some(it.pos() - 1), // only the inner loop
                             Some(_) => {}
                         }
+                    };
+                    let offset = self.lexer.span().end;
+                    self.add_comment(
+                        offset + comment_start,
+                        offset + comment_end.unwrap_or(it.pos()),
+                    );
+                    if comment_end.is_none() {
+                        self.lexer.bump(it.pos());
+                        return Ok(());
                     }
                     indent_start = self.lexer.span().end + it.pos();
                 }
@@ -414,6 +440,11 @@ impl<'a> Lexer<'a> {
                                 continue;
                             }
                         }
+                        Token::Comment => {
+                            let span = self.lexer.span();
+                            self.add_comment(span.start, span.end);
+                            continue;
+                        }
                         Token::Reserved => Some(self.err_now(LexemeError::ReservedKeyword)),
                         Token::Error => Some(self.err_now(LexemeError::InvalidInput)),
                         Token::Int(radix) => {
@@ -493,10 +524,12 @@ pub enum Token {
     #[regex(" +", logos::skip)] // Whitespace
     #[token("\\\n", logos::skip)] // Escaped newline
     #[token("\\\r\n", logos::skip)] // Escaped newline (Windows line ending)
-    #[regex(r#"#[^\n]*"#, logos::skip)] // Comments
     #[error]
     Error,
 
+    #[regex(r#"#[^\n]*"#)]
+    Comment, // Comment, recorded by the lexer rather than passed to the parser
+
     #[regex("\t+")] // Tabs (might be an error)
     Tabs,
 
@@ -515,9 +548,7 @@ pub enum Token {
     #[token("r\"")]
     RawDoubleQuote,
 
-    #[regex(
-        "as|import|is|class|nonlocal|del|raise|except|try|finally|while|from|with|global|yield"
-    )]
+    #[regex("as|import|class|nonlocal|del|raise|except|try|finally|while|from|with|global|yield")]
     Reserved, // One of the reserved keywords
 
     #[regex(
@@ -561,6 +592,8 @@ pub enum Token {
     Def,
     #[token("in")]
     In,
+    #[token("is")]
+    Is,
     #[token("pass")]
     Pass,
     #[token("elif")]
@@ -691,6 +724,7 @@ impl Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Token::Error => write!(f, "lexical error"),
+            Token::Comment => write!(f, "comment"),
             Token::Indent => write!(f, "new indentation block"),
             Token::Dedent => write!(f, "end of indentation block"),
             Token::Newline => write!(f, "new line"),
@@ -705,6 +739,7 @@ impl Display for Token {
             Token::Or => write!(f, "keyword 'or'"),
             Token::Def => write!(f, "keyword 'def'"),
             Token::In => write!(f, "keyword 'in'"),
+            Token::Is => write!(f, "keyword 'is'"),
             Token::Pass => write!(f, "keyword 'pass'"),
             Token::Elif => write!(f, "keyword 'elif'"),
             Token::Return => write!(f, "keyword 'return'"),
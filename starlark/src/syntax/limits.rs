@@ -0,0 +1,52 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+const DEFAULT_MAX_DEPTH: usize = 1000;
+
+// When less than this much stack is left, recursive passes continue on a new segment.
+const STACK_RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT_SIZE: usize = 1024 * 1024;
+
+/// Run `f`, first moving to a fresh stack segment if the current one is nearly used up.
+/// Recursive passes over the AST and the compiled code call this at each level,
+/// so deeply nested code can't overflow the stack, whatever the build profile.
+pub(crate) fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, f)
+}
+
+/// Limits on the size of a module, checked after parsing by
+/// [`parse_with_limits`](crate::syntax::AstModule::parse_with_limits),
+/// to reject adversarial input before evaluation starts.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ParseLimits {
+    /// The maximum depth of nested statements, expressions and assignment targets,
+    /// e.g. `[[1]]` has depth 3 within its statement. Later passes over the code grow the
+    /// stack as needed, so any depth is safe, but deeper code takes more memory to process.
+    pub max_depth: usize,
+    /// The maximum number of statements and expressions in the module.
+    /// Unlimited by default.
+    pub max_nodes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_nodes: usize::MAX,
+        }
+    }
+}
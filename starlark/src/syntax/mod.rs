@@ -19,6 +19,13 @@
 
 pub use ast::AstModule;
 pub use dialect::Dialect;
+pub(crate) use dialect::DialectFlags;
+pub use format::format_source;
+pub(crate) use limits::ensure_sufficient_stack;
+pub use limits::ParseLimits;
+pub use reparse::TextEdit;
+
+pub use crate::analysis::{Completion, CompletionKind, HoverInfo, HoverKind, Symbol, SymbolKind};
 
 #[cfg(test)]
 mod grammar_tests;
@@ -30,8 +37,11 @@ mod testcases;
 pub(crate) mod ast;
 pub(crate) mod cursors;
 mod dialect;
+mod format;
 pub(crate) mod lexer;
+mod limits;
 pub(crate) mod payload_map;
+mod reparse;
 pub(crate) mod validate;
 
 #[allow(clippy::all)]
@@ -15,20 +15,23 @@
  * limitations under the License.
  */
 
-use std::{fs, path::Path};
+use std::{fs, mem, path::Path};
 
 use anyhow::anyhow;
 use gazebo::prelude::*;
 use lalrpop_util as lu;
 
 use crate::{
-    codemap::{CodeMap, FileSpan, Pos, Span},
+    codemap::{CodeMap, FileSpan, Pos, Span, Spanned},
     errors::Diagnostic,
     syntax::{
-        ast::{AstModule, AstStmt, Stmt},
+        ast::{AstExpr, AstModule, AstStmt, Expr, Stmt},
         dialect::Dialect,
+        format::Layout,
         grammar::StarlarkParser,
         lexer::{Lexer, Token},
+        limits::ParseLimits,
+        uniplate::VisitMut,
     },
 };
 
@@ -46,6 +49,48 @@ fn one_of(expected: &[String]) -> String {
     result
 }
 
+enum Node {
+    Stmt(AstStmt),
+    Expr(AstExpr),
+}
+
+/// Drop an AST without recursion, for one nested too deeply to drop normally.
+/// Each node has its children taken out before it is dropped, so the drop of any
+/// node only has leaves beneath it.
+fn drop_iteratively(statement: AstStmt) {
+    drop_nodes(vec![Node::Stmt(statement)])
+}
+
+/// Like [`drop_iteratively`], but for an expression.
+pub(crate) fn drop_expr_iteratively(expr: AstExpr) {
+    drop_nodes(vec![Node::Expr(expr)])
+}
+
+fn drop_nodes(mut todo: Vec<Node>) {
+    while let Some(x) = todo.pop() {
+        let mut take = |x: VisitMut<_>| match x {
+            VisitMut::Stmt(x) => {
+                let leaf = Spanned {
+                    span: x.span,
+                    node: Stmt::Pass,
+                };
+                todo.push(Node::Stmt(mem::replace(x, leaf)))
+            }
+            VisitMut::Expr(x) => {
+                let leaf = Spanned {
+                    span: x.span,
+                    node: Expr::Tuple(Vec::new()),
+                };
+                todo.push(Node::Expr(mem::replace(x, leaf)))
+            }
+        };
+        match x {
+            Node::Stmt(mut x) => x.visit_children_mut(take),
+            Node::Expr(mut x) => x.visit_expr_mut(|x| take(VisitMut::Expr(x))),
+        }
+    }
+}
+
 /// Convert the error to a codemap diagnostic.
 ///
 /// To build this diagnostic, the method needs the file span corresponding
@@ -95,13 +140,28 @@ pub(crate) fn parse_error_add_span(
 }
 
 impl AstModule {
-    fn create(
+    pub(crate) fn create(
         codemap: CodeMap,
         statement: AstStmt,
+        comments: Vec<Spanned<String>>,
         dialect: &Dialect,
+        limits: &ParseLimits,
     ) -> anyhow::Result<AstModule> {
+        if let Err((e, too_deep)) = Stmt::check_limits(&codemap, &statement, limits) {
+            if too_deep {
+                // Dropping the AST is recursive too, so would overflow the stack
+                // on the very input we are rejecting.
+                drop_iteratively(statement);
+            }
+            return Err(e);
+        }
         Stmt::validate(&codemap, &statement, dialect)?;
-        Ok(AstModule { codemap, statement })
+        Ok(AstModule {
+            codemap,
+            statement,
+            comments,
+            flags: dialect.flags(),
+        })
     }
 
     /// Parse a file stored on disk. For details see [`parse`](AstModule::parse).
@@ -125,10 +185,33 @@ impl AstModule {
     /// assert_eq!(err.span.unwrap().to_string(), "filename:2:11");
     /// ```
     pub fn parse(filename: &str, content: String, dialect: &Dialect) -> anyhow::Result<Self> {
+        Self::parse_with_limits(filename, content, dialect, &ParseLimits::default())
+    }
+
+    /// Parse a Starlark module, like [`parse`](AstModule::parse), but fail if the module
+    /// is nested more deeply or is larger than the given [`ParseLimits`].
+    /// Useful to reject adversarial input before doing any more work with it.
+    ///
+    /// ```
+    /// use starlark::syntax::{AstModule, Dialect, ParseLimits};
+    ///
+    /// let limits = ParseLimits { max_nodes: 10, ..ParseLimits::default() };
+    /// let code = "x = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]".to_owned();
+    /// let err = AstModule::parse_with_limits("x.star", code, &Dialect::Standard, &limits).unwrap_err();
+    /// assert!(err.to_string().contains("exceeded the maximum of 10"));
+    /// ```
+    pub fn parse_with_limits(
+        filename: &str,
+        content: String,
+        dialect: &Dialect,
+        limits: &ParseLimits,
+    ) -> anyhow::Result<Self> {
         let codemap = CodeMap::new(filename.to_owned(), content);
-        let lexer = Lexer::new(codemap.source(), dialect, codemap.dupe());
-        match StarlarkParser::new().parse(&codemap, dialect, lexer) {
-            Ok(v) => Ok(AstModule::create(codemap, v, dialect)?),
+        let mut lexer = Lexer::new(codemap.source(), dialect, codemap.dupe());
+        let res = StarlarkParser::new().parse(&codemap, dialect, limits, &mut lexer);
+        let comments = lexer.into_comments();
+        match res {
+            Ok(v) => Ok(AstModule::create(codemap, v, comments, dialect, limits)?),
             Err(p) => Err(parse_error_add_span(p, codemap.source().len(), &codemap)),
         }
     }
@@ -155,6 +238,25 @@ impl AstModule {
         loads
     }
 
+    /// Convert the module back to Starlark source code.
+    ///
+    /// The output is canonical rather than a copy of the original text: expressions are fully
+    /// parenthesized and indentation is two spaces. Comments are placed as by
+    /// [`format_source`](crate::syntax::format_source), except that those inside a statement
+    /// are moved above it.
+    /// Parsing the output gives an equivalent module, and unparsing that gives the same output.
+    pub fn unparse(&self) -> String {
+        self.print(Layout::Unparse)
+    }
+
+    /// The comments in the module, in source order, with their locations.
+    /// The text of each comment includes the leading `#`.
+    pub fn comments(&self) -> impl Iterator<Item = (FileSpan, &str)> {
+        self.comments
+            .iter()
+            .map(move |c| (self.file_span(c.span), c.node.as_str()))
+    }
+
     /// Look up a [`Span`] contained in this module to a [`FileSpan`].
     pub(crate) fn file_span(&self, x: Span) -> FileSpan {
         self.codemap.file_span(x)
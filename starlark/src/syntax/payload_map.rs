@@ -199,6 +199,7 @@ impl<A: AstPayload> ParameterP<A> {
                 box defa.into_map_payload(f),
             ),
             ParameterP::NoArgs => ParameterP::NoArgs,
+            ParameterP::PositionalOnly => ParameterP::PositionalOnly,
             ParameterP::Args(name, ty) => ParameterP::Args(
                 name.into_map_payload(f),
                 ty.map(|defa| box defa.into_map_payload(f)),
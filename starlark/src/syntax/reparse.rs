@@ -0,0 +1,454 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Reparsing a module after an edit, reusing the top-level statements the edit didn't touch.
+//!
+//! Every top-level statement starts at the beginning of a line and runs to the end of a line,
+//! with the lexer at the top level on either side of it. So if we reparse just the text from
+//! the statement before an edit to the statement after it, the statements outside that range
+//! are exactly what a full parse would produce, only moved by the change in length.
+//! The neighbouring statements are included since an edit next to a statement can extend it,
+//! e.g. by adding a line to the body of a `def`. If the text in between doesn't parse on its
+//! own, e.g. because the edit opened a bracket, we fall back to parsing the whole module.
+
+use std::ops::Range;
+
+use gazebo::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    codemap::{CodeMap, Pos, Span, Spanned},
+    syntax::{
+        ast::{
+            Argument, Assign, AstArgument, AstAssign, AstAssignIdent, AstExpr, AstLiteral,
+            AstParameter, AstStmt, Clause, Expr, ForClause, Parameter, Stmt,
+        },
+        AstModule, Dialect, ParseLimits,
+    },
+};
+
+#[derive(Debug, Error)]
+enum ReparseError {
+    #[error("Edit range {0}..{1} is not within the module source, of length {2}")]
+    InvalidRange(usize, usize, usize),
+}
+
+/// A change to the source of a module, as passed to [`reparse`](AstModule::reparse):
+/// the bytes in `range` of the old source are replaced with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range of the old source to replace, which must be on character boundaries.
+    pub range: Range<usize>,
+    /// The text to put in place of the range.
+    pub replacement: String,
+}
+
+impl AstModule {
+    /// Apply an edit to the source of a module and parse the result, as
+    /// [`parse`](AstModule::parse) would, but only parsing the top-level statements
+    /// next to the edit and reusing the rest from `old`. Useful for editors, which
+    /// reparse on every keystroke. Takes ownership of `old` so the unaffected statements
+    /// are moved rather than copied. The [`Dialect`] should be the one `old` was parsed with.
+    ///
+    /// ```
+    /// use starlark::syntax::{AstModule, Dialect, TextEdit};
+    ///
+    /// let old = AstModule::parse("x.star", "x = 1\ny = 2\n".to_owned(), &Dialect::Standard).unwrap();
+    /// let edit = TextEdit { range: 4..5, replacement: "100".to_owned() };
+    /// let new = AstModule::reparse(old, &edit, &Dialect::Standard).unwrap();
+    /// assert_eq!(new.unparse(), "x = 100\ny = 2\n");
+    /// ```
+    pub fn reparse(old: AstModule, edit: &TextEdit, dialect: &Dialect) -> anyhow::Result<Self> {
+        let old_source = old.codemap.source();
+        if old_source.get(edit.range.clone()).is_none() {
+            return Err(ReparseError::InvalidRange(
+                edit.range.start,
+                edit.range.end,
+                old_source.len(),
+            )
+            .into());
+        }
+        let filename = old.codemap.filename().to_owned();
+        let source = format!(
+            "{}{}{}",
+            &old_source[..edit.range.start],
+            edit.replacement,
+            &old_source[edit.range.end..]
+        );
+        let delta = edit.replacement.len() as i64 - edit.range.len() as i64;
+
+        let AstModule {
+            codemap,
+            statement,
+            comments,
+            ..
+        } = old;
+        let (top_span, mut stmts) = match statement {
+            Spanned {
+                span,
+                node: Stmt::Statements(stmts),
+            } => (span, stmts),
+            _ => return Self::parse(&filename, source, dialect),
+        };
+
+        // Statements `lo..hi` are replaced by whatever the text from `begin` to `end` parses to.
+        let (edit_start, edit_end) = (
+            Pos::new(edit.range.start as u32),
+            Pos::new(edit.range.end as u32),
+        );
+        let lo = stmts
+            .iter()
+            .position(|x| x.span.end() >= edit_start)
+            .unwrap_or(stmts.len())
+            .saturating_sub(1);
+        let hi = (stmts.iter().filter(|x| x.span.begin() <= edit_end).count() + 1).min(stmts.len());
+        let begin = if lo == 0 {
+            0
+        } else {
+            stmts[lo].span.begin().get() as usize
+        };
+        let end = if hi == stmts.len() {
+            codemap.source().len()
+        } else {
+            stmts[hi].span.begin().get() as usize
+        };
+        let new_end = (end as i64 + delta) as usize;
+
+        let fragment = match Self::parse(&filename, source[begin..new_end].to_owned(), dialect) {
+            Ok(fragment) => fragment,
+            Err(_) => return Self::parse(&filename, source, dialect),
+        };
+        let (fragment_span, mut fragment_stmts) = match fragment.statement {
+            Spanned {
+                span,
+                node: Stmt::Statements(stmts),
+            } => (span, stmts),
+            x => (x.span, vec![x]),
+        };
+        let begin_shift = begin as i64;
+        fragment_stmts
+            .iter_mut()
+            .for_each(|x| shift_stmt(x, begin_shift));
+
+        let suffix = stmts.split_off(hi);
+        stmts.truncate(lo);
+        let span = Span::new(
+            if stmts.is_empty() {
+                fragment_span.shift(begin_shift).begin()
+            } else {
+                top_span.begin()
+            },
+            if suffix.is_empty() {
+                fragment_span.shift(begin_shift).end()
+            } else {
+                Pos::new((top_span.end().get() as i64 + delta) as u32)
+            },
+        );
+        stmts.extend(fragment_stmts);
+        stmts.extend(suffix.into_map(|mut x| {
+            shift_stmt(&mut x, delta);
+            x
+        }));
+
+        let (before, rest): (Vec<_>, Vec<_>) = comments
+            .into_iter()
+            .partition(|x| (x.span.begin().get() as usize) < begin);
+        let mut new_comments = before;
+        new_comments.extend(fragment.comments.into_iter().map(|mut x| {
+            x.span = x.span.shift(begin_shift);
+            x
+        }));
+        new_comments.extend(
+            rest.into_iter()
+                .filter(|x| x.span.begin().get() as usize >= end)
+                .map(|mut x| {
+                    x.span = x.span.shift(delta);
+                    x
+                }),
+        );
+        Self::create(
+            CodeMap::new(filename, source),
+            Spanned {
+                span,
+                node: Stmt::Statements(stmts),
+            },
+            new_comments,
+            dialect,
+            &ParseLimits::default(),
+        )
+    }
+}
+
+// The shift functions are recursive, but only run on code which has passed the nesting limit.
+
+fn shift_stmt(x: &mut AstStmt, by: i64) {
+    x.span = x.span.shift(by);
+    match &mut x.node {
+        Stmt::Break | Stmt::Continue | Stmt::Pass => {}
+        Stmt::Return(x) => x.iter_mut().for_each(|x| shift_expr(x, by)),
+        Stmt::Expression(x) => shift_expr(x, by),
+        Stmt::Assign(lhs, rhs) | Stmt::AssignModify(lhs, _, rhs) => {
+            shift_assign(lhs, by);
+            shift_expr(rhs, by);
+        }
+        Stmt::Statements(xs) => xs.iter_mut().for_each(|x| shift_stmt(x, by)),
+        Stmt::If(cond, box then_block) => {
+            shift_expr(cond, by);
+            shift_stmt(then_block, by);
+        }
+        Stmt::IfElse(cond, box (then_block, else_block)) => {
+            shift_expr(cond, by);
+            shift_stmt(then_block, by);
+            shift_stmt(else_block, by);
+        }
+        Stmt::For(var, box (over, body)) => {
+            shift_assign(var, by);
+            shift_expr(over, by);
+            shift_stmt(body, by);
+        }
+        Stmt::Def(name, params, ret, body, _) => {
+            shift_ident(name, by);
+            params.iter_mut().for_each(|x| shift_param(x, by));
+            ret.iter_mut().for_each(|x| shift_expr(x, by));
+            shift_stmt(body, by);
+        }
+        Stmt::Load(load) => {
+            load.span = load.span.shift(by);
+            load.module.span = load.module.span.shift(by);
+            for (local, their) in &mut load.args {
+                shift_ident(local, by);
+                their.span = their.span.shift(by);
+            }
+        }
+    }
+}
+
+fn shift_expr(x: &mut AstExpr, by: i64) {
+    x.span = x.span.shift(by);
+    match &mut x.node {
+        Expr::Tuple(xs) | Expr::List(xs) => xs.iter_mut().for_each(|x| shift_expr(x, by)),
+        Expr::Dot(x, field) => {
+            shift_expr(x, by);
+            field.span = field.span.shift(by);
+        }
+        Expr::Call(f, args) => {
+            shift_expr(f, by);
+            args.iter_mut().for_each(|x| shift_arg(x, by));
+        }
+        Expr::ArrayIndirection(box (array, index)) => {
+            shift_expr(array, by);
+            shift_expr(index, by);
+        }
+        Expr::Slice(x, start, stop, stride) => {
+            shift_expr(x, by);
+            start.iter_mut().for_each(|x| shift_expr(x, by));
+            stop.iter_mut().for_each(|x| shift_expr(x, by));
+            stride.iter_mut().for_each(|x| shift_expr(x, by));
+        }
+        Expr::Identifier(name, _) => name.span = name.span.shift(by),
+        Expr::Lambda(params, body, _) => {
+            params.iter_mut().for_each(|x| shift_param(x, by));
+            shift_expr(body, by);
+        }
+        Expr::Literal(x) => match x {
+            AstLiteral::Int(x) => x.span = x.span.shift(by),
+            AstLiteral::Float(x) => x.span = x.span.shift(by),
+            AstLiteral::String(x) => x.span = x.span.shift(by),
+        },
+        Expr::Not(x) | Expr::Minus(x) | Expr::Plus(x) | Expr::BitNot(x) => shift_expr(x, by),
+        Expr::Op(lhs, _, rhs) => {
+            shift_expr(lhs, by);
+            shift_expr(rhs, by);
+        }
+        Expr::If(box (cond, then_expr, else_expr)) => {
+            shift_expr(cond, by);
+            shift_expr(then_expr, by);
+            shift_expr(else_expr, by);
+        }
+        Expr::Dict(xs) => xs.iter_mut().for_each(|(k, v)| {
+            shift_expr(k, by);
+            shift_expr(v, by);
+        }),
+        Expr::ListComprehension(x, for_, clauses) => {
+            shift_expr(x, by);
+            shift_for_clause(for_, by);
+            clauses.iter_mut().for_each(|x| shift_clause(x, by));
+        }
+        Expr::DictComprehension(box (k, v), for_, clauses) => {
+            shift_expr(k, by);
+            shift_expr(v, by);
+            shift_for_clause(for_, by);
+            clauses.iter_mut().for_each(|x| shift_clause(x, by));
+        }
+    }
+}
+
+fn shift_assign(x: &mut AstAssign, by: i64) {
+    x.span = x.span.shift(by);
+    match &mut x.node {
+        Assign::Tuple(xs) => xs.iter_mut().for_each(|x| shift_assign(x, by)),
+        Assign::ArrayIndirection(box (array, index)) => {
+            shift_expr(array, by);
+            shift_expr(index, by);
+        }
+        Assign::Dot(x, field) => {
+            shift_expr(x, by);
+            field.span = field.span.shift(by);
+        }
+        Assign::Identifier(x) => shift_ident(x, by),
+    }
+}
+
+fn shift_ident(x: &mut AstAssignIdent, by: i64) {
+    x.span = x.span.shift(by);
+}
+
+fn shift_param(x: &mut AstParameter, by: i64) {
+    x.span = x.span.shift(by);
+    match &mut x.node {
+        Parameter::NoArgs | Parameter::PositionalOnly => {}
+        Parameter::Normal(name, typ)
+        | Parameter::Args(name, typ)
+        | Parameter::KwArgs(name, typ) => {
+            shift_ident(name, by);
+            typ.iter_mut().for_each(|x| shift_expr(x, by));
+        }
+        Parameter::WithDefaultValue(name, typ, default) => {
+            shift_ident(name, by);
+            typ.iter_mut().for_each(|x| shift_expr(x, by));
+            shift_expr(default, by);
+        }
+    }
+}
+
+fn shift_arg(x: &mut AstArgument, by: i64) {
+    x.span = x.span.shift(by);
+    match &mut x.node {
+        Argument::Positional(x) | Argument::Args(x) | Argument::KwArgs(x) => shift_expr(x, by),
+        Argument::Named(name, x) => {
+            name.span = name.span.shift(by);
+            shift_expr(x, by);
+        }
+    }
+}
+
+fn shift_for_clause(x: &mut ForClause, by: i64) {
+    shift_assign(&mut x.var, by);
+    shift_expr(&mut x.over, by);
+}
+
+fn shift_clause(x: &mut Clause, by: i64) {
+    match x {
+        Clause::For(x) => shift_for_clause(x, by),
+        Clause::If(x) => shift_expr(x, by),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str =
+        "# top\ndef f(x):\n    # inside\n    return x + 1\n\ny = 2\n\nz = [\n  3,\n]\n# end\n";
+
+    fn parse(x: &str) -> AstModule {
+        AstModule::parse("x.star", x.to_owned(), &Dialect::Extended).unwrap()
+    }
+
+    fn top(x: &AstModule) -> &[AstStmt] {
+        match &x.statement.node {
+            Stmt::Statements(xs) => xs,
+            _ => panic!("Expected statements"),
+        }
+    }
+
+    fn def_body(x: &AstModule, name: &str) -> *const AstStmt {
+        top(x)
+            .iter()
+            .find_map(|x| match &x.node {
+                Stmt::Def(n, _, _, body, _) if n.node.0 == name => Some(&**body as *const _),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn edit(old: AstModule, from: &str, to: &str) -> anyhow::Result<AstModule> {
+        let start = old.codemap.source().find(from).unwrap();
+        let edit = TextEdit {
+            range: start..start + from.len(),
+            replacement: to.to_owned(),
+        };
+        AstModule::reparse(old, &edit, &Dialect::Extended)
+    }
+
+    /// Replace the first `from` with `to`, checking the result is the same as
+    /// parsing from scratch, down to the locations.
+    fn reparse(old: &str, from: &str, to: &str) -> AstModule {
+        let res = edit(parse(old), from, to).unwrap();
+        let full = parse(&old.replacen(from, to, 1));
+        assert_eq!(res.codemap.source(), full.codemap.source());
+        assert_eq!(format!("{:?}", top(&res)), format!("{:?}", top(&full)));
+        assert_eq!(res.comments, full.comments);
+        res
+    }
+
+    #[test]
+    fn test_reuse() {
+        // An edit far from `f` moves it to the new module rather than parsing it again
+        let old = parse(SOURCE);
+        let body = def_body(&old, "f");
+        let new = edit(old, "3", "30").unwrap();
+        assert_eq!(def_body(&new, "f"), body);
+
+        // An edit inside `f` parses it again
+        let old = parse(SOURCE);
+        let body = def_body(&old, "f");
+        let new = edit(old, "1", "2").unwrap();
+        assert_ne!(def_body(&new, "f"), body);
+    }
+
+    #[test]
+    fn test_same_as_parse() {
+        reparse(SOURCE, "3", "30");
+        reparse(SOURCE, "y = 2", "y = 200");
+        reparse(SOURCE, "x + 1", "x");
+        reparse(SOURCE, "# top\n", "");
+        reparse(SOURCE, "# end\n", "w = 4\n");
+        reparse(SOURCE, "\ny = 2\n", "");
+        // Adds to the body of `f`
+        reparse(SOURCE, "\ny = 2", "    y = 2");
+        // Joins two statements
+        reparse("x = 1\ny = 2\nz = 3\n", "\ny", "; y");
+        reparse(SOURCE, SOURCE, "");
+        reparse("", "", "x = 1\n");
+    }
+
+    #[test]
+    fn test_fallback() {
+        // The statements next to the edit don't parse on their own, but the module does
+        let res = reparse("a = 1\nb = 2\nc = 3\nd = 4\n# '''\n", "1", "'''");
+        assert_eq!(top(&res).len(), 1);
+
+        assert!(edit(parse(SOURCE), "y", "(y").is_err());
+        let edit = TextEdit {
+            range: 0..1000,
+            replacement: String::new(),
+        };
+        let err = AstModule::reparse(parse(SOURCE), &edit, &Dialect::Extended).unwrap_err();
+        assert!(err.to_string().contains("not within the module source"));
+    }
+}
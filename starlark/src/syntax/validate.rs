@@ -28,9 +28,12 @@ use crate::{
     syntax::{
         ast::{
             Argument, Assign, AssignIdentP, AssignOp, AstArgument, AstAssign, AstAssignIdent,
-            AstExpr, AstParameter, AstStmt, AstString, Expr, Parameter, Stmt,
+            AstExpr, AstParameter, AstStmt, AstString, Clause, Expr, ForClause, Parameter, Stmt,
         },
-        Dialect,
+        ensure_sufficient_stack,
+        parser::drop_expr_iteratively,
+        uniplate::Visit,
+        Dialect, ParseLimits,
     },
 };
 
@@ -52,6 +55,10 @@ enum ValidateError {
     InvalidLhs,
     #[error("left-hand-side of modifying assignment cannot be a list or tuple")]
     InvalidModifyLhs,
+    #[error("Code nested too deeply, exceeded the maximum depth of {0}")]
+    NestedTooDeeply(usize),
+    #[error("Code too large, exceeded the maximum of {0} statements and expressions")]
+    TooManyNodes(usize),
 }
 
 #[derive(Eq, PartialEq, Ord, PartialOrd)]
@@ -173,6 +180,12 @@ enum ArgumentUseOrderError {
     ArgsParameterAfterStars,
     #[error("Multiple kwargs dictionary in parameters")]
     MultipleKwargs,
+    #[error("Bare `*` must be followed by a named parameter")]
+    NoArgsWithoutNamed,
+    #[error("`/` must be preceded by a parameter")]
+    PositionalOnlyFirst,
+    #[error("`/` after another `/`, args array or kwargs dictionary")]
+    PositionalOnlyAfterStars,
 }
 
 fn check_parameters(parameters: &[AstParameter], codemap: &CodeMap) -> anyhow::Result<()> {
@@ -186,6 +199,11 @@ fn check_parameters(parameters: &[AstParameter], codemap: &CodeMap) -> anyhow::R
     let mut seen_args = false;
     let mut seen_kwargs = false;
     let mut seen_optional = false;
+    // `/` can only occur once, after a parameter and before any `*`
+    let mut seen_positional = false;
+    let mut seen_positional_only = false;
+    // A bare `*` must be followed by at least one keyword-only parameter
+    let mut no_args = None;
 
     for arg in parameters.iter() {
         match &arg.node {
@@ -193,12 +211,16 @@ fn check_parameters(parameters: &[AstParameter], codemap: &CodeMap) -> anyhow::R
                 if seen_kwargs || seen_optional {
                     return err(arg.span, ArgumentUseOrderError::PositionalThenNonPositional);
                 }
+                no_args = None;
+                seen_positional = true;
                 test_param_name(&mut argset, n, arg, codemap)?;
             }
             Parameter::WithDefaultValue(n, ..) => {
                 if seen_kwargs {
                     return err(arg.span, ArgumentUseOrderError::DefaultParameterAfterStars);
                 }
+                no_args = None;
+                seen_positional = true;
                 seen_optional = true;
                 test_param_name(&mut argset, n, arg, codemap)?;
             }
@@ -207,6 +229,16 @@ fn check_parameters(parameters: &[AstParameter], codemap: &CodeMap) -> anyhow::R
                     return err(arg.span, ArgumentUseOrderError::ArgsParameterAfterStars);
                 }
                 seen_args = true;
+                no_args = Some(arg.span);
+            }
+            Parameter::PositionalOnly => {
+                if seen_positional_only || seen_args || seen_kwargs {
+                    return err(arg.span, ArgumentUseOrderError::PositionalOnlyAfterStars);
+                }
+                if !seen_positional {
+                    return err(arg.span, ArgumentUseOrderError::PositionalOnlyFirst);
+                }
+                seen_positional_only = true;
             }
             Parameter::Args(n, ..) => {
                 if seen_args || seen_kwargs {
@@ -224,6 +256,9 @@ fn check_parameters(parameters: &[AstParameter], codemap: &CodeMap) -> anyhow::R
             }
         }
     }
+    if let Some(span) = no_args {
+        return err(span, ArgumentUseOrderError::NoArgsWithoutNamed);
+    }
     Ok(())
 }
 
@@ -251,25 +286,53 @@ impl Stmt {
         Ok(Stmt::Def(name, parameters, return_type, box stmts, ()))
     }
 
-    pub fn check_assign(codemap: &CodeMap, x: AstExpr) -> anyhow::Result<AstAssign> {
-        Ok(Spanned {
-            span: x.span,
-            node: match x.node {
+    pub fn check_assign(
+        codemap: &CodeMap,
+        limits: &ParseLimits,
+        x: AstExpr,
+    ) -> anyhow::Result<AstAssign> {
+        // Check the whole target before converting any of it, so on failure we can drop it
+        // without recursion, as it may be nested too deeply to drop normally.
+        let mut err = None;
+        let mut todo = vec![(&x, 1)];
+        while let Some((y, depth)) = todo.pop() {
+            let e = match &y.node {
+                _ if depth > limits.max_depth => ValidateError::NestedTooDeeply(limits.max_depth),
                 Expr::Tuple(xs) | Expr::List(xs) => {
-                    Assign::Tuple(xs.into_try_map(|x| Self::check_assign(codemap, x))?)
+                    todo.extend(xs.iter().rev().map(|x| (x, depth + 1)));
+                    continue;
                 }
-                Expr::Dot(a, b) => Assign::Dot(a, b),
-                Expr::ArrayIndirection(box (a, b)) => Assign::ArrayIndirection(box (a, b)),
-                Expr::Identifier(x, ()) => Assign::Identifier(x.into_map(|s| AssignIdentP(s, ()))),
-                _ => {
-                    return Err(Diagnostic::new(ValidateError::InvalidLhs, x.span, codemap));
-                }
-            },
-        })
+                Expr::Dot(..) | Expr::ArrayIndirection(..) | Expr::Identifier(..) => continue,
+                _ => ValidateError::InvalidLhs,
+            };
+            err = Some(Diagnostic::new(e, y.span, codemap));
+            break;
+        }
+        if let Some(err) = err {
+            drop_expr_iteratively(x);
+            return Err(err);
+        }
+
+        fn convert(x: AstExpr) -> AstAssign {
+            ensure_sufficient_stack(|| Spanned {
+                span: x.span,
+                node: match x.node {
+                    Expr::Tuple(xs) | Expr::List(xs) => Assign::Tuple(xs.into_map(convert)),
+                    Expr::Dot(a, b) => Assign::Dot(a, b),
+                    Expr::ArrayIndirection(box (a, b)) => Assign::ArrayIndirection(box (a, b)),
+                    Expr::Identifier(x, ()) => {
+                        Assign::Identifier(x.into_map(|s| AssignIdentP(s, ())))
+                    }
+                    _ => unreachable!("checked to be a valid assignment target"),
+                },
+            })
+        }
+        Ok(convert(x))
     }
 
     pub fn check_assignment(
         codemap: &CodeMap,
+        limits: &ParseLimits,
         lhs: AstExpr,
         op: Option<AssignOp>,
         rhs: AstExpr,
@@ -287,13 +350,113 @@ impl Stmt {
                 _ => {}
             }
         }
-        let lhs = Self::check_assign(codemap, lhs)?;
+        let lhs = Self::check_assign(codemap, limits, lhs)?;
         Ok(match op {
             None => Stmt::Assign(lhs, box rhs),
             Some(op) => Stmt::AssignModify(lhs, op, box rhs),
         })
     }
 
+    /// Check the module is within the limits. Must be called before any recursive pass
+    /// over the AST, so is itself iterative.
+    ///
+    /// On failure, returns the error along with whether the AST was nested deeper than
+    /// `max_depth`, in which case even dropping it normally may overflow the stack.
+    pub(crate) fn check_limits(
+        codemap: &CodeMap,
+        stmt: &AstStmt,
+        limits: &ParseLimits,
+    ) -> Result<(), (anyhow::Error, bool)> {
+        // The visitors on `Assign` recurse, so we walk assignment targets ourselves,
+        // which means taking apart anything containing them.
+        enum Node<'a> {
+            Stmt(&'a AstStmt),
+            Expr(&'a AstExpr),
+            Assign(&'a AstAssign),
+        }
+
+        fn push_for<'a>(x: &'a ForClause, push: &mut impl FnMut(Node<'a>)) {
+            push(Node::Assign(&x.var));
+            push(Node::Expr(&x.over));
+        }
+
+        fn push_clause<'a>(x: &'a Clause, push: &mut impl FnMut(Node<'a>)) {
+            match x {
+                Clause::For(x) => push_for(x, push),
+                Clause::If(x) => push(Node::Expr(x)),
+            }
+        }
+
+        let span = |x: &Node| match x {
+            Node::Stmt(x) => x.span,
+            Node::Expr(x) => x.span,
+            Node::Assign(x) => x.span,
+        };
+        let mut nodes = 0;
+        // The first node which pushed us over `max_nodes`. We keep going to check
+        // the depth of the rest, so know it's safe to drop the AST.
+        let mut too_many = None;
+        let mut todo = vec![(Node::Stmt(stmt), 1)];
+        while let Some((x, depth)) = todo.pop() {
+            if depth > limits.max_depth {
+                let err = ValidateError::NestedTooDeeply(limits.max_depth);
+                return Err((Diagnostic::new(err, span(&x), codemap), true));
+            }
+            nodes += 1;
+            if nodes > limits.max_nodes && too_many.is_none() {
+                too_many = Some(span(&x));
+            }
+            let mut push = |x| todo.push((x, depth + 1));
+            match x {
+                Node::Stmt(x) => match &x.node {
+                    Stmt::Assign(lhs, rhs) | Stmt::AssignModify(lhs, _, rhs) => {
+                        push(Node::Assign(lhs));
+                        push(Node::Expr(rhs));
+                    }
+                    Stmt::For(var, box (over, body)) => {
+                        push(Node::Assign(var));
+                        push(Node::Expr(over));
+                        push(Node::Stmt(body));
+                    }
+                    _ => x.visit_children(|x| match x {
+                        Visit::Stmt(x) => push(Node::Stmt(x)),
+                        Visit::Expr(x) => push(Node::Expr(x)),
+                    }),
+                },
+                Node::Expr(x) => match &x.node {
+                    Expr::ListComprehension(x, for_, clauses) => {
+                        push(Node::Expr(x));
+                        push_for(for_, &mut push);
+                        clauses.iter().for_each(|x| push_clause(x, &mut push));
+                    }
+                    Expr::DictComprehension(box (k, v), for_, clauses) => {
+                        push(Node::Expr(k));
+                        push(Node::Expr(v));
+                        push_for(for_, &mut push);
+                        clauses.iter().for_each(|x| push_clause(x, &mut push));
+                    }
+                    _ => x.visit_expr(|x| push(Node::Expr(x))),
+                },
+                Node::Assign(x) => match &x.node {
+                    Assign::Tuple(xs) => xs.iter().for_each(|x| push(Node::Assign(x))),
+                    Assign::Dot(a, _) => push(Node::Expr(a)),
+                    Assign::ArrayIndirection(box (a, b)) => {
+                        push(Node::Expr(a));
+                        push(Node::Expr(b));
+                    }
+                    Assign::Identifier(_) => {}
+                },
+            }
+        }
+        match too_many {
+            None => Ok(()),
+            Some(span) => {
+                let err = ValidateError::TooManyNodes(limits.max_nodes);
+                Err((Diagnostic::new(err, span, codemap), false))
+            }
+        }
+    }
+
     /// Validate all statements only occur where they are allowed to.
     pub fn validate(codemap: &CodeMap, stmt: &AstStmt, dialect: &Dialect) -> anyhow::Result<()> {
         // Inside a for, we allow continue/break, unless we go beneath a def.
@@ -310,7 +473,7 @@ impl Stmt {
         ) -> anyhow::Result<()> {
             let err = |x| Err(Diagnostic::new(x, stmt.span, codemap));
 
-            match &stmt.node {
+            ensure_sufficient_stack(|| match &stmt.node {
                 Stmt::Def(_, _, _, body, _payload) => f(codemap, dialect, body, false, false, true),
                 Stmt::For(_, box (_, body)) => {
                     if top_level && !dialect.enable_top_level_stmt {
@@ -335,7 +498,7 @@ impl Stmt {
                 _ => stmt.node.visit_stmt_result(|x| {
                     f(codemap, dialect, x, top_level, inside_for, inside_def)
                 }),
-            }
+            })
         }
 
         f(codemap, dialect, stmt, true, false, false)
@@ -15,8 +15,10 @@
  * limitations under the License.
  */
 
+use gazebo::prelude::*;
+
 use crate as starlark;
-use crate::values::{Freeze, Freezer, FrozenHeap};
+use crate::values::{Freeze, Freezer, FrozenHeap, FrozenValue, Heap};
 
 #[derive(Freeze)]
 struct TestStruct {
@@ -46,3 +48,35 @@ fn test_anon_struct() -> anyhow::Result<()> {
     t.freeze(&freezer)?;
     Ok(())
 }
+
+#[derive(Freeze)]
+struct TestValueStruct<V> {
+    value: V,
+    values: Vec<V>,
+    maybe: Option<V>,
+    count: i32,
+}
+
+#[test]
+fn test_value_fields() -> anyhow::Result<()> {
+    let heap = Heap::new();
+    let t = TestValueStruct {
+        value: heap.alloc("a"),
+        values: vec![heap.alloc(1), heap.alloc("b")],
+        maybe: Some(heap.alloc(vec![2])),
+        count: 3,
+    };
+    let freezer = Freezer::new(FrozenHeap::new());
+    let frozen: TestValueStruct<FrozenValue> = t.freeze(&freezer)?;
+    assert_eq!(frozen.value.to_value().unpack_str(), Some("a"));
+    assert_eq!(
+        frozen.values.map(|x| x.to_value().to_repr()),
+        vec!["1".to_owned(), "\"b\"".to_owned()]
+    );
+    assert_eq!(
+        frozen.maybe.map(|x| x.to_value().to_repr()),
+        Some("[2]".to_owned())
+    );
+    assert_eq!(frozen.count, 3);
+    Ok(())
+}
@@ -0,0 +1,57 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate as starlark;
+use crate::values::{Heap, Trace, Value};
+
+#[derive(Trace)]
+struct TestStruct<'v> {
+    values: Vec<Value<'v>>,
+    maybe: Option<Value<'v>>,
+    #[trace(skip)]
+    name: &'static str,
+}
+
+#[derive(Trace)]
+struct TestAnonStruct<'v>(Value<'v>, #[trace(skip)] Vec<u8>);
+
+#[test]
+fn test_survives_gc() {
+    let heap = Heap::new();
+    let mut x = TestStruct {
+        values: vec![heap.alloc("first"), heap.alloc(vec![1, 2])],
+        maybe: Some(heap.alloc("second")),
+        name: "test",
+    };
+    let mut y = TestAnonStruct(heap.alloc("third"), vec![1]);
+    for i in 0..1000 {
+        heap.alloc_str(&format!("garbage {}", i));
+    }
+    let stats = unsafe {
+        heap.garbage_collect(|tracer| {
+            x.trace(tracer);
+            y.trace(tracer);
+        })
+    };
+    assert!(stats.bytes_freed() > 0);
+    assert_eq!(x.values[0].unpack_str(), Some("first"));
+    assert_eq!(x.values[1].to_repr(), "[1, 2]");
+    assert_eq!(x.maybe.unwrap().unpack_str(), Some("second"));
+    assert_eq!(x.name, "test");
+    assert_eq!(y.0.unpack_str(), Some("third"));
+    assert_eq!(y.1, vec![1]);
+}
@@ -19,4 +19,5 @@ mod derive_freeze_basic;
 mod derive_freeze_bounds;
 mod derive_freeze_validator;
 mod derive_freeze_validator_order;
+mod derive_trace;
 mod freeze_access_value;
@@ -21,7 +21,13 @@ use gazebo::prelude::*;
 use indexmap::Equivalent;
 use itertools::Itertools;
 
-use crate::collections::SmallMap;
+use crate::{
+    collections::SmallMap,
+    values::{
+        float::StarlarkFloat, int::INT_TYPE, list::List, string::STRING_TYPE, tuple::Tuple,
+        type_value::TypeValue, Value, ValueLike,
+    },
+};
 
 pub fn equals_slice<E, X1, X2>(
     xs: &[X1],
@@ -77,3 +83,52 @@ pub fn compare_small_map<E, K, K2: Ord + Hash, V1, V2>(
             )?
     })
 }
+
+/// Compare two values, as the builtins which sort or find the smallest value do.
+/// Usually the same as [`Value::compare`], but with `cross_type`, set by
+/// [`enable_cross_type_ordering`](crate::syntax::Dialect::enable_cross_type_ordering),
+/// values of different types which can't be compared are ordered by type name instead,
+/// including inside lists and tuples. Values of different types which can be compared,
+/// such as ints and floats, still compare by value.
+pub(crate) fn compare_values<'v>(
+    x: Value<'v>,
+    y: Value<'v>,
+    cross_type: bool,
+) -> anyhow::Result<Ordering> {
+    if cross_type {
+        if let (Some(xs), Some(ys)) = (List::from_value(x), List::from_value(y)) {
+            return compare_elements(xs.content(), ys.content(), cross_type);
+        }
+        if let (Some(xs), Some(ys)) = (Tuple::from_value(x), Tuple::from_value(y)) {
+            return compare_elements(xs.content(), ys.content(), cross_type);
+        }
+    }
+    match x.compare(y) {
+        Err(_) if cross_type && x.get_type() != y.get_type() => {
+            Ok(type_order_key(x).cmp(type_order_key(y)))
+        }
+        res => res,
+    }
+}
+
+/// Compare two lists or tuples element by element with [`compare_values`].
+pub(crate) fn compare_elements<'v, V1: ValueLike<'v>, V2: ValueLike<'v>>(
+    xs: &[V1],
+    ys: &[V2],
+    cross_type: bool,
+) -> anyhow::Result<Ordering> {
+    compare_slice(xs, ys, |x, y| {
+        compare_values(x.to_value(), y.to_value(), cross_type)
+    })
+}
+
+/// The type name to order by when values of different types can't be compared.
+/// Types which can be compared with each other share a name, so they sort together
+/// and the order stays consistent with their own comparisons.
+fn type_order_key(x: Value) -> &'static str {
+    match x.get_type() {
+        StarlarkFloat::TYPE => INT_TYPE,
+        TypeValue::TYPE => STRING_TYPE,
+        t => t,
+    }
+}
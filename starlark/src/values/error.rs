@@ -65,8 +65,10 @@ pub enum ValueError {
 
 #[derive(Debug, Error)]
 pub(crate) enum ControlError {
-    #[error("Value of type `{0}` is not hashable")]
+    #[error("unhashable type: {0}")]
     NotHashableValue(String),
+    #[error("Tuple element at index {0} of type `{1}` is not hashable")]
+    NotHashableTupleElement(usize, String),
     #[error("Too many recursion levels")]
     TooManyRecursionLevel,
 }
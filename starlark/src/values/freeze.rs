@@ -47,6 +47,22 @@ use crate::{
 ///     data: AdditionalData,
 /// }
 /// ```
+///
+/// The derived `freeze` freezes every field, so each field must implement `Freeze`
+/// or be marked `#[freeze(identity)]`. Otherwise the derive is rejected at compile time,
+/// with the error pointing at the field, rather than a field being silently left unfrozen:
+///
+/// ```compile_fail
+/// # struct AdditionalData;
+///
+/// use starlark::values::Freeze;
+///
+/// #[derive(Freeze)]
+/// struct MyType<V> {
+///     value: V,
+///     data: AdditionalData,
+/// }
+/// ```
 pub trait Freeze {
     /// When type is frozen, it is frozen into this type.
     type Frozen;
@@ -92,6 +108,14 @@ impl Freeze for bool {
     }
 }
 
+impl Freeze for () {
+    type Frozen = ();
+
+    fn freeze(self, _freezer: &Freezer) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
 impl<'v, T: 'static> Freeze for marker::PhantomData<&'v T> {
     type Frozen = PhantomData<&'static T>;
 
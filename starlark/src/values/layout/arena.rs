@@ -166,6 +166,24 @@ pub struct HeapSummary {
 }
 
 impl Arena {
+    /// Arena with approximately `bytes` reserved up front.
+    pub(crate) fn with_capacity(bytes: usize) -> Self {
+        if bytes == 0 {
+            return Self::default();
+        }
+        // Most values do not need dropping, so reserve the space there.
+        Arena {
+            non_drop: Bump::with_capacity(bytes),
+            drop: Bump::new(),
+        }
+    }
+
+    /// Number of chunks the arena allocated memory in.
+    #[cfg(test)]
+    pub(crate) fn chunk_count(&mut self) -> usize {
+        self.non_drop.iter_allocated_chunks().count() + self.drop.iter_allocated_chunks().count()
+    }
+
     pub fn allocated_bytes(&self) -> usize {
         self.drop.allocated_bytes() + self.non_drop.allocated_bytes()
     }
@@ -880,6 +880,9 @@ impl<'v> StarlarkValueDyn<'v> for BlackHole {
     fn length(&self) -> anyhow::Result<i32> {
         panic!()
     }
+    fn length_hint(&self) -> Option<usize> {
+        panic!()
+    }
     fn get_attr(&self, _attribute: &str, _heap: &'v Heap) -> Option<Value<'v>> {
         panic!()
     }
@@ -892,18 +895,32 @@ impl<'v> StarlarkValueDyn<'v> for BlackHole {
     fn is_in(&self, _other: Value<'v>) -> anyhow::Result<bool> {
         panic!()
     }
+    fn contains_key(&self, _key: Value<'v>) -> anyhow::Result<bool> {
+        panic!()
+    }
     fn plus(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
     fn minus(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
+    fn bit_not(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        panic!()
+    }
     fn radd(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
         panic!()
     }
     fn add(&self, _rhs: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
+    fn add_assign(
+        &self,
+        _me: Value<'v>,
+        _other: Value<'v>,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        panic!()
+    }
     fn sub(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
@@ -919,13 +936,13 @@ impl<'v> StarlarkValueDyn<'v> for BlackHole {
     fn floor_div(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
-    fn bit_and(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>> {
+    fn bit_and(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
     fn bit_or(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
-    fn bit_xor(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>> {
+    fn bit_xor(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         panic!()
     }
     fn left_shift(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>> {
@@ -1063,6 +1080,9 @@ impl<'v, Mode: 'static, T: StarlarkValue<'v>> StarlarkValueDyn<'v> for AValueImp
     fn length(&self) -> anyhow::Result<i32> {
         self.1.length()
     }
+    fn length_hint(&self) -> Option<usize> {
+        self.1.length_hint()
+    }
     fn get_attr(&self, attribute: &str, heap: &'v Heap) -> Option<Value<'v>> {
         self.1.get_attr(attribute, heap)
     }
@@ -1075,18 +1095,32 @@ impl<'v, Mode: 'static, T: StarlarkValue<'v>> StarlarkValueDyn<'v> for AValueImp
     fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
         self.1.is_in(other)
     }
+    fn contains_key(&self, key: Value<'v>) -> anyhow::Result<bool> {
+        self.1.contains_key(key)
+    }
     fn plus(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.plus(heap)
     }
     fn minus(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.minus(heap)
     }
+    fn bit_not(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.1.bit_not(heap)
+    }
     fn radd(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
         self.1.radd(lhs, heap)
     }
     fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.add(rhs, heap)
     }
+    fn add_assign(
+        &self,
+        me: Value<'v>,
+        other: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        self.1.add_assign(me, other, heap)
+    }
     fn sub(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.sub(other, heap)
     }
@@ -1102,14 +1136,14 @@ impl<'v, Mode: 'static, T: StarlarkValue<'v>> StarlarkValueDyn<'v> for AValueImp
     fn floor_div(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.floor_div(other, heap)
     }
-    fn bit_and(&self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
-        self.1.bit_and(other)
+    fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.1.bit_and(other, heap)
     }
     fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.1.bit_or(other, heap)
     }
-    fn bit_xor(&self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
-        self.1.bit_xor(other)
+    fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.1.bit_xor(other, heap)
     }
     fn left_shift(&self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
         self.1.left_shift(other)
@@ -28,6 +28,7 @@ use std::{
     ops::Deref,
     ptr,
     sync::Arc,
+    time::{Duration, Instant},
     usize,
 };
 
@@ -63,6 +64,13 @@ pub struct Heap {
     /// Peak memory seen when a garbage collection takes place (may be lower than currently allocated)
     peak_allocated: Cell<usize>,
     arena: FastCell<Arena>,
+    /// Strings allocated with [`alloc_str_intern`](Heap::alloc_str_intern).
+    /// The lifetime is erased, so this must be cleared whenever the values move.
+    interned: RefCell<HashSet<StringValue<'static>>>,
+    /// Bytes to reserve at the next garbage collection, set by
+    /// [`set_size_hint`](Heap::set_size_hint) when the heap was already in use.
+    /// Zero once used, so later collections don't reserve again.
+    size_hint: Cell<usize>,
 }
 
 impl Debug for Heap {
@@ -80,8 +88,9 @@ impl Debug for Heap {
 /// Can be kept alive by a [`FrozenHeapRef`].
 #[derive(Default)]
 pub struct FrozenHeap {
-    arena: Arena,                          // My memory
-    refs: RefCell<HashSet<FrozenHeapRef>>, // Memory I depend on
+    arena: Arena,                                  // My memory
+    refs: RefCell<HashSet<FrozenHeapRef>>,         // Memory I depend on
+    interned: RefCell<HashSet<FrozenStringValue>>, // Strings allocated with `alloc_str_intern`
 }
 
 /// `FrozenHeap` when it is no longer modified and can be share between threads.
@@ -183,7 +192,7 @@ impl FrozenHeap {
     /// [`FrozenHeapRef`] which can be [`clone`](Clone::clone)d, shared between threads,
     /// and ensures the underlying values allocated on the [`FrozenHeap`] remain valid.
     pub fn into_ref(self) -> FrozenHeapRef {
-        let FrozenHeap { arena, refs } = self;
+        let FrozenHeap { arena, refs, .. } = self;
         FrozenHeapRef(Arc::new(FrozenFrozenHeap {
             arena,
             refs: refs.into_inner(),
@@ -216,6 +225,22 @@ impl FrozenHeap {
         }
     }
 
+    /// Allocate a string on this heap, reusing the previous allocation if an equal
+    /// string was already allocated with this function. Be careful about the warnings
+    /// around [`FrozenValue`].
+    pub fn alloc_str_intern(&self, x: &str) -> FrozenStringValue {
+        if let Some(x) = constant_string(x) {
+            return x;
+        }
+        let mut interned = self.interned.borrow_mut();
+        if let Some(v) = interned.get(x) {
+            return *v;
+        }
+        let v = self.alloc_str(x);
+        interned.insert(v);
+        v
+    }
+
     /// Allocate a tuple with the given elements on this heap.
     pub fn alloc_tuple<'v>(&'v self, elems: &[FrozenValue]) -> FrozenValue {
         if elems.is_empty() {
@@ -379,6 +404,29 @@ impl Heap {
         Self::default()
     }
 
+    /// Hint the number of bytes this heap is expected to grow to, so the backing
+    /// storage can be reserved up front instead of growing in steps.
+    ///
+    /// This is purely advisory: the space is reserved once, straight away if nothing has
+    /// been allocated on the heap yet, otherwise at the next garbage collection.
+    pub fn set_size_hint(&self, bytes: usize) {
+        if self.allocated_bytes() == 0 {
+            // Nothing points into the empty arena, so it is fine to replace it.
+            unsafe {
+                self.arena.take();
+                self.arena.set(Arena::with_capacity(bytes));
+            }
+            self.size_hint.set(0);
+        } else {
+            self.size_hint.set(bytes);
+        }
+    }
+
+    #[cfg(test)]
+    fn chunk_count(&self) -> usize {
+        unsafe { (*self.arena.get_mut()).chunk_count() }
+    }
+
     /// Number of bytes allocated on this heap, not including any memory
     /// represented by [`extra_memory`](crate::values::StarlarkValue::extra_memory).
     pub fn allocated_bytes(&self) -> usize {
@@ -447,6 +495,25 @@ impl Heap {
         }
     }
 
+    /// Allocate a string on the heap, reusing the previous allocation if an equal
+    /// string was already allocated with this function. Interned strings share storage,
+    /// so are [`ptr_eq`](Value::ptr_eq), which saves memory when the same string
+    /// is allocated many times. Garbage collection forgets previously interned strings,
+    /// so equal strings interned either side of a collection will be distinct.
+    pub fn alloc_str_intern<'v>(&'v self, x: &str) -> StringValue<'v> {
+        if let Some(x) = constant_string(x) {
+            return x.to_string_value();
+        }
+        let mut interned = self.interned.borrow_mut();
+        if let Some(v) = interned.get(x) {
+            // Safe because the set is cleared before any value on this heap moves.
+            return unsafe { transmute!(StringValue, StringValue, *v) };
+        }
+        let v = self.alloc_str(x);
+        interned.insert(unsafe { transmute!(StringValue, StringValue, v) });
+        v
+    }
+
     /// Allocate a string on the heap, based on two concatenated strings.
     pub fn alloc_str_concat<'v>(&'v self, x: &str, y: &str) -> StringValue<'v> {
         if x.is_empty() {
@@ -515,10 +582,21 @@ impl Heap {
         self.alloc_raw(list_avalue(array))
     }
 
-    /// Allocate a list with the given elements.
+    /// Allocate a list with the elements produced by an iterator,
+    /// without collecting them into an intermediate [`Vec`].
     pub fn alloc_list_iter<'v>(&'v self, elems: impl IntoIterator<Item = Value<'v>>) -> Value<'v> {
+        self.alloc_list_iter_with_capacity(0, elems)
+    }
+
+    /// Allocate a list with the elements produced by an iterator,
+    /// reserving space for at least `capacity` elements up front.
+    pub(crate) fn alloc_list_iter_with_capacity<'v>(
+        &'v self,
+        capacity: usize,
+        elems: impl IntoIterator<Item = Value<'v>>,
+    ) -> Value<'v> {
         let elems = elems.into_iter();
-        let array = self.alloc_array(0);
+        let array = self.alloc_array(capacity);
         let list = self.alloc_raw_typed(list_avalue(array));
         list.0.extend(elems, self);
         list.to_value()
@@ -571,14 +649,26 @@ impl Heap {
         })
     }
 
-    /// Garbage collect any values that are unused. This function is _unsafe_ in
-    /// the sense that any `Value<'v>` not returned by `Tracer` _will become
-    /// invalid_. Furthermore, any references to values, e.g `&'v str` will
+    /// Garbage collect any values that are unused, returning how much memory was freed.
+    /// The function `f` is given a [`Tracer`] and must [`trace`](Tracer::trace) every root,
+    /// usually by calling [`Trace::trace`](crate::values::Trace::trace) on the structures holding them.
+    ///
+    /// This function is _unsafe_ in the sense that any `Value<'v>` not returned by `Tracer`
+    /// _will become invalid_. Furthermore, any references to values, e.g `&'v str` will
     /// also become invalid.
-    pub(crate) unsafe fn garbage_collect<'v>(&'v self, f: impl FnOnce(&Tracer<'v>)) {
+    pub unsafe fn garbage_collect<'v>(&'v self, f: impl FnOnce(&Tracer<'v>)) -> GcStats {
+        let start = Instant::now();
+        let bytes_before = self.allocated_bytes();
         // Record the highest peak, so it never decreases
         self.peak_allocated.set(self.peak_allocated_bytes());
-        self.garbage_collect_internal(f)
+        // Interned strings are about to move, so forget them.
+        self.interned.borrow_mut().clear();
+        self.garbage_collect_internal(f);
+        GcStats {
+            bytes_before,
+            bytes_after: self.allocated_bytes(),
+            duration: start.elapsed(),
+        }
     }
 
     unsafe fn garbage_collect_internal<'v>(&'v self, f: impl FnOnce(&Tracer<'v>)) {
@@ -588,7 +678,7 @@ impl Heap {
         let _arena = self.arena.take();
 
         let tracer = Tracer::<'v> {
-            arena: Arena::default(),
+            arena: Arena::with_capacity(self.size_hint.take()),
             phantom: PhantomData,
         };
         f(&tracer);
@@ -601,6 +691,24 @@ impl Heap {
     }
 }
 
+/// The outcome of a garbage collection, as returned by [`Heap::garbage_collect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GcStats {
+    /// Bytes allocated on the heap before collecting.
+    pub bytes_before: usize,
+    /// Bytes allocated on the heap after collecting, i.e. those still reachable.
+    pub bytes_after: usize,
+    /// How long the collection took.
+    pub duration: Duration,
+}
+
+impl GcStats {
+    /// The number of bytes the collection freed.
+    pub fn bytes_freed(&self) -> usize {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
 /// Used to perform garbage collection by [`Trace::trace`](crate::values::Trace::trace).
 pub struct Tracer<'v> {
     arena: Arena,
@@ -666,3 +774,107 @@ where
     FrozenHeapRef: Send + Sync,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::values::{list::List, FrozenHeap, Heap, Value};
+
+    #[test]
+    fn test_alloc_str_intern() {
+        let heap = Heap::new();
+        let x = heap.alloc_str_intern("interned string");
+        let y = heap.alloc_str_intern("interned string");
+        assert!(x.to_value().ptr_eq(y.to_value()));
+        let z = heap.alloc_str("interned string");
+        assert!(!x.to_value().ptr_eq(z.to_value()));
+
+        let interned = Heap::new();
+        let plain = Heap::new();
+        for _ in 0..10 {
+            interned.alloc_str_intern("interned string");
+            plain.alloc_str("interned string");
+        }
+        assert!(interned.allocated_bytes() < plain.allocated_bytes());
+    }
+
+    #[test]
+    fn test_frozen_alloc_str_intern() {
+        let heap = FrozenHeap::new();
+        let x = heap.alloc_str_intern("interned string");
+        let y = heap.alloc_str_intern("interned string");
+        assert!(x.unpack().to_value().ptr_eq(y.unpack().to_value()));
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let plain = Heap::new();
+        let hinted = Heap::new();
+        hinted.set_size_hint(1 << 20);
+        for i in 0..1000 {
+            let s = format!("string number {}", i);
+            plain.alloc_str(&s);
+            hinted.alloc_str(&s);
+        }
+        assert_eq!(1, hinted.chunk_count());
+        assert!(plain.chunk_count() > 1);
+    }
+
+    #[test]
+    fn test_size_hint_reserved_once() {
+        let heap = Heap::new();
+        let mut root = heap.alloc_str("kept").to_value();
+        heap.set_size_hint(1 << 20);
+        assert_eq!(1 << 20, heap.size_hint.get());
+        unsafe { heap.garbage_collect(|tracer| tracer.trace(&mut root)) };
+        // Used by the first collection, so later ones start small.
+        assert_eq!(0, heap.size_hint.get());
+
+        let empty = Heap::new();
+        empty.set_size_hint(1 << 20);
+        assert_eq!(0, empty.size_hint.get());
+    }
+
+    #[test]
+    fn test_garbage_collect_stats() {
+        let heap = Heap::new();
+        let mut root = heap.alloc_str("kept").to_value();
+        for i in 0..1000 {
+            heap.alloc_str(&format!("garbage {}", i));
+        }
+        let stats = unsafe { heap.garbage_collect(|tracer| tracer.trace(&mut root)) };
+        assert!(stats.bytes_before > stats.bytes_after);
+        assert_eq!(stats.bytes_after, heap.allocated_bytes());
+        assert_eq!(
+            stats.bytes_freed(),
+            stats.bytes_before - heap.allocated_bytes()
+        );
+        assert_eq!(Some("kept"), root.unpack_str());
+    }
+
+    #[test]
+    fn test_alloc_list_iter() {
+        let heap = Heap::new();
+        let elems: Vec<Value> = (0..10000).map(Value::new_int).collect();
+        let from_vec = heap.alloc_list(&elems);
+        let from_iter = heap.alloc_list_iter(elems.iter().copied());
+        assert!(from_vec.equals(from_iter).unwrap());
+        assert_eq!(10000, List::from_value(from_iter).unwrap().content().len());
+
+        // Iterators without an exact size hint grow the list as they go.
+        let evens = heap.alloc_list_iter(
+            elems
+                .iter()
+                .copied()
+                .filter(|x| x.unpack_int().unwrap() % 2 == 0),
+        );
+        assert_eq!(5000, List::from_value(evens).unwrap().content().len());
+        assert_eq!(
+            Some(9998),
+            List::from_value(evens)
+                .unwrap()
+                .content()
+                .last()
+                .and_then(|x| x.unpack_int())
+        );
+    }
+}
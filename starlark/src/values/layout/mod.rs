@@ -18,7 +18,8 @@
 // Possible optimisations:
 // Encoding none, bool etc in the pointer of frozen value
 
-pub use heap::{Freezer, FrozenHeap, FrozenHeapRef, Heap, Tracer};
+pub use arena::HeapSummary;
+pub use heap::{Freezer, FrozenHeap, FrozenHeapRef, GcStats, Heap, Tracer};
 pub(crate) use pointer_i32::PointerI32;
 pub(crate) use string::StringValueLike;
 pub use string::{static_string::*, FrozenStringValue, StringValue};
@@ -409,11 +409,22 @@ impl<'v> Value<'v> {
         self.get_ref().length()
     }
 
+    /// Expected number of elements when iterating over this value,
+    /// see [`length_hint`](StarlarkValue::length_hint).
+    pub fn length_hint(self) -> Option<usize> {
+        self.get_ref().length_hint()
+    }
+
     /// `other in x`.
     pub fn is_in(self, other: Value<'v>) -> anyhow::Result<bool> {
         self.get_ref().is_in(other)
     }
 
+    /// Is `key` a key of this mapping, see [`contains_key`](StarlarkValue::contains_key).
+    pub fn contains_key(self, key: Value<'v>) -> anyhow::Result<bool> {
+        self.get_ref().contains_key(key)
+    }
+
     /// `+x`.
     pub fn plus(self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.get_ref().plus(heap)
@@ -424,6 +435,11 @@ impl<'v> Value<'v> {
         self.get_ref().minus(heap)
     }
 
+    /// `~x`.
+    pub fn bit_not(self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.get_ref().bit_not(heap)
+    }
+
     /// `x - other`.
     pub fn sub(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         self.get_ref().sub(other, heap)
@@ -450,8 +466,8 @@ impl<'v> Value<'v> {
     }
 
     /// `x & other`.
-    pub fn bit_and(self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
-        self.get_ref().bit_and(other)
+    pub fn bit_and(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.get_ref().bit_and(other, heap)
     }
 
     /// `x | other`.
@@ -460,8 +476,8 @@ impl<'v> Value<'v> {
     }
 
     /// `x ^ other`.
-    pub fn bit_xor(self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
-        self.get_ref().bit_xor(other)
+    pub fn bit_xor(self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.get_ref().bit_xor(other, heap)
     }
 
     /// `x << other`.
@@ -579,6 +595,7 @@ impl<'v> Value<'v> {
     }
 
     /// Return the contents of an iterable collection, as an owned vector.
+    /// Fails if the value is not iterable.
     pub fn iterate_collect(self, heap: &'v Heap) -> anyhow::Result<Vec<Value<'v>>> {
         // You might reasonably think this is mostly called on lists (I think it is),
         // and thus that a fast-path here would speed things up. But in my experiments
@@ -883,7 +900,13 @@ pub trait ValueLike<'v>:
         self.to_value().invoke(args, eval)
     }
 
-    /// Hash the value.
+    /// Hash the value, feeding it into `hasher`. Fails if the value is not hashable.
+    ///
+    /// To hash a host structure containing several values, create one [`StarlarkHasher`]
+    /// and write every value into it, rather than combining the results of
+    /// [`get_hashed`](ValueLike::get_hashed), which are only 32 bits.
+    /// Containers such as tuples feed each element into the same hasher, so the order
+    /// of the elements affects the hash.
     fn write_hash(self, hasher: &mut StarlarkHasher) -> anyhow::Result<()>;
 
     /// Get hash value.
@@ -1002,3 +1025,23 @@ where
     FrozenValue: Send + Sync,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroI32;
+
+    use crate::values::{range::Range, Heap, Value};
+
+    #[test]
+    fn test_iterate_collect() {
+        let heap = Heap::new();
+        let tuple = heap.alloc_tuple(&[Value::new_int(0), Value::new_int(1), Value::new_int(2)]);
+        let range = heap.alloc(Range::new(0, 3, NonZeroI32::new(1).unwrap()));
+        assert_eq!(
+            tuple.iterate_collect(&heap).unwrap(),
+            range.iterate_collect(&heap).unwrap()
+        );
+        assert_eq!(range.iterate_collect(&heap).unwrap().len(), 3);
+        assert!(Value::new_int(1).iterate_collect(&heap).is_err());
+    }
+}
@@ -34,7 +34,9 @@ pub use gazebo::{
     any::{AnyLifetime, ProvidesStaticType},
     coerce::Coerce,
 };
-pub use starlark_derive::{starlark_attrs, Freeze, NoSerialize, StarlarkAttrs, Trace};
+pub use starlark_derive::{
+    starlark_attrs, AllocValue, Freeze, NoSerialize, StarlarkAttrs, Trace, UnpackValue,
+};
 
 pub use crate::values::{
     alloc_value::*,
@@ -51,7 +53,7 @@ pub use crate::values::{
 };
 
 #[macro_use]
-mod comparison;
+pub(crate) mod comparison;
 
 // Submodules
 mod alloc_value;
@@ -66,6 +68,7 @@ pub(crate) mod iter;
 pub(crate) mod layout;
 pub(crate) mod num;
 mod owned;
+pub(crate) mod record_derive;
 pub(crate) mod recursive_repr_or_json_guard;
 mod stack_guard;
 mod trace;
@@ -19,9 +19,12 @@ use std::{fmt, fmt::Display, ops::Deref};
 
 use gazebo::prelude::*;
 
-use crate::values::{
-    none::NoneType, AllocFrozenValue, FrozenHeap, FrozenHeapRef, FrozenValue, FrozenValueTyped,
-    StarlarkValue, Value,
+use crate::{
+    eval::FrozenDef,
+    values::{
+        none::NoneType, AllocFrozenValue, FrozenHeap, FrozenHeapRef, FrozenValue, FrozenValueTyped,
+        StarlarkValue, Value, ValueLike,
+    },
 };
 
 /// A [`FrozenValue`] along with a [`FrozenHeapRef`] that ensures it is kept alive.
@@ -101,6 +104,46 @@ impl OwnedFrozenValue {
         }
     }
 
+    /// Disassemble the compiled bytecode, if this is a function defined in Starlark,
+    /// one element per instruction: the opcode name followed by its arguments.
+    ///
+    /// Useful for testing what the compiler produces.
+    /// Opcodes are an implementation detail and may change between versions.
+    ///
+    /// ```
+    /// use starlark::environment::{Globals, Module};
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::{AstModule, Dialect};
+    ///
+    /// let module = Module::new();
+    /// let ast = AstModule::parse("f.star", "def f(): return 1 + 2".to_owned(), &Dialect::Standard).unwrap();
+    /// Evaluator::new(&module).eval_module(ast, &Globals::standard()).unwrap();
+    /// let f = module.freeze().unwrap().get("f").unwrap();
+    /// assert_eq!(f.disassemble(), Some(vec!["ReturnConst 3".to_owned()]));
+    /// ```
+    pub fn disassemble(&self) -> Option<Vec<String>> {
+        Some(self.value().downcast_ref::<FrozenDef>()?.disassemble())
+    }
+
+    /// Human readable dump of the compiled bytecode, if this is a function defined in Starlark,
+    /// one instruction per line with its address, arguments and source location.
+    ///
+    /// ```
+    /// use starlark::environment::{Globals, Module};
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::{AstModule, Dialect};
+    ///
+    /// let module = Module::new();
+    /// let ast = AstModule::parse("f.star", "def f(x):\n  return x + 1".to_owned(), &Dialect::Standard).unwrap();
+    /// Evaluator::new(&module).eval_module(ast, &Globals::standard()).unwrap();
+    /// let f = module.freeze().unwrap().get("f").unwrap();
+    /// assert!(f.disassemble_pretty().unwrap().contains(": Return  # f.star:2:"));
+    /// ```
+    pub fn disassemble_pretty(&self) -> Option<String> {
+        let def = self.value().downcast_ref::<FrozenDef>()?;
+        Some(def.disassemble_pretty())
+    }
+
     /// Obtain the [`Value`] stored inside.
     pub fn value<'v>(&'v self) -> Value<'v> {
         Value::new_frozen(self.value)
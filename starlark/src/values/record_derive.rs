@@ -0,0 +1,59 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Helpers for the code generated by `#[derive(AllocValue)]` and `#[derive(UnpackValue)]`
+//! on record-like structs, where each named field becomes an entry keyed by its name.
+
+use crate::{
+    collections::SmallMap,
+    values::{dict::Dict, structs::Struct, Heap, Value},
+};
+
+/// Allocate a dict mapping each field name to its value.
+pub fn alloc_record<'v>(heap: &'v Heap, fields: Vec<(&str, Value<'v>)>) -> Value<'v> {
+    let mut content = SmallMap::with_capacity(fields.len());
+    for (name, value) in fields {
+        content.insert_hashed(heap.alloc_str(name).to_value().get_hashed().unwrap(), value);
+    }
+    heap.alloc(Dict::new(content))
+}
+
+/// Given a dict with string keys, or a struct, with exactly the field `names`,
+/// return the values of those fields in the same order.
+pub fn unpack_record<'v>(value: Value<'v>, names: &[&str]) -> Option<Vec<Value<'v>>> {
+    if let Some(dict) = Dict::from_value(value) {
+        if dict.len() != names.len() {
+            return None;
+        }
+        names.iter().map(|name| dict.get_str(name)).collect()
+    } else if let Some(s) = Struct::from_value(value) {
+        if s.fields.len() != names.len() {
+            return None;
+        }
+        names
+            .iter()
+            .map(|name| {
+                s.fields
+                    .iter()
+                    .find(|(k, _)| k.as_str() == *name)
+                    .map(|(_, v)| *v)
+            })
+            .collect()
+    } else {
+        None
+    }
+}
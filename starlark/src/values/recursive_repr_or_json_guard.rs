@@ -15,7 +15,7 @@
  * limitations under the License.
  */
 
-//! Detect recursion when doing `repr` or `to_json`.
+//! Detect recursion when doing `repr`, `to_json` or comparing containers for equality.
 
 use std::{cell::Cell, intrinsics::unlikely};
 
@@ -27,6 +27,9 @@ pub(crate) struct ReprStackGuard;
 /// Pop the stack on drop.
 pub(crate) struct JsonStackGuard;
 
+/// Pop the stack on drop.
+pub(crate) struct EqualsStackGuard;
+
 impl Drop for ReprStackGuard {
     fn drop(&mut self) {
         let mut stack = REPR_STACK.take();
@@ -45,6 +48,15 @@ impl Drop for JsonStackGuard {
     }
 }
 
+impl Drop for EqualsStackGuard {
+    fn drop(&mut self) {
+        let mut stack = EQUALS_STACK.take();
+        let popped = stack.pop();
+        debug_assert!(popped.is_some());
+        EQUALS_STACK.set(stack);
+    }
+}
+
 /// Release per-thread memory.
 ///
 /// `#[thread_local]` is fast, but it [does not call destructor on thread exit][1].
@@ -65,6 +77,7 @@ impl Drop for ReleaseMemoryOnThreadExit {
         // but that's fine because we don't need to release memory of empty sets.
         REPR_STACK.take();
         JSON_STACK.take();
+        EQUALS_STACK.take();
     }
 }
 
@@ -85,12 +98,18 @@ pub(crate) struct ReprCycle;
 /// Returned when `to_json` is called recursively and a cycle is detected.
 pub(crate) struct JsonCycle;
 
+/// Returned when `equals` is called recursively on a pair already being compared.
+pub(crate) struct EqualsCycle;
+
 #[thread_local]
 static REPR_STACK: Cell<SmallSet<usize>> = Cell::new(SmallSet::new());
 
 #[thread_local]
 static JSON_STACK: Cell<SmallSet<usize>> = Cell::new(SmallSet::new());
 
+#[thread_local]
+static EQUALS_STACK: Cell<SmallSet<(usize, usize)>> = Cell::new(SmallSet::new());
+
 /// Push a value to the stack, return error if it is already on the stack.
 pub(crate) fn repr_stack_push(value: Value) -> Result<ReprStackGuard, ReprCycle> {
     let mut stack = REPR_STACK.take();
@@ -120,3 +139,19 @@ pub(crate) fn json_stack_push(value: Value) -> Result<JsonStackGuard, JsonCycle>
         Ok(JsonStackGuard)
     }
 }
+
+/// Push a pair of values being compared, identified by their addresses,
+/// return error if the pair is already on the stack.
+pub(crate) fn equals_stack_push(x: usize, y: usize) -> Result<EqualsStackGuard, EqualsCycle> {
+    let mut stack = EQUALS_STACK.take();
+    if unlikely(stack.capacity() == 0) {
+        init_release_memory_on_thread_exit();
+    }
+    if unlikely(!stack.insert((x, y))) {
+        EQUALS_STACK.set(stack);
+        Err(EqualsCycle)
+    } else {
+        EQUALS_STACK.set(stack);
+        Ok(EqualsStackGuard)
+    }
+}
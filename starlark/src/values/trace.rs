@@ -38,9 +38,16 @@ use crate::{
 ///
 /// #[derive(Trace)]
 /// struct MySet<'v> {
-///    keys: Vec<Value<'v>>
+///    keys: Vec<Value<'v>>,
+///    // This field can't contain a `Value`, so doesn't need tracing.
+///    #[trace(skip)]
+///    name: &'static str,
 /// }
 /// ```
+///
+/// A field marked `#[trace(skip)]` must be `'static`, which is checked at compile time,
+/// since a `'static` type can't hold a `Value<'v>`. The field attribute
+/// `#[trace(unsafe_ignore)]` skips a field without that check.
 pub unsafe trait Trace<'v> {
     /// Recursively "trace" the value.
     ///
@@ -151,6 +158,10 @@ unsafe impl<'v> Trace<'v> for bool {
     fn trace(&mut self, _tracer: &Tracer<'v>) {}
 }
 
+unsafe impl<'v> Trace<'v> for () {
+    fn trace(&mut self, _tracer: &Tracer<'v>) {}
+}
+
 unsafe impl<'v> Trace<'v> for std::time::Instant {
     fn trace(&mut self, _tracer: &Tracer<'v>) {}
 }
@@ -339,6 +339,10 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
 
     /// Directly invoke a function.
     /// The number of `named` and `names` arguments are guaranteed to be equal.
+    ///
+    /// Called whenever the value is called, as `x(...)`, so implementing it makes a custom
+    /// type callable like a function. `me` is the value being called. The default fails,
+    /// since most values are not callable.
     fn invoke(
         &self,
         _me: Value<'v>,
@@ -431,6 +435,13 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
         ValueError::unsupported(self, "len()")
     }
 
+    /// Expected number of elements produced by iterating over this value,
+    /// used to pre-size collections such as `list(x)` and `tuple(x)`.
+    /// The hint does not need to be exact. Return [`None`] if unknown.
+    fn length_hint(&self) -> Option<usize> {
+        None
+    }
+
     /// Get an attribute for the current value as would be returned by dotted
     /// expression (i.e. `a.attribute`).
     ///
@@ -477,6 +488,15 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
         ValueError::unsupported_owned(other.get_type(), "in", Some(self.get_type()))
     }
 
+    /// Tell whether `key` is a key of the current value, if it is a mapping.
+    ///
+    /// Unlike [`is_in`](StarlarkValue::is_in), which follows whatever `in` means for the
+    /// type, this only ever checks keys, so is unambiguous for mapping-like types.
+    /// Defaults to [`is_in`](StarlarkValue::is_in), which is correct for `dict`.
+    fn contains_key(&self, key: Value<'v>) -> anyhow::Result<bool> {
+        self.is_in(key)
+    }
+
     /// Apply the `+` unary operator to the current value.
     ///
     /// # Examples
@@ -503,6 +523,19 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
         ValueError::unsupported(self, "-")
     }
 
+    /// Apply the `~` unary operator to the current value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// ~5 == -6
+    /// # "#);
+    /// ```
+    fn bit_not(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        ValueError::unsupported(self, "~")
+    }
+
     /// Add with the arguments the other way around. Should return [`None`]
     /// to fall through to normal add.
     fn radd(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
@@ -526,6 +559,30 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
         ValueError::unsupported_with(self, "+", rhs)
     }
 
+    /// Implement `self += other`, where `me` is the Value form of self.
+    /// Defaults to [`add`](StarlarkValue::add), producing a new value.
+    /// Mutable types may instead update themselves in place and return `me`,
+    /// which is what lists do.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # starlark::assert::is_true(r#"
+    /// x = [1, 2]
+    /// y = x
+    /// x += [3]
+    /// y == [1, 2, 3]
+    /// # "#);
+    /// ```
+    fn add_assign(
+        &self,
+        _me: Value<'v>,
+        other: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        self.add(other, heap)
+    }
+
     /// Substract `other` from the current value.
     ///
     /// # Examples
@@ -621,7 +678,7 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
     }
 
     /// Bitwise `&` operator.
-    fn bit_and(&self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
+    fn bit_and(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, "&", other)
     }
 
@@ -642,7 +699,7 @@ pub trait StarlarkValue<'v>: 'v + AnyLifetime<'v> + Debug + Display + Serialize
     }
 
     /// Bitwise `^` operator.
-    fn bit_xor(&self, other: Value<'v>) -> anyhow::Result<Value<'v>> {
+    fn bit_xor(&self, other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         ValueError::unsupported_with(self, "^", other)
     }
 
@@ -750,22 +807,31 @@ pub(crate) trait StarlarkValueDyn<'v>: 'v + Serialize {
         _f: &mut dyn FnMut(&mut dyn Iterator<Item = Value<'v>>) -> anyhow::Result<()>,
     ) -> anyhow::Result<()>;
     fn length(&self) -> anyhow::Result<i32>;
+    fn length_hint(&self) -> Option<usize>;
     fn get_attr(&self, _attribute: &str, _heap: &'v Heap) -> Option<Value<'v>>;
     fn has_attr(&self, _attribute: &str) -> bool;
     fn dir_attr(&self) -> Vec<String>;
     fn is_in(&self, _other: Value<'v>) -> anyhow::Result<bool>;
+    fn contains_key(&self, _key: Value<'v>) -> anyhow::Result<bool>;
     fn plus(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn minus(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
+    fn bit_not(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn radd(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>>;
     fn add(&self, _rhs: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
+    fn add_assign(
+        &self,
+        _me: Value<'v>,
+        _other: Value<'v>,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>>;
     fn sub(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn mul(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn percent(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn div(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn floor_div(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
-    fn bit_and(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>>;
+    fn bit_and(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn bit_or(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
-    fn bit_xor(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>>;
+    fn bit_xor(&self, _other: Value<'v>, _heap: &'v Heap) -> anyhow::Result<Value<'v>>;
     fn left_shift(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>>;
     fn right_shift(&self, _other: Value<'v>) -> anyhow::Result<Value<'v>>;
     fn export_as(&self, _variable_name: &str, _eval: &mut Evaluator<'v, '_>);
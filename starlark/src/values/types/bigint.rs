@@ -0,0 +1,520 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Arbitrary precision integers, produced when `int` arithmetic overflows 32 bits
+//! and [`enable_bigint`](crate::syntax::Dialect::enable_bigint) is set.
+//!
+//! Values which fit in 32 bits are always represented as ordinary ints, so a
+//! [`StarlarkBigInt`] is never equal to a small int.
+
+use std::{cmp::Ordering, hash::Hasher};
+
+use derive_more::Display;
+use gazebo::any::AnyLifetime;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+use serde::{ser::Error, Serialize, Serializer};
+
+use crate as starlark;
+use crate::{
+    collections::StarlarkHasher,
+    values::{
+        float::StarlarkFloat, int::INT_TYPE, num::Num, Heap, StarlarkValue, Value, ValueError,
+        ValueLike,
+    },
+};
+
+/// An integer which does not fit in 32 bits.
+#[derive(Clone, Debug, AnyLifetime, Display)]
+#[display(fmt = "{}", _0)]
+pub struct StarlarkBigInt(BigInt);
+
+starlark_simple_value!(StarlarkBigInt);
+
+impl StarlarkBigInt {
+    /// Allocate an integer, using the small int representation if it fits.
+    pub fn alloc<'v>(x: BigInt, heap: &'v Heap) -> Value<'v> {
+        match x.to_i32() {
+            Some(x) => Value::new_int(x),
+            None => heap.alloc(StarlarkBigInt(x)),
+        }
+    }
+
+    /// The underlying integer.
+    pub fn get(&self) -> &BigInt {
+        &self.0
+    }
+
+    /// A small int as a [`StarlarkBigInt`], to reuse the bigint operators when the
+    /// left operand is a small int and the right is a bigint. The result isn't normalized,
+    /// so must not be allocated.
+    pub(crate) fn promote(x: i32) -> Self {
+        StarlarkBigInt(BigInt::from(x))
+    }
+
+    /// An int computed as `i64`, which is a bigint if it doesn't fit in 32 bits.
+    /// Code with [`enable_bigint`](crate::syntax::Dialect::enable_bigint) set uses this
+    /// for the result of arithmetic on small ints, where the operators on [`Value`]
+    /// would fail with an overflow.
+    pub(crate) fn alloc_i64<'v>(x: i64, heap: &'v Heap) -> Value<'v> {
+        match i32::try_from(x) {
+            Ok(x) => Value::new_int(x),
+            Err(_) => heap.alloc(StarlarkBigInt(BigInt::from(x))),
+        }
+    }
+
+    /// Compare exactly with a float, rather than with the nearest float, which may be
+    /// the same for several large integers. NaN is greater than any integer, as it is
+    /// greater than any other float.
+    pub(crate) fn compare_float(&self, f: f64) -> Ordering {
+        if f.is_nan() {
+            return Ordering::Less;
+        }
+        // Only `None` if `f` is infinite.
+        match BigInt::from_f64(f.floor()) {
+            None if f > 0.0 => Ordering::Less,
+            None => Ordering::Greater,
+            Some(floor) => match self.0.cmp(&floor) {
+                Ordering::Equal if f.fract() != 0.0 => Ordering::Less,
+                ord => ord,
+            },
+        }
+    }
+
+    /// Round to a multiple of `10^k`, with values exactly halfway rounding to the even
+    /// multiple, as `round(x, -k)` does.
+    pub(crate) fn round_digits(&self, k: u32) -> BigInt {
+        // `10^k` is more than twice `self`, so it rounds to zero.
+        if u64::from(k) > self.0.bits() {
+            return BigInt::zero();
+        }
+        let p = BigInt::from(10).pow(k);
+        // Division truncates, so adjust to get a remainder in `0..p`.
+        let mut r = &self.0 % &p;
+        if r.is_negative() {
+            r += &p;
+        }
+        let q = (&self.0 - &r) / &p;
+        let q = match (&r + &r).cmp(&p) {
+            Ordering::Greater => q + 1,
+            Ordering::Equal if q.bit(0) => q + 1,
+            _ => q,
+        };
+        q * p
+    }
+
+    /// Unpack either a small int or a bigint as a [`BigInt`].
+    pub(crate) fn unpack_bigint(x: Value) -> Option<BigInt> {
+        match x.unpack_int() {
+            Some(x) => Some(BigInt::from(x)),
+            None => Some(x.downcast_ref::<StarlarkBigInt>()?.0.clone()),
+        }
+    }
+
+    fn bin_op<'v>(
+        &self,
+        other: Value<'v>,
+        heap: &'v Heap,
+        op: &'static str,
+        f: impl FnOnce(&BigInt, BigInt) -> BigInt,
+    ) -> anyhow::Result<Value<'v>> {
+        match Self::unpack_bigint(other) {
+            Some(other) => Ok(Self::alloc(f(&self.0, other), heap)),
+            None => ValueError::unsupported_with(self, op, other),
+        }
+    }
+
+    /// Like `bin_op`, but failing if `other` is zero.
+    fn div_op<'v>(
+        &self,
+        other: Value<'v>,
+        heap: &'v Heap,
+        op: &'static str,
+        f: impl FnOnce(&BigInt, BigInt) -> BigInt,
+    ) -> anyhow::Result<Value<'v>> {
+        match Self::unpack_bigint(other) {
+            Some(other) if other.is_zero() => Err(ValueError::DivisionByZero.into()),
+            Some(other) => Ok(Self::alloc(f(&self.0, other), heap)),
+            None => ValueError::unsupported_with(self, op, other),
+        }
+    }
+
+    /// The nearest float, which is infinite if the integer is too large.
+    fn to_float(x: &BigInt) -> StarlarkFloat {
+        StarlarkFloat(match x.to_f64() {
+            Some(x) => x,
+            None if x.is_negative() => f64::NEG_INFINITY,
+            None => f64::INFINITY,
+        })
+    }
+}
+
+impl<'v> StarlarkValue<'v> for StarlarkBigInt {
+    starlark_type!(INT_TYPE);
+
+    fn to_bool(&self) -> bool {
+        !self.0.is_zero()
+    }
+
+    fn to_int(&self) -> anyhow::Result<i32> {
+        Err(ValueError::IntegerOverflow.into())
+    }
+
+    fn extra_memory(&self) -> usize {
+        ((self.0.bits() + 7) / 8) as usize
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        // Must hash the same as an equal float.
+        match self.0.to_f64() {
+            Some(f) if BigInt::from_f64(f).as_ref() == Some(&self.0) => {
+                hasher.write_u64(Num::from(f).get_hash_64())
+            }
+            _ => hasher.write(&self.0.to_signed_bytes_le()),
+        }
+        Ok(())
+    }
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        // Bigints are normalized, so can never be equal to a small int.
+        Ok(match other.downcast_ref::<StarlarkBigInt>() {
+            Some(other) => self.0 == other.0,
+            None => match other.unpack_num() {
+                Some(Num::Float(f)) => self.compare_float(f) == Ordering::Equal,
+                _ => false,
+            },
+        })
+    }
+
+    fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
+        match Self::unpack_bigint(other) {
+            Some(other) => Ok(self.0.cmp(&other)),
+            None => match other.unpack_num() {
+                Some(Num::Float(f)) => Ok(self.compare_float(f)),
+                _ => ValueError::unsupported_with(self, "cmp()", other),
+            },
+        }
+    }
+
+    fn plus(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(self.clone()))
+    }
+
+    fn minus(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(Self::alloc(-&self.0, heap))
+    }
+
+    fn bit_not(&self, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(Self::alloc(!&self.0, heap))
+    }
+
+    fn radd(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        let lhs = lhs.unpack_int()?;
+        Some(Ok(Self::alloc(BigInt::from(lhs) + &self.0, heap)))
+    }
+
+    fn add(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "+", |a, b| a + b)
+    }
+
+    fn sub(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "-", |a, b| a - b)
+    }
+
+    fn mul(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "*", |a, b| a * b)
+    }
+
+    fn div(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let other = match Self::unpack_bigint(other) {
+            Some(other) => heap.alloc(Self::to_float(&other)),
+            None if other.unpack_num().is_some() => other,
+            None => return ValueError::unsupported_with(self, "/", other),
+        };
+        Self::to_float(&self.0).div(other, heap)
+    }
+
+    fn floor_div(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        if let Some(Num::Float(_)) = other.unpack_num() {
+            return Self::to_float(&self.0).floor_div(other, heap);
+        }
+        self.div_op(other, heap, "//", |a, b| {
+            // Division truncates, but `//` rounds towards negative infinity.
+            let r = a % &b;
+            if !r.is_zero() && r.is_negative() != b.is_negative() {
+                a / b - 1
+            } else {
+                a / b
+            }
+        })
+    }
+
+    fn percent(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        if let Some(Num::Float(_)) = other.unpack_num() {
+            return Self::to_float(&self.0).percent(other, heap);
+        }
+        self.div_op(other, heap, "%", |a, b| {
+            // The result has the sign of the divisor.
+            let r = a % &b;
+            if !r.is_zero() && r.is_negative() != b.is_negative() {
+                r + b
+            } else {
+                r
+            }
+        })
+    }
+
+    fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "&", |a, b| a & b)
+    }
+
+    fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "|", |a, b| a | b)
+    }
+
+    fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        self.bin_op(other, heap, "^", |a, b| a ^ b)
+    }
+}
+
+impl Serialize for StarlarkBigInt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0.to_i128() {
+            Some(x) => serializer.serialize_i128(x),
+            None => Err(S::Error::custom(format!(
+                "integer {} is too large to serialize",
+                self.0
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assert::Assert,
+        environment::{Globals, Module},
+        eval::Evaluator,
+        syntax::{AstModule, Dialect},
+    };
+
+    fn bigint_assert() -> Assert<'static> {
+        let mut a = Assert::new();
+        a.dialect(&Dialect {
+            enable_bigint: true,
+            ..Dialect::Extended
+        });
+        a
+    }
+
+    #[test]
+    fn test_bigint_pow() {
+        bigint_assert().pass(
+            r#"
+x = pow(2, 70)
+assert_eq(str(x), "1180591620717411303424")
+assert_eq(type(x), "int")
+assert_eq(-x + x, 0)
+assert_eq(x - pow(2, 69) * 2, 0)
+assert_eq(str(-x), "-1180591620717411303424")
+assert_eq(~x, -x - 1)
+assert_eq(~(-2147483647 - 2), 2147483647 + 1)
+"#,
+        );
+    }
+
+    #[test]
+    fn test_bigint_overflow_boundary() {
+        bigint_assert().pass(
+            r#"
+min32 = -2147483647 - 1
+# Each operator promotes when crossing the 32 bit boundary
+assert_eq(str(2147483647 + 1), "2147483648")
+assert_eq(str(min32 + -1), "-2147483649")
+assert_eq(str(2147483647 - -1), "2147483648")
+assert_eq(str(min32 - 1), "-2147483649")
+assert_eq(str(65536 * 65536), "4294967296")
+assert_eq(str(min32 * -1), "2147483648")
+assert_eq(str(min32 // -1), "2147483648")
+assert_eq(str(-min32), "2147483648")
+x = 2147483647
+x += 1
+assert_eq(str(x), "2147483648")
+
+# And keeps going past the 64 bit boundary
+max64 = pow(2, 63) - 1
+assert_eq(str(max64), "9223372036854775807")
+assert_eq(str(max64 + 1), "9223372036854775808")
+assert_eq(str(-max64 - 2), "-9223372036854775809")
+assert_eq(str(max64 * 2), "18446744073709551614")
+assert_eq(str(max64 * max64), "85070591730234615847396907784232501249")
+
+# Results which fit in 32 bits again are ordinary ints
+assert_eq(max64 - max64 + 1, 1)
+assert_eq((2147483647 + 1) - 1, 2147483647)
+"#,
+        );
+    }
+
+    #[test]
+    fn test_bigint_division() {
+        bigint_assert().pass(
+            r#"
+x = pow(2, 70)
+assert_eq((2147483647 + 1) // 2, 1073741824)
+assert_eq(str(x // 2), "590295810358705651712")
+assert_eq(str(-x // 3), "-393530540239137101142")
+assert_eq(x // x, 1)
+assert_eq(x % 7, 2)
+assert_eq(-x % 7, 5)
+assert_eq(x % -7, -5)
+assert_eq(x % x, 0)
+assert_eq(7 // x, 0)
+assert_eq(-7 // x, -1)
+assert_eq(7 % x, 7)
+assert_eq(str(-7 % x), "1180591620717411303417")
+assert_eq(x / x, 1.0)
+assert_eq((2147483647 + 1) / 2, 1073741824.0)
+assert_eq(1 / (2147483647 + 1), 1.0 / 2147483648.0)
+assert_eq((2147483647 + 1) // 2.0, 1073741824.0)
+assert_eq((2147483647 + 1) % 2.5, 0.5)
+"#,
+        );
+        bigint_assert().fail("(2147483647 + 1) // 0", "Cannot divide by zero");
+        bigint_assert().fail("(2147483647 + 1) % 0", "Cannot divide by zero");
+        bigint_assert().fail("(2147483647 + 1) / 0", "Cannot divide by zero");
+        bigint_assert().fail("(2147483647 + 1) // 'x'", "not supported");
+    }
+
+    #[test]
+    fn test_bigint_round_abs() {
+        bigint_assert().pass(
+            r#"
+x = pow(2, 70)
+assert_eq(str(abs(-2147483647 - 1)), "2147483648")
+assert_eq(abs(-x), x)
+assert_eq(abs(x), x)
+assert_eq(round(x), x)
+assert_eq(round(x, 2), x)
+assert_eq(str(round(x, -20)), "1200000000000000000000")
+assert_eq(str(round(-x, -20)), "-1200000000000000000000")
+assert_eq(str(round(x + 5, -1)), "1180591620717411303430")
+assert_eq(str(round(pow(10, 20) * 25, -21)), "2000000000000000000000")
+assert_eq(round(x, -22), 0)
+assert_eq(round(x, -1000), 0)
+assert_eq(str(round(2147483647, -1)), "2147483650")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_bigint_bitwise() {
+        bigint_assert().pass(
+            r#"
+x = pow(2, 70)
+assert_eq(x & (x - 1), 0)
+assert_eq(str(x | 1), "1180591620717411303425")
+assert_eq(x ^ (x + 1), 1)
+assert_eq(-x & 255, 0)
+assert_eq(-1 & x, x)
+assert_eq(str(-x | 5), "-1180591620717411303419")
+assert_eq(5 ^ -x, -x | 5)
+assert_eq(str(pow(2, 40) & (pow(2, 40) + 3)), "1099511627776")
+assert_eq(type(x & 255), "int")
+"#,
+        );
+        bigint_assert().fail("(2147483647 + 1) & 'x'", "not supported");
+    }
+
+    #[test]
+    fn test_bigint_compare() {
+        bigint_assert().pass(
+            r#"
+big = pow(2, 31)
+assert_true(big > 2147483647)
+assert_true(2147483647 < big)
+assert_true(-big < -2147483647)
+assert_true(big != 2147483647)
+assert_true(big == 1 + 2147483647)
+assert_eq(big - 1, 2147483647)
+assert_eq(type(big - 1), "int")
+assert_eq(sorted([big, 1, -big]), [-big, 1, big])
+assert_eq({big: 1}[2147483647 + 1], 1)
+"#,
+        );
+        bigint_assert().fail("(2147483647 + 1) < 'x'", "cmp()");
+    }
+
+    #[test]
+    fn test_bigint_compare_float() {
+        bigint_assert().pass(
+            r#"
+big = pow(2, 31)
+assert_true(big < 2147483648.5)
+assert_true(big > 2147483647.5)
+assert_true(1.5 < big)
+assert_true(-big < -2147483647.5)
+assert_true(big == 2147483648.0)
+assert_true(2147483648.0 == big)
+assert_true(big != 2147483648.5)
+assert_eq({2147483648.0: 1}[big], 1)
+# Compared exactly, not by rounding the bigint to the nearest float
+assert_true(pow(2, 53) + 1 > 9007199254740992.0)
+assert_true(pow(2, 53) + 1 != 9007199254740992.0)
+assert_true(pow(2, 2000) < float("inf"))
+assert_true(-pow(2, 2000) > float("-inf"))
+assert_true(big < float("nan"))
+assert_true(big != float("nan"))
+"#,
+        );
+    }
+
+    #[test]
+    fn test_bigint_follows_defining_module() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        let bigint = Dialect {
+            enable_bigint: true,
+            ..Dialect::Standard
+        };
+        let ast = AstModule::parse(
+            "a.star",
+            "def square(x):\n    return x * x\n".to_owned(),
+            &bigint,
+        )
+        .unwrap();
+        eval.eval_module(ast, &globals).unwrap();
+        // Code from the bigint module promotes, even when called from a module without.
+        let ast = AstModule::parse(
+            "b.star",
+            "str(square(65536))".to_owned(),
+            &Dialect::Standard,
+        )
+        .unwrap();
+        let res = eval.eval_module(ast, &globals).unwrap();
+        assert_eq!(res.unpack_str(), Some("4294967296"));
+        // But code from the other module doesn't, despite sharing the heap.
+        let ast =
+            AstModule::parse("c.star", "65536 * 65536".to_owned(), &Dialect::Standard).unwrap();
+        let err = eval.eval_module(ast, &globals).unwrap_err();
+        assert!(err.to_string().contains("Integer overflow"), "{}", err);
+    }
+}
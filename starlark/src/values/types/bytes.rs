@@ -0,0 +1,262 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The bytes type, an immutable sequence of bytes, for passing binary data between
+//! Starlark and the host. _Not part of standard Starlark._
+//!
+//! Bytes are produced from strings with `encode`, and turned back into strings with `decode`.
+//! Indexing gives the byte as an int, and slicing gives more bytes. Only available if
+//! [`enable_bytes`](crate::syntax::Dialect::enable_bytes) is set.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! "héllo".encode().decode() == "héllo"
+//! type("a".encode()) == "bytes"
+//! len("héllo".encode()) == 6
+//! "abc".encode()[0] == 97
+//! "abc".encode()[1:] == "bc".encode()
+//! # "#);
+//! ```
+
+use std::{
+    fmt::{self, Display, Write},
+    hash::Hasher,
+    str,
+};
+
+use gazebo::any::AnyLifetime;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate as starlark;
+use crate::{
+    collections::StarlarkHasher,
+    environment::{Methods, MethodsStatic},
+    values::{
+        index::{apply_slice, convert_index},
+        Heap, StarlarkValue, Value,
+    },
+};
+
+#[derive(Error, Debug)]
+enum BytesError {
+    #[error("The `bytes` type is not enabled, see `Dialect::enable_bytes`")]
+    NotEnabled,
+    #[error("Unsupported encoding `{0}`, only `utf-8` is supported")]
+    UnsupportedEncoding(String),
+    #[error("Invalid UTF-8 sequence at byte {0}")]
+    InvalidUtf8(usize),
+}
+
+/// An immutable sequence of bytes, as returned by `str.encode()`.
+/// Converts to JSON as an array of ints.
+#[derive(Debug, Clone, PartialEq, Eq, AnyLifetime, Serialize)]
+pub struct Bytes(Box<[u8]>);
+
+starlark_simple_value!(Bytes);
+
+impl Bytes {
+    /// The result of calling `type()` on bytes.
+    pub const TYPE: &'static str = "bytes";
+
+    /// Create a new [`Bytes`] value.
+    pub fn new(bytes: impl Into<Box<[u8]>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// The underlying bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Encode a string, with an encoding name as accepted by `str.encode()`.
+    /// Fails unless `enabled`, which comes from the dialect of the calling code.
+    pub(crate) fn encode(s: &str, encoding: Option<&str>, enabled: bool) -> anyhow::Result<Self> {
+        if !enabled {
+            return Err(BytesError::NotEnabled.into());
+        }
+        check_encoding(encoding)?;
+        Ok(Self::new(s.as_bytes()))
+    }
+
+    /// Decode to a string, with an encoding name as accepted by `bytes.decode()`.
+    pub(crate) fn decode(&self, encoding: Option<&str>) -> anyhow::Result<&str> {
+        check_encoding(encoding)?;
+        str::from_utf8(&self.0).map_err(|e| BytesError::InvalidUtf8(e.valid_up_to()).into())
+    }
+}
+
+/// Only UTF-8 is supported, which is also the default.
+fn check_encoding(encoding: Option<&str>) -> anyhow::Result<()> {
+    match encoding {
+        None => Ok(()),
+        Some(x) if x.eq_ignore_ascii_case("utf-8") || x.eq_ignore_ascii_case("utf8") => Ok(()),
+        Some(x) => Err(BytesError::UnsupportedEncoding(x.to_owned()).into()),
+    }
+}
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("b\"")?;
+        for &b in self.0.iter() {
+            match b {
+                b'"' => f.write_str("\\\"")?,
+                b'\\' => f.write_str("\\\\")?,
+                b'\n' => f.write_str("\\n")?,
+                b'\r' => f.write_str("\\r")?,
+                b'\t' => f.write_str("\\t")?,
+                0x20..=0x7e => f.write_char(b as char)?,
+                _ => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+impl<'v> StarlarkValue<'v> for Bytes {
+    starlark_type!(Bytes::TYPE);
+
+    fn get_methods(&self) -> Option<&'static Methods> {
+        static RES: MethodsStatic = MethodsStatic::new();
+        RES.methods(crate::stdlib::bytes::bytes_methods)
+    }
+
+    fn to_bool(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn at(&self, index: Value, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let i = convert_index(index, self.0.len() as i32)? as usize;
+        Ok(Value::new_int(self.0[i] as i32))
+    }
+
+    fn length(&self) -> anyhow::Result<i32> {
+        Ok(self.0.len() as i32)
+    }
+
+    fn slice(
+        &self,
+        start: Option<Value>,
+        stop: Option<Value>,
+        stride: Option<Value>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc_simple(Bytes::new(apply_slice(&self.0, start, stop, stride)?)))
+    }
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        match Bytes::from_value(other) {
+            None => Ok(false),
+            Some(other) => Ok(self.0 == other.0),
+        }
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        hasher.write(&self.0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assert,
+        assert::Assert,
+        environment::{Globals, Module},
+        eval::Evaluator,
+        syntax::{AstModule, Dialect},
+    };
+
+    #[test]
+    fn test_encode_decode() {
+        assert::all_true(
+            r#"
+"hello".encode() == "hello".encode("utf-8")
+"hello".encode() != "world".encode()
+"".encode().decode() == ""
+"héllo 世界".encode("UTF-8").decode("utf8") == "héllo 世界"
+repr("a\"\n".encode()) == 'b"a\\"\\n"'
+repr("é".encode()) == 'b"\\xc3\\xa9"'
+not "".encode()
+{"a".encode(): 1}["a".encode()] == 1
+"#,
+        );
+        assert::fail("'a'.encode('latin-1')", "Unsupported encoding `latin-1`");
+    }
+
+    #[test]
+    fn test_index_slice() {
+        assert::all_true(
+            r#"
+len("".encode()) == 0
+len("abc".encode()) == 3
+"abc".encode()[0] == 97
+"abc".encode()[-1] == 99
+type("é".encode()[1]) == "int"
+"é".encode()[1] == 169
+"abcd".encode()[1:3] == "bc".encode()
+"abcd".encode()[::-1] == "dcba".encode()
+"abcd".encode()[::2].decode() == "ac"
+"abc".encode() != "abc"
+json("é".encode()) == "[195,169]"
+"#,
+        );
+        assert::fail("'abc'.encode()[3]", "out of bound");
+    }
+
+    #[test]
+    fn test_dialect() {
+        let mut a = Assert::new();
+        a.dialect(&Dialect::Standard);
+        a.fail("'abc'.encode()", "not enabled");
+    }
+
+    #[test]
+    fn test_dialect_follows_defining_module() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        let ast = AstModule::parse(
+            "a.star",
+            "def encode(x):\n    return x.encode()\n".to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        eval.eval_module(ast, &globals).unwrap();
+        // Code from the module with bytes can create them, even when called from one without.
+        let ast =
+            AstModule::parse("b.star", "encode('abc')".to_owned(), &Dialect::Standard).unwrap();
+        let res = eval.eval_module(ast, &globals).unwrap();
+        assert_eq!(
+            Bytes::from_value(res).map(Bytes::as_bytes),
+            Some(&b"abc"[..])
+        );
+        // But code from the other module can't, despite sharing the heap.
+        let ast =
+            AstModule::parse("c.star", "'abc'.encode()".to_owned(), &Dialect::Standard).unwrap();
+        let err = eval.eval_module(ast, &globals).unwrap_err();
+        assert!(err.to_string().contains("not enabled"), "{}", err);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let mut a = Assert::new();
+        a.globals_add(|builder| builder.set("invalid", Bytes::new(vec![b'a', 0xc3, 0x28])));
+        a.fail("invalid.decode()", "Invalid UTF-8 sequence at byte 1");
+    }
+}
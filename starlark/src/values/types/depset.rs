@@ -0,0 +1,390 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The depset type, a set built up from other sets without copying them, created with
+//! `depset()`. _Not part of standard Starlark._
+//!
+//! A depset has some direct elements and some transitive depsets, which are shared rather
+//! than copied, so collecting the inputs of every target in a build graph takes linear
+//! space. The elements are only flattened, without duplicates, by `to_list()`.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! depset([1, 2], transitive = [depset([2, 3])]).to_list() == [2, 3, 1]
+//! depset([1, 2], order = "preorder", transitive = [depset([2, 3])]).to_list() == [1, 2, 3]
+//! # "#);
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+use gazebo::{any::AnyLifetime, coerce::Coerce};
+use thiserror::Error;
+
+use crate::{
+    self as starlark,
+    collections::{Hashed, SmallMap},
+    environment::{Methods, MethodsStatic},
+    values::{
+        display::display_container, Freeze, FrozenValue, Heap, StarlarkValue, Trace, Value,
+        ValueLike,
+    },
+};
+
+#[derive(Debug, Error)]
+enum DepsetError {
+    #[error(
+        "Unknown depset order `{0}`, expected `default`, `postorder`, `preorder` or `topological`"
+    )]
+    UnknownOrder(String),
+    #[error("Expected the transitive elements of a depset to be depsets, got `{0}`")]
+    NotDepset(String),
+    #[error("Order mismatch, can't add a depset with order `{1}` to a depset with order `{0}`")]
+    OrderMismatch(&'static str, &'static str),
+}
+
+/// The order in which [`Depset::to_list`] returns elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepsetOrder {
+    /// The same as [`Postorder`](DepsetOrder::Postorder), but a depset with this order
+    /// can contain, or be contained in, depsets of any order.
+    Default,
+    /// The elements of the transitive depsets, left to right, then the direct elements.
+    Postorder,
+    /// The direct elements, then the elements of the transitive depsets, left to right.
+    Preorder,
+    /// Every depset's direct elements before those of the depsets it contains, so an element
+    /// comes before everything it depends on.
+    Topological,
+}
+
+impl DepsetOrder {
+    fn parse(x: &str) -> anyhow::Result<Self> {
+        match x {
+            "default" => Ok(Self::Default),
+            "postorder" => Ok(Self::Postorder),
+            "preorder" => Ok(Self::Preorder),
+            "topological" => Ok(Self::Topological),
+            _ => Err(DepsetError::UnknownOrder(x.to_owned()).into()),
+        }
+    }
+
+    /// The name of the order, as passed to `depset()`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Postorder => "postorder",
+            Self::Preorder => "preorder",
+            Self::Topological => "topological",
+        }
+    }
+
+    fn is_compatible(self, other: Self) -> bool {
+        self == other || self == Self::Default || other == Self::Default
+    }
+}
+
+impl<V> DepsetGen<V> {
+    /// The result of calling `type()` on a depset.
+    pub const TYPE: &'static str = "depset";
+
+    /// The order in which [`to_list`](DepsetGen::to_list) returns elements.
+    pub fn order(&self) -> DepsetOrder {
+        self.order
+    }
+
+    /// Is the depset empty, with no elements of its own or in its transitive depsets.
+    pub fn is_empty(&self) -> bool {
+        // Empty transitive depsets are dropped on creation
+        self.direct.is_empty() && self.transitive.is_empty()
+    }
+}
+
+starlark_complex_value!(pub Depset);
+
+/// The result of calling `depset()`.
+#[derive(Clone, Debug, Trace, Freeze, AnyLifetime)]
+#[repr(C)]
+pub struct DepsetGen<V> {
+    #[trace(skip)]
+    #[freeze(identity)]
+    order: DepsetOrder,
+    /// The direct elements, as the keys of a map, so they are hashed and without duplicates.
+    direct: SmallMap<V, ()>,
+    /// The transitive depsets, all non-empty.
+    transitive: Vec<V>,
+}
+
+unsafe impl<'v> Coerce<DepsetGen<Value<'v>>> for DepsetGen<FrozenValue> {}
+
+impl<'v, V: ValueLike<'v>> DepsetGen<V> {
+    /// The elements of the depset and all its transitive depsets, without duplicates,
+    /// in the order of the depset. When an element is reachable in more than one way,
+    /// the first place it would appear is kept, except for topological order,
+    /// where the last is kept.
+    pub fn to_list(&self) -> Vec<Value<'v>> {
+        let mut res = SmallMap::new();
+        match self.order {
+            DepsetOrder::Default | DepsetOrder::Postorder => self.walk(false, false, &mut res),
+            DepsetOrder::Preorder => self.walk(true, false, &mut res),
+            DepsetOrder::Topological => {
+                // Reverse postorder over the reversed graph puts each depset before
+                // the depsets it contains, and keeps the left to right order otherwise.
+                self.walk(false, true, &mut res);
+                let mut res = res.into_iter().map(|(x, _)| x).collect::<Vec<_>>();
+                res.reverse();
+                return res;
+            }
+        }
+        res.into_iter().map(|(x, _)| x).collect()
+    }
+
+    fn direct_hashed(&self) -> Vec<Hashed<Value<'v>>> {
+        self.direct
+            .iter_hashed()
+            .map(|(x, _)| Hashed::new_unchecked(x.hash(), x.key().to_value()))
+            .collect()
+    }
+
+    /// Add the elements reachable from this depset to `res`, visiting each depset once,
+    /// with the direct elements first if `preorder`, and everything right to left if `reverse`.
+    /// Uses an explicit stack, since chains of depsets can be far deeper than the native stack.
+    fn walk(&self, preorder: bool, reverse: bool, res: &mut SmallMap<Value<'v>, ()>) {
+        let mut visited = HashSet::new();
+        let mut todo = Vec::new();
+        self.push_walk(preorder, reverse, &mut todo);
+        while let Some(x) = todo.pop() {
+            match x {
+                Walk::Depset(x) => {
+                    if visited.insert(x.ptr_value()) {
+                        // Checked to be a depset on creation
+                        Depset::from_value(x)
+                            .unwrap()
+                            .push_walk(preorder, reverse, &mut todo);
+                    }
+                }
+                Walk::Direct(direct) => {
+                    for x in direct {
+                        res.insert_hashed(x, ());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Push the work to walk this depset onto `todo`, which is done last first.
+    fn push_walk(&self, preorder: bool, reverse: bool, todo: &mut Vec<Walk<'v>>) {
+        let mut direct = self.direct_hashed();
+        let mut transitive = self
+            .transitive
+            .iter()
+            .map(|x| Walk::Depset(x.to_value()))
+            .collect::<Vec<_>>();
+        if reverse {
+            direct.reverse();
+            transitive.reverse();
+        }
+        if preorder {
+            todo.extend(transitive.into_iter().rev());
+            todo.push(Walk::Direct(direct));
+        } else {
+            todo.push(Walk::Direct(direct));
+            todo.extend(transitive.into_iter().rev());
+        }
+    }
+}
+
+/// Work left to do in [`DepsetGen::walk`].
+enum Walk<'v> {
+    /// Walk a depset, unless it has been visited already.
+    Depset(Value<'v>),
+    /// Add the direct elements of a depset to the result.
+    Direct(Vec<Hashed<Value<'v>>>),
+}
+
+impl<'v> Depset<'v> {
+    /// Create a depset from `depset()`, failing if any of `direct` are unhashable, any of
+    /// `transitive` are not depsets, or the orders of the depsets are not compatible.
+    pub(crate) fn new(
+        order: &str,
+        direct: Vec<Value<'v>>,
+        transitive: Vec<Value<'v>>,
+    ) -> anyhow::Result<Self> {
+        let order = DepsetOrder::parse(order)?;
+        let mut direct_set = SmallMap::with_capacity(direct.len());
+        for x in direct {
+            direct_set.insert_hashed(x.get_hashed()?, ());
+        }
+        let mut transitive_depsets = Vec::with_capacity(transitive.len());
+        for x in transitive {
+            let depset = match Depset::from_value(x) {
+                Some(depset) => depset,
+                None => return Err(DepsetError::NotDepset(x.to_repr()).into()),
+            };
+            if !order.is_compatible(depset.order) {
+                return Err(DepsetError::OrderMismatch(order.name(), depset.order.name()).into());
+            }
+            if !depset.is_empty() {
+                transitive_depsets.push(x);
+            }
+        }
+        Ok(Self {
+            order,
+            direct: direct_set,
+            transitive: transitive_depsets,
+        })
+    }
+}
+
+impl<'v, V: ValueLike<'v>> Display for DepsetGen<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display_container(f, "depset([", "]", self.to_list())?;
+        if self.order != DepsetOrder::Default {
+            write!(f, ", order = \"{}\"", self.order.name())?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl<'v, V: ValueLike<'v>> StarlarkValue<'v> for DepsetGen<V>
+where
+    Self: AnyLifetime<'v>,
+{
+    starlark_type!(Depset::TYPE);
+
+    fn get_methods(&self) -> Option<&'static Methods> {
+        static RES: MethodsStatic = MethodsStatic::new();
+        RES.methods(crate::stdlib::depset::depset_methods)
+    }
+
+    fn extra_memory(&self) -> usize {
+        self.direct.extra_memory() + self.transitive.capacity() * std::mem::size_of::<V>()
+    }
+
+    fn collect_repr_cycle(&self, collector: &mut String) {
+        collector.push_str("depset(...)");
+    }
+
+    fn to_bool(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_depset() {
+        assert::all_true(
+            r#"
+type(depset()) == "depset"
+depset().to_list() == []
+not depset() and bool(depset([1]))
+depset([1, 2, 1]).to_list() == [1, 2]
+x = depset([1]); depset(transitive = [x, x]).to_list() == [1]
+repr(depset([1, 2])) == "depset([1, 2])"
+repr(depset([1], order = "preorder")) == 'depset([1], order = "preorder")'
+not depset(transitive = [depset(), depset()])
+"#,
+        );
+        assert::fail("depset([[1]])", "unhashable type");
+        assert::fail("depset(transitive = [[1]])", "to be depsets");
+        assert::fail("depset(order = 'random')", "Unknown depset order `random`");
+        assert::fail(
+            "depset(order = 'preorder', transitive = [depset(order = 'postorder')])",
+            "Order mismatch",
+        );
+    }
+
+    #[test]
+    fn test_merge_order() {
+        // The classic diamond: d depends on b and c, which both depend on a.
+        let diamond = |order: &str| {
+            format!(
+                r#"
+a = depset(["a"], order = "{0}")
+b = depset(["b"], order = "{0}", transitive = [a])
+c = depset(["c"], order = "{0}", transitive = [a])
+d = depset(["d"], order = "{0}", transitive = [b, c])
+d.to_list()
+"#,
+                order
+            )
+        };
+        let check = |order: &str, want: &str| {
+            assert_eq!(assert::pass(&diamond(order)).value().to_repr(), want)
+        };
+        check("default", r#"["a", "b", "c", "d"]"#);
+        check("postorder", r#"["a", "b", "c", "d"]"#);
+        check("preorder", r#"["d", "b", "a", "c"]"#);
+        check("topological", r#"["d", "b", "c", "a"]"#);
+
+        assert::all_true(
+            r#"
+x = depset([1, 2], transitive = [depset([3, 4]), depset([5])]); x.to_list() == [3, 4, 5, 1, 2]
+x = depset([1, 2], order = "preorder", transitive = [depset([3, 4]), depset([5])]); x.to_list() == [1, 2, 3, 4, 5]
+x = depset([1, 2], order = "topological", transitive = [depset([3, 4]), depset([5])]); x.to_list() == [1, 2, 3, 4, 5]
+x = depset([1], order = "topological", transitive = [depset([2], transitive = [depset([3])])]); x.to_list() == [1, 2, 3]
+x = depset([2], transitive = [depset([1, 2, 3])]); x.to_list() == [1, 2, 3]
+x = depset([2], order = "preorder", transitive = [depset([1, 2, 3])]); x.to_list() == [2, 1, 3]
+x = depset([2], order = "topological", transitive = [depset([1, 2, 3])]); x.to_list() == [1, 2, 3]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        // Far deeper than the native stack could manage if walking the depsets recursed.
+        // The chain is local to a function, so it isn't reachable when the module is frozen.
+        assert::pass(
+            r#"
+def chain(order):
+    x = depset(order = order)
+    for i in range(100000):
+        x = depset([i], order = order, transitive = [x])
+    return x.to_list(), repr(x)
+xs, r = chain("default")
+assert_eq(len(xs), 100000)
+assert_eq(xs[:3], [0, 1, 2])
+assert_true(r.startswith("depset([0, 1, 2, "))
+xs, r = chain("preorder")
+assert_eq(xs[:3], [99999, 99998, 99997])
+"#,
+        );
+    }
+
+    #[test]
+    fn test_frozen() {
+        let mut a = assert::Assert::new();
+        a.module(
+            "deps",
+            r#"
+base = depset(["a"], order = "topological")
+lib = depset(["b"], order = "topological", transitive = [base])
+"#,
+        );
+        a.all_true(
+            r#"
+load("deps", "base", "lib"); depset(["c"], order = "topological", transitive = [lib, base]).to_list() == ["c", "b", "a"]
+load("deps", "lib"); depset(["c"], transitive = [lib]).to_list() == ["a", "b", "c"]
+"#,
+        );
+    }
+}
@@ -16,6 +16,16 @@
  */
 
 //! The dictionary type, a mutable associative-map, which iterates in insertion order.
+//!
+//! The order is guaranteed, as in Python 3.7 onwards, and is followed by iteration, `keys()`,
+//! `values()`, `items()`, `repr()` and conversion to JSON. Assigning to an existing key keeps
+//! its original position, and removing a key leaves the others in order.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! list({"b": 1, "a": 2}) == ["b", "a"]
+//! # "#);
+//! ```
 
 use std::{
     any::TypeId,
@@ -42,9 +52,10 @@ use crate::{
     environment::{Methods, MethodsStatic},
     values::{
         comparison::equals_small_map, display::display_keyed_container, error::ValueError,
-        iter::ARefIterator, string::hash_string_value, AllocFrozenValue, AllocValue, Freeze,
-        Freezer, FrozenHeap, FrozenStringValue, FrozenValue, Heap, StarlarkValue, StringValue,
-        Trace, UnpackValue, Value, ValueLike,
+        iter::ARefIterator, recursive_repr_or_json_guard::equals_stack_push,
+        string::hash_string_value, AllocFrozenValue, AllocValue, Freeze, Freezer, FrozenHeap,
+        FrozenStringValue, FrozenValue, Heap, StarlarkValue, StringValue, Trace, UnpackValue,
+        Value, ValueLike,
     },
 };
 
@@ -442,8 +453,14 @@ where
     fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
         match Dict::from_value(other) {
             None => Ok(false),
-            Some(other) => {
-                equals_small_map(&*self.0.content(), &other.content, |x, y| x.equals(*y))
+            Some(other_dict) => {
+                // Like lists, dicts can contain themselves
+                let _guard =
+                    match equals_stack_push(self as *const Self as usize, other.ptr_value()) {
+                        Ok(guard) => guard,
+                        Err(..) => return Ok(true),
+                    };
+                equals_small_map(&*self.0.content(), &other_dict.content, |x, y| x.equals(*y))
             }
         }
     }
@@ -464,10 +481,14 @@ where
     }
 
     fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        self.contains_key(other)
+    }
+
+    fn contains_key(&self, key: Value<'v>) -> anyhow::Result<bool> {
         Ok(self
             .0
             .content()
-            .contains_key_hashed(other.get_hashed()?.borrow()))
+            .contains_key_hashed(key.get_hashed()?.borrow()))
     }
 
     fn iterate<'a>(
@@ -648,9 +669,71 @@ b1 and b2 and b3
         Ok(())
     }
 
+    #[test]
+    fn test_contains_key() {
+        let heap = Heap::new();
+        let mut sm = SmallMap::new();
+        sm.insert_hashed(heap.alloc("key").get_hashed().unwrap(), heap.alloc("value"));
+        let d = heap.alloc(Dict::new(sm));
+        assert!(d.contains_key(heap.alloc("key")).unwrap());
+        assert!(!d.contains_key(heap.alloc("value")).unwrap());
+        assert!(d.contains_key(heap.alloc_list(&[])).is_err());
+        // For non-mappings, defaults to `in`
+        let xs = heap.alloc_list(&[heap.alloc("key")]);
+        assert!(xs.contains_key(heap.alloc("key")).unwrap());
+    }
+
+    #[test]
+    fn test_unhashable_key() {
+        assert::fail("d = {}\nd[[1]] = 2", "unhashable type: list");
+        assert::fail("{}.get({})", "unhashable type: dict");
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        assert::pass(
+            r#"
+d = {"z": 1, "a": 2}
+d["m"] = 3
+d["z"] = 4
+assert_eq(list(d), ["z", "a", "m"])
+assert_eq(d.keys(), ["z", "a", "m"])
+assert_eq(d.values(), [4, 2, 3])
+assert_eq(d.items(), [("z", 4), ("a", 2), ("m", 3)])
+assert_eq([k for k in d], ["z", "a", "m"])
+assert_eq(repr(d), '{"z": 4, "a": 2, "m": 3}')
+assert_eq(json(d), '{"z":4,"a":2,"m":3}')
+d.pop("a")
+d["a"] = 5
+assert_eq(json(d), '{"z":4,"m":3,"a":5}')
+"#,
+        );
+    }
+
     #[test]
     fn test_repr_cycle() {
         assert::eq("d = {}; d[17] = d; repr(d)", "'{17: {...}}'");
         assert::eq("d = {}; d[17] = d; str(d)", "'{17: {...}}'");
     }
+
+    #[test]
+    fn test_equals_cycle() {
+        assert::pass(
+            r#"
+a = {"x": 1}
+a["self"] = a
+b = {"x": 1}
+b["self"] = b
+assert_eq(a, b)
+c = {"x": 2}
+c["self"] = c
+assert_ne(a, c)
+l1 = [{}]
+l1[0]["l"] = l1
+l2 = [{}]
+l2[0]["l"] = l2
+assert_eq(l1, l2)
+"#,
+        );
+    }
 }
@@ -26,6 +26,8 @@ use std::{
 use gazebo::{any::AnyLifetime, prelude::*};
 use serde::Serialize;
 
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
 use crate::{
     collections::StarlarkHasher,
     values::{
@@ -209,6 +211,10 @@ impl<'v> StarlarkValue<'v> for StarlarkFloat {
     }
 
     fn equals(&self, other: Value) -> anyhow::Result<bool> {
+        #[cfg(feature = "bigint")]
+        if let Some(other) = StarlarkBigInt::from_value(other) {
+            return Ok(other.compare_float(self.0) == Ordering::Equal);
+        }
         if other.unpack_num().is_some() {
             Ok(self.compare(other)? == Ordering::Equal)
         } else {
@@ -285,6 +291,10 @@ impl<'v> StarlarkValue<'v> for StarlarkFloat {
     }
 
     fn compare(&self, other: Value) -> anyhow::Result<Ordering> {
+        #[cfg(feature = "bigint")]
+        if let Some(other) = StarlarkBigInt::from_value(other) {
+            return Ok(other.compare_float(self.0).reverse());
+        }
         if let Some(other_float) = other.unpack_num().map(|n| n.as_float()) {
             // According to the spec (https://github.com/bazelbuild/starlark/blob/689f54426951638ef5b7c41a14d8fc48e65c5f77/spec.md#floating-point-numbers)
             // All NaN values compare equal to each other, but greater than any non-NaN float value.
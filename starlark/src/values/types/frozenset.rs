@@ -0,0 +1,282 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The frozenset type, an immutable set of hashable values created with `frozenset()`.
+//!
+//! Elements are kept in insertion order, but equality and hashing ignore the order,
+//! so a frozenset can be used as a dictionary key or an element of another frozenset.
+//!
+//! ```
+//! # starlark::assert::is_true(r#"
+//! frozenset([1, 2, 2]) == frozenset([2, 1])
+//! # "#);
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    hash::Hasher,
+};
+
+use gazebo::{any::AnyLifetime, coerce::Coerce};
+use indexmap::Equivalent;
+use serde::Serialize;
+
+use crate::{
+    self as starlark,
+    collections::{Hashed, SmallMap, StarlarkHasher},
+    values::{
+        display::display_container, error::ValueError, Freeze, FrozenValue, Heap, StarlarkValue,
+        Trace, Value, ValueLike,
+    },
+};
+
+impl<V> FrozenSetGen<V> {
+    /// The result of calling `type()` on a frozenset.
+    pub const TYPE: &'static str = "frozenset";
+
+    /// Create a new [`FrozenSet`] from a map whose keys are the elements.
+    pub fn new(content: SmallMap<V, ()>) -> Self {
+        Self { content }
+    }
+
+    /// The number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.content.len()
+    }
+
+    /// Is the set empty.
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+}
+
+starlark_complex_value!(pub FrozenSet);
+
+/// The result of calling `frozenset()`.
+#[derive(Clone, Default, Debug, Trace, Freeze, AnyLifetime)]
+#[repr(C)]
+pub struct FrozenSetGen<V> {
+    /// The elements of the set, as the keys of a map.
+    content: SmallMap<V, ()>,
+}
+
+unsafe impl<'v> Coerce<FrozenSetGen<Value<'v>>> for FrozenSetGen<FrozenValue> {}
+
+impl<'v, V: ValueLike<'v>> FrozenSetGen<V> {
+    /// Iterate over the elements of the set.
+    pub fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = Value<'v>> + 'a
+    where
+        'v: 'a,
+    {
+        self.content.keys().map(|x| x.to_value())
+    }
+
+    fn iter_hashed<'a>(&'a self) -> impl Iterator<Item = Hashed<Value<'v>>> + 'a
+    where
+        'v: 'a,
+    {
+        self.content
+            .iter_hashed()
+            .map(|(x, _)| Hashed::new_unchecked(x.hash(), x.key().to_value()))
+    }
+
+    fn contains_hashed(&self, x: Hashed<Value<'v>>) -> bool
+    where
+        Value<'v>: Equivalent<V>,
+    {
+        self.content.contains_key_hashed(x.borrow())
+    }
+}
+
+impl<'v> FrozenSet<'v> {
+    /// Build a frozenset from the elements of an iterator, failing if any are unhashable.
+    pub fn from_values(it: impl Iterator<Item = Value<'v>>) -> anyhow::Result<Self> {
+        let mut content = SmallMap::with_capacity(it.size_hint().0);
+        for x in it {
+            content.insert_hashed(x.get_hashed()?, ());
+        }
+        Ok(Self::new(content))
+    }
+
+    fn from_hashed(it: impl Iterator<Item = Hashed<Value<'v>>>) -> Self {
+        let mut content = SmallMap::with_capacity(it.size_hint().0);
+        for x in it {
+            content.insert_hashed(x, ());
+        }
+        Self::new(content)
+    }
+
+    fn from_value_or_unsupported(
+        lhs: &impl StarlarkValue<'v>,
+        op: &str,
+        rhs: Value<'v>,
+    ) -> anyhow::Result<&'v Self> {
+        FrozenSet::from_value(rhs).map_or_else(|| ValueError::unsupported_with(lhs, op, rhs), Ok)
+    }
+}
+
+impl<'v, V: ValueLike<'v>> Display for FrozenSetGen<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display_container(f, "frozenset([", "])", self.content.keys())
+    }
+}
+
+impl<'v, V: ValueLike<'v>> StarlarkValue<'v> for FrozenSetGen<V>
+where
+    Self: AnyLifetime<'v>,
+    Value<'v>: Equivalent<V>,
+{
+    starlark_type!(FrozenSet::TYPE);
+
+    fn extra_memory(&self) -> usize {
+        self.content.extra_memory()
+    }
+
+    fn collect_repr_cycle(&self, collector: &mut String) {
+        collector.push_str("frozenset(...)");
+    }
+
+    fn to_bool(&self) -> bool {
+        !self.is_empty()
+    }
+
+    fn length(&self) -> anyhow::Result<i32> {
+        Ok(self.len() as i32)
+    }
+
+    fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        Ok(self.contains_hashed(other.get_hashed()?))
+    }
+
+    fn iterate<'a>(
+        &'a self,
+        _heap: &'v Heap,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = Value<'v>> + 'a>>
+    where
+        'v: 'a,
+    {
+        Ok(box self.iter())
+    }
+
+    fn with_iterator(
+        &self,
+        _heap: &'v Heap,
+        f: &mut dyn FnMut(&mut dyn Iterator<Item = Value<'v>>) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        f(&mut self.iter())
+    }
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        match FrozenSet::from_value(other) {
+            None => Ok(false),
+            Some(other) => {
+                Ok(self.len() == other.len()
+                    && self.iter_hashed().all(|x| other.contains_hashed(x)))
+            }
+        }
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        // Elements were hashed on insertion, so combine their hashes with a commutative
+        // operation, making the result independent of insertion order.
+        let sum = self
+            .content
+            .iter_hashed()
+            .fold(0u32, |acc, (x, _)| acc.wrapping_add(x.hash().get()));
+        hasher.write_u32(sum);
+        hasher.write_usize(self.len());
+        Ok(())
+    }
+
+    fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let other = FrozenSet::from_value_or_unsupported(self, "|", other)?;
+        Ok(heap.alloc(FrozenSet::from_hashed(
+            self.iter_hashed().chain(other.iter_hashed()),
+        )))
+    }
+
+    fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let other = FrozenSet::from_value_or_unsupported(self, "&", other)?;
+        Ok(heap.alloc(FrozenSet::from_hashed(
+            self.iter_hashed().filter(|x| other.contains_hashed(*x)),
+        )))
+    }
+
+    fn sub(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let other = FrozenSet::from_value_or_unsupported(self, "-", other)?;
+        Ok(heap.alloc(FrozenSet::from_hashed(
+            self.iter_hashed().filter(|x| !other.contains_hashed(*x)),
+        )))
+    }
+
+    fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let other = FrozenSet::from_value_or_unsupported(self, "^", other)?;
+        Ok(heap.alloc(FrozenSet::from_hashed(
+            self.iter_hashed()
+                .filter(|x| !other.contains_hashed(*x))
+                .chain(other.iter_hashed().filter(|x| !self.contains_hashed(*x))),
+        )))
+    }
+}
+
+impl<'v, V: ValueLike<'v>> Serialize for FrozenSetGen<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_frozenset() {
+        assert::all_true(
+            r#"
+s = frozenset([1, 2, 2, 3]); len(s) == 3
+s = frozenset([1, 2, 2, 3]); 2 in s
+s = frozenset([1, 2, 2, 3]); 4 not in s
+s = frozenset([1, 2, 2, 3]); list(s) == [1, 2, 3]
+s = frozenset([1, 2, 2, 3]); type(s) == "frozenset"
+s = frozenset([1, 2, 2, 3]); repr(s) == "frozenset([1, 2, 3])"
+not frozenset()
+frozenset([1, 2]) | frozenset([2, 3]) == frozenset([1, 2, 3])
+frozenset([1, 2]) & frozenset([2, 3]) == frozenset([2])
+frozenset([1, 2]) - frozenset([2, 3]) == frozenset([1])
+frozenset([1, 2]) ^ frozenset([2, 3]) == frozenset([1, 3])
+json(frozenset(["a", "b"])) == '["a","b"]'
+"#,
+        );
+        assert::fail("frozenset([[1]])", "unhashable type: list");
+        assert::fail("frozenset([1]) | [2]", "not supported");
+    }
+
+    #[test]
+    fn test_frozenset_hash() {
+        assert::all_true(
+            r#"
+d = {frozenset([1, 2, 3]): "x"}; d[frozenset([3, 2, 1])] == "x"
+d = {frozenset([1, 2, 3]): "x"}; frozenset([1, 2, 3]) in d
+d = {frozenset([1, 2, 3]): "x"}; frozenset([1, 2]) not in d
+frozenset([frozenset([1, 2])]) == frozenset([frozenset([2, 1])])
+"#,
+        );
+    }
+}
@@ -15,13 +15,15 @@
  * limitations under the License.
  */
 
-//! The integer type. Currently limited to 32 bit.
+//! The integer type. Limited to 32 bit unless the `bigint` feature is enabled.
 //!
 //! Can be created with [`new_int`](Value::new_int) and unwrapped with [`unpack_int`](Value::unpack_int).
 //! Unlike most Starlark values, these aren't actually represented on the [`Heap`], but as special values.
-//! At some point in the future we plan to support arbitrary sized integers (as required by the
-//! [Starlark spec](https://github.com/bazelbuild/starlark/blob/master/spec.md#integers)), and those larger
-//! integer values will be stored on the heap.
+//! With the `bigint` feature and [`enable_bigint`](crate::syntax::Dialect::enable_bigint), arithmetic
+//! which overflows promotes to arbitrary sized integers (as required by the
+//! [Starlark spec](https://github.com/bazelbuild/starlark/blob/master/spec.md#integers)), which are stored
+//! on the heap. Otherwise, `+`, `-`, `*` and `//` (and unary `-`) fail with an "Integer overflow" error
+//! when the result doesn't fit in 32 bits, rather than wrapping around.
 
 use std::{
     cmp::Ordering,
@@ -29,8 +31,12 @@ use std::{
     hash::Hasher,
 };
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
 use serde::{Serialize, Serializer};
 
+#[cfg(feature = "bigint")]
+use crate::values::bigint::StarlarkBigInt;
 use crate::{
     collections::{StarlarkHashValue, StarlarkHasher},
     values::{
@@ -79,6 +85,31 @@ where
     }
 }
 
+/// Produce an int from the result of arithmetic on two 32 bit ints,
+/// which can't overflow an `i64`, failing if it doesn't fit in 32 bits.
+/// Code with [`enable_bigint`](crate::syntax::Dialect::enable_bigint) set promotes
+/// such results instead, see `StarlarkBigInt::int_op`.
+fn int_result<'v>(x: i64) -> anyhow::Result<Value<'v>> {
+    match i32::try_from(x) {
+        Ok(x) => Ok(Value::new_int(x)),
+        Err(_) => Err(ValueError::IntegerOverflow.into()),
+    }
+}
+
+/// `a // b` rounding towards negative infinity, or `None` if `b` is zero.
+/// On 32 bit operands the result can't overflow an `i64`.
+pub(crate) fn int_floor_div(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        return None;
+    }
+    let offset = if (a < 0) != (b < 0) && a % b != 0 {
+        1
+    } else {
+        0
+    };
+    Some(a / b - offset)
+}
+
 impl Display for PointerI32 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.get())
@@ -118,44 +149,46 @@ impl<'v> StarlarkValue<'v> for PointerI32 {
         Ok(Value::new_int(self.get()))
     }
     fn minus(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
-        self.get()
-            .checked_neg()
-            .map(Value::new_int)
-            .ok_or_else(|| ValueError::IntegerOverflow.into())
+        int_result(-(self.get() as i64))
+    }
+    fn bit_not(&self, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(Value::new_int(!self.get()))
     }
     fn add(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         match other.unpack_num() {
-            Some(Num::Int(other)) => self
-                .get()
-                .checked_add(other)
-                .map(Value::new_int)
-                .ok_or_else(|| ValueError::IntegerOverflow.into()),
+            Some(Num::Int(other)) => int_result(self.get() as i64 + other as i64),
             Some(Num::Float(_)) => StarlarkFloat(self.get() as f64).add(other, heap),
             None => ValueError::unsupported_with(self, "+", other),
         }
     }
     fn sub(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         match other.unpack_num() {
-            Some(Num::Int(other)) => self
-                .get()
-                .checked_sub(other)
-                .map(Value::new_int)
-                .ok_or_else(|| ValueError::IntegerOverflow.into()),
+            Some(Num::Int(other)) => int_result(self.get() as i64 - other as i64),
             Some(Num::Float(_)) => StarlarkFloat(self.get() as f64).sub(other, heap),
-            None => ValueError::unsupported_with(self, "-", other),
+            None => {
+                #[cfg(feature = "bigint")]
+                if let Some(other) = StarlarkBigInt::from_value(other) {
+                    return Ok(StarlarkBigInt::alloc(
+                        BigInt::from(self.get()) - other.get(),
+                        heap,
+                    ));
+                }
+                ValueError::unsupported_with(self, "-", other)
+            }
         }
     }
     fn mul(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         if let Some(other) = other.unpack_int() {
-            self.get()
-                .checked_mul(other)
-                .ok_or_else(|| ValueError::IntegerOverflow.into())
-                .map(Value::new_int)
+            int_result(self.get() as i64 * other as i64)
         } else {
             other.mul(Value::new_int(self.get()), heap)
         }
     }
     fn div(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        #[cfg(feature = "bigint")]
+        if StarlarkBigInt::from_value(other).is_some() {
+            return StarlarkBigInt::promote(self.get()).div(other, heap);
+        }
         if other.unpack_num().is_some() {
             StarlarkFloat(self.get() as f64).div(other, heap)
         } else {
@@ -166,6 +199,10 @@ impl<'v> StarlarkValue<'v> for PointerI32 {
         if let Some(Num::Float(_)) = other.unpack_num() {
             return StarlarkFloat(self.get() as f64).percent(other, heap);
         }
+        #[cfg(feature = "bigint")]
+        if StarlarkBigInt::from_value(other).is_some() {
+            return StarlarkBigInt::promote(self.get()).percent(other, heap);
+        }
         i64_arith_bin_op(self.get(), other, "%", |a, b| {
             if b == 0 {
                 return Err(ValueError::DivisionByZero.into());
@@ -186,47 +223,69 @@ impl<'v> StarlarkValue<'v> for PointerI32 {
         if let Some(Num::Float(_)) = other.unpack_num() {
             return StarlarkFloat(self.get() as f64).floor_div(other, heap);
         }
-        i64_arith_bin_op(self.get(), other, "//", |a, b| {
-            if b == 0 {
-                return Err(ValueError::DivisionByZero.into());
-            }
-            let sig = b.signum() * a.signum();
-            let offset = if sig < 0 && a % b != 0 { 1 } else { 0 };
-            match a.checked_div(b) {
-                Some(div) => Ok(div - offset),
-                None => Err(ValueError::IntegerOverflow.into()),
+        match other.unpack_int() {
+            Some(0) => Err(ValueError::DivisionByZero.into()),
+            // Only `i32::min_value() // -1` overflows, so follow the same policy as `+`
+            Some(b) => int_result(int_floor_div(self.get() as i64, b as i64).unwrap()),
+            None => {
+                #[cfg(feature = "bigint")]
+                if StarlarkBigInt::from_value(other).is_some() {
+                    return StarlarkBigInt::promote(self.get()).floor_div(other, heap);
+                }
+                ValueError::unsupported_owned(INT_TYPE, "//", Some(INT_TYPE))
             }
-        })
+        }
     }
 
     fn compare(&self, other: Value) -> anyhow::Result<Ordering> {
         match other.unpack_num() {
             Some(Num::Int(other)) => Ok(self.get().cmp(&other)),
             Some(Num::Float(_)) => StarlarkFloat(self.get() as f64).compare(other),
-            None => ValueError::unsupported_with(self, "==", other),
+            None => {
+                #[cfg(feature = "bigint")]
+                if let Some(other) = StarlarkBigInt::from_value(other) {
+                    return Ok(BigInt::from(self.get()).cmp(other.get()));
+                }
+                ValueError::unsupported_with(self, "==", other)
+            }
         }
     }
 
-    fn bit_and(&self, other: Value) -> anyhow::Result<Value<'v>> {
+    #[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+    fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         if let Some(other) = other.unpack_int() {
             Ok(Value::new_int(self.get() & other))
         } else {
+            #[cfg(feature = "bigint")]
+            if StarlarkBigInt::from_value(other).is_some() {
+                return StarlarkBigInt::promote(self.get()).bit_and(other, heap);
+            }
             ValueError::unsupported_with(self, "&", other)
         }
     }
 
-    fn bit_or(&self, other: Value, _heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+    #[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+    fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         if let Some(other) = other.unpack_int() {
             Ok(Value::new_int(self.get() | other))
         } else {
+            #[cfg(feature = "bigint")]
+            if StarlarkBigInt::from_value(other).is_some() {
+                return StarlarkBigInt::promote(self.get()).bit_or(other, heap);
+            }
             ValueError::unsupported_with(self, "|", other)
         }
     }
 
-    fn bit_xor(&self, other: Value) -> anyhow::Result<Value<'v>> {
+    #[cfg_attr(not(feature = "bigint"), allow(unused_variables))]
+    fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         if let Some(other) = other.unpack_int() {
             Ok(Value::new_int(self.get() ^ other))
         } else {
+            #[cfg(feature = "bigint")]
+            if StarlarkBigInt::from_value(other).is_some() {
+                return StarlarkBigInt::promote(self.get()).bit_xor(other, heap);
+            }
             ValueError::unsupported_with(self, "^", other)
         }
     }
@@ -295,4 +354,40 @@ mod tests {
 "#,
         );
     }
+
+    #[test]
+    fn test_overflow() {
+        assert::fail("2147483647 + 1", "Integer overflow");
+        assert::fail("-2147483647 - 2", "Integer overflow");
+        assert::fail("65536 * 65536", "Integer overflow");
+        assert::fail("-(-2147483647 - 1)", "Integer overflow");
+    }
+
+    #[test]
+    fn test_overflow_boundary() {
+        // Without `enable_bigint`, results must fit in 32 bits
+        assert::all_true(
+            r#"
+2147483646 + 1 == 2147483647
+-2147483647 + -1 == -2147483647 - 1
+-2147483647 - 1 < 0
+2147483647 - 2147483647 == 0
+46341 * 46340 == 2147441940
+-65536 * 32768 == -2147483647 - 1
+(-2147483647 - 1) // 1 == -2147483647 - 1
+(-2147483647 - 1) // 2 == -1073741824
+-7 // 2 == -4
+7 // -2 == -4
+"#,
+        );
+        assert::fail("1 + 2147483647", "Integer overflow");
+        assert::fail("(-2147483647 - 1) + -1", "Integer overflow");
+        assert::fail("2147483647 - -1", "Integer overflow");
+        assert::fail("0 - (-2147483647 - 1)", "Integer overflow");
+        assert::fail("46341 * 46341", "Integer overflow");
+        assert::fail("-65536 * 32769", "Integer overflow");
+        assert::fail("(-2147483647 - 1) * -1", "Integer overflow");
+        assert::fail("(-2147483647 - 1) // -1", "Integer overflow");
+        assert::fail("1 // 0", "Cannot divide by zero");
+    }
 }
@@ -41,10 +41,11 @@ use crate::{
     environment::{Methods, MethodsStatic},
     values::{
         array::Array,
-        comparison::{compare_slice, equals_slice},
+        comparison::{compare_elements, equals_slice},
         display::display_container,
         error::ValueError,
         index::{apply_slice, convert_index},
+        recursive_repr_or_json_guard::equals_stack_push,
         AllocFrozenValue, AllocValue, FrozenHeap, FrozenStringValue, FrozenValue, Heap,
         StarlarkValue, UnpackValue, Value, ValueLike, ValueTyped,
     },
@@ -483,14 +484,23 @@ where
     fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
         match List::from_value(other) {
             None => Ok(false),
-            Some(other) => equals_slice(&*self.0.content(), &other.content, |x, y| x.equals(*y)),
+            Some(other_list) => {
+                // Lists can contain themselves. If we get back to comparing the same pair,
+                // assume they are equal, and let the other elements decide.
+                let _guard =
+                    match equals_stack_push(self as *const Self as usize, other.ptr_value()) {
+                        Ok(guard) => guard,
+                        Err(..) => return Ok(true),
+                    };
+                equals_slice(&*self.0.content(), &other_list.content, |x, y| x.equals(*y))
+            }
         }
     }
 
     fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
         match List::from_value(other) {
             None => ValueError::unsupported_with(self, "cmp()", other),
-            Some(other) => compare_slice(&*self.0.content(), &other.content, |x, y| x.compare(*y)),
+            Some(other) => compare_elements(&*self.0.content(), &other.content, false),
         }
     }
 
@@ -507,6 +517,10 @@ where
         Ok(self.0.content().len() as i32)
     }
 
+    fn length_hint(&self) -> Option<usize> {
+        Some(self.0.content().len())
+    }
+
     fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
         for x in self.0.content().iter() {
             if x.equals(other)? {
@@ -554,6 +568,26 @@ where
         }
     }
 
+    fn add_assign(
+        &self,
+        me: Value<'v>,
+        other: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        // The Starlark spec says list += mutates, while nothing else does.
+        // If the value is None, that must mean its a FrozenList, thus turn it into an immutable error
+        let list = List::from_value_mut(me)?.ok_or(ValueError::CannotMutateImmutableValue)?;
+        // Be careful if they alias, so we don't have `me`
+        // mutably borrowed when we iterate over `other`.
+        if me.ptr_eq(other) {
+            list.double(heap);
+        } else {
+            // TODO: if RHS is list, consider calling `List::extend_from_slice`.
+            other.with_iterator(heap, |it| list.extend(it, heap))?;
+        }
+        Ok(me)
+    }
+
     fn mul(&self, other: Value, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
         let l = i32::unpack_param(other)?;
         let mut result = Vec::with_capacity(self.0.content().len() * cmp::max(0, l) as usize);
@@ -640,7 +674,31 @@ impl<'v, V: UnpackValue<'v>> Deref for ListOf<'v, V> {
 
 #[cfg(test)]
 mod tests {
-    use crate::assert::{self, Assert};
+    use crate::{
+        assert::{self, Assert},
+        environment::{Globals, Module},
+        eval::Evaluator,
+        syntax::{AstModule, Dialect},
+        values::list::List,
+    };
+
+    #[test]
+    fn test_list_reserves_length_hint() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        let mut eval = Evaluator::new(&module);
+        let program = "def f(n):\n  return list(range(n))\nx = f(1000)";
+        eval.eval_module(
+            AstModule::parse("a.star", program.to_owned(), &Dialect::Standard).unwrap(),
+            &globals,
+        )
+        .unwrap();
+        let x = List::from_value_mut(module.get("x").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(1000, x.len());
+        assert_eq!(1000, x.content.get().capacity());
+    }
 
     #[test]
     fn test_to_str() {
@@ -657,6 +715,62 @@ str([]) == "[]"
     fn test_repr_cycle() {
         assert::eq("l = []; l.append(l); repr(l)", "'[[...]]'");
         assert::eq("l = []; l.append(l); str(l)", "'[[...]]'");
+        assert::pass(
+            r#"
+a = [1]
+b = [a]
+a.append(b)
+assert_eq(repr(a), "[1, [[...]]]")
+assert_eq(repr(b), "[[1, [...]]]")
+
+# Cycles through other containers
+d = {"k": []}
+d["k"].append(d)
+assert_eq(repr(d), '{"k": [{...}]}')
+t = ([],)
+t[0].append(t)
+assert_eq(repr(t), "([(...)],)")
+
+# Other ways of rendering a value detect the cycle too
+assert_eq("{}".format(a), "[1, [[...]]]")
+assert_eq("%s" % [a], "[[1, [[...]]]]")
+
+# Only values currently being rendered are elided, not repeated ones
+x = [1]
+assert_eq(repr([x, x, [x]]), "[[1], [1], [[1]]]")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_equals_cycle() {
+        assert::pass(
+            r#"
+a = [1]
+a.append(a)
+b = [1]
+b.append(b)
+assert_eq(a, a)
+assert_eq(a, b)
+# Unrolling a cycle gives an equal value
+c = [1, [1]]
+c[1].append(c)
+assert_eq(a, c)
+
+# Differences after the cycle are still found
+x = []
+x.append(x)
+x.append(1)
+y = []
+y.append(y)
+y.append(2)
+assert_ne(x, y)
+d = [2]
+d.append(d)
+assert_ne(a, d)
+assert_ne(a, [1, [1, [1]]])
+"#,
+        );
     }
 
     #[test]
@@ -17,10 +17,15 @@
 
 pub mod any;
 pub mod array;
+#[cfg(feature = "bigint")]
+pub mod bigint;
 pub mod bool;
+pub mod bytes;
 pub mod dict;
+pub mod depset;
 pub mod enumeration;
 pub mod float;
+pub mod frozenset;
 pub mod function;
 pub mod int;
 pub(crate) mod known_methods;
@@ -28,7 +33,9 @@ pub mod list;
 pub mod none;
 pub mod range;
 pub mod record;
+pub mod select;
 pub mod string;
 pub mod structs;
 pub mod tuple;
+pub mod type_value;
 pub(crate) mod unbound;
@@ -152,6 +152,10 @@ impl<'v> StarlarkValue<'v> for Range {
         Ok(Value::new_int(self.start + self.step.get() * index))
     }
 
+    fn length_hint(&self) -> Option<usize> {
+        self.length().ok().map(|n| n as usize)
+    }
+
     fn equals(&self, other: Value) -> anyhow::Result<bool> {
         if let Some(other) = other.downcast_ref::<Self>() {
             self.equals_range(&*other)
@@ -256,7 +260,10 @@ impl PartialEq for Range {
 mod tests {
     use std::num::NonZeroI32;
 
-    use crate::values::{range::Range, Heap, StarlarkValue, Value};
+    use crate::{
+        assert,
+        values::{range::Range, Heap, StarlarkValue, Value},
+    };
 
     fn range(start: i32, stop: i32, range: i32) -> Range {
         Range {
@@ -342,4 +349,31 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_range_lazy() {
+        // None of these materialize the range, so they are instant even on huge ranges.
+        let heap = Heap::new();
+        let before = heap.allocated_bytes();
+        let big = range_stop(i32::max_value());
+        assert_eq!(Some(i32::max_value()), big.length().ok());
+        assert!(big.is_in(Value::new_int(i32::max_value() - 1)).unwrap());
+        assert_eq!(
+            Value::new_int(1000),
+            big.at(Value::new_int(1000), &heap).unwrap()
+        );
+        assert_eq!(before, heap.allocated_bytes());
+
+        assert::all_true(
+            r#"
+len(range(1000000)) == 1000000
+range(0, 10, 2)[3] == 6
+range(0, 10, 2)[-1] == 8
+999999 in range(1000000)
+1000000 not in range(1000000)
+range(1000000)[10:20:5] == range(10, 20, 5)
+type(range(10)) == "range"
+"#,
+        );
+    }
 }
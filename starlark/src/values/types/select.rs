@@ -0,0 +1,255 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The select type, a value which depends on configuration the host only knows after
+//! evaluation, created with `select()`. _Not part of standard Starlark._
+//!
+//! A `select` maps conditions to values. Selects can be added to each other and to ordinary
+//! values, and the host later calls [`Select::resolve`] to pick one value for each `select`
+//! and add them all together.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! type(select({"debug": ["-g"], "//conditions:default": []})) == "select"
+//! type(["-O"] + select({"debug": ["-g"], "//conditions:default": []})) == "select"
+//! # "#);
+//! ```
+
+use std::fmt::{self, Display};
+
+use gazebo::{any::AnyLifetime, coerce::Coerce, prelude::*};
+use thiserror::Error;
+
+use crate::{
+    self as starlark,
+    collections::SmallMap,
+    values::{
+        display::display_keyed_container, Freeze, FrozenValue, Heap, StarlarkValue, Trace, Value,
+        ValueLike,
+    },
+};
+
+#[derive(Debug, Error)]
+enum SelectError {
+    #[error("`select()` needs at least one condition")]
+    Empty,
+    #[error(
+        "No condition of `select()` holds and there is no `//conditions:default`, conditions are {0}"
+    )]
+    NoMatch(String),
+}
+
+impl<V> SelectGen<V> {
+    /// The result of calling `type()` on a select.
+    pub const TYPE: &'static str = "select";
+
+    /// The condition used when no other condition of a `select` holds.
+    pub const DEFAULT_CONDITION: &'static str = "//conditions:default";
+}
+
+starlark_complex_value!(pub Select);
+
+/// The result of calling `select()`, or adding one to another value.
+#[derive(Clone, Debug, Trace, Freeze, NoSerialize, AnyLifetime)]
+#[repr(C)]
+pub struct SelectGen<V> {
+    /// The selects which were added together, in order, each mapping conditions to values.
+    /// An ordinary value added to a select is stored with just the default condition.
+    /// Never empty.
+    parts: Vec<SmallMap<String, V>>,
+}
+
+unsafe impl<'v> Coerce<SelectGen<Value<'v>>> for SelectGen<FrozenValue> {}
+
+impl<'v, V: ValueLike<'v>> SelectGen<V> {
+    fn value_parts(&self) -> Vec<SmallMap<String, Value<'v>>> {
+        self.parts
+            .map(|x| x.iter().map(|(k, v)| (k.clone(), v.to_value())).collect())
+    }
+
+    /// Pick a value for each `select` making up this value and add them together, in order.
+    /// The value of a `select` is the first of its conditions for which `config` returns `true`,
+    /// or the value for [`DEFAULT_CONDITION`](SelectGen::DEFAULT_CONDITION) if there is none.
+    /// Fails if a `select` has no matching condition and no default, or if adding the values fails.
+    pub fn resolve(
+        &self,
+        config: impl Fn(&str) -> bool,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        let mut res: Option<Value<'v>> = None;
+        for part in &self.parts {
+            let value = part
+                .iter()
+                .find(|(k, _)| k.as_str() != Self::DEFAULT_CONDITION && config(k))
+                .map(|(_, v)| v)
+                .or_else(|| part.get(Self::DEFAULT_CONDITION));
+            let value = match value {
+                Some(v) => v.to_value(),
+                None => {
+                    let conditions = part.keys().map(|x| format!("`{}`", x)).collect::<Vec<_>>();
+                    let conditions = conditions.join(", ");
+                    return Err(SelectError::NoMatch(conditions).into());
+                }
+            };
+            res = Some(match res {
+                None => value,
+                Some(res) => res.add(value, heap)?,
+            });
+        }
+        // Selects are never empty, so we always have a result
+        Ok(res.unwrap_or_else(Value::new_none))
+    }
+}
+
+impl<'v> Select<'v> {
+    /// Create a select from `select()`, mapping conditions to values.
+    pub(crate) fn new(conditions: SmallMap<String, Value<'v>>) -> anyhow::Result<Self> {
+        if conditions.is_empty() {
+            return Err(SelectError::Empty.into());
+        }
+        Ok(Self {
+            parts: vec![conditions],
+        })
+    }
+
+    /// The parts of a value being added to a select.
+    fn parts_of(x: Value<'v>) -> Vec<SmallMap<String, Value<'v>>> {
+        match Select::from_value(x) {
+            Some(x) => x.value_parts(),
+            None => {
+                let mut part = SmallMap::new();
+                part.insert(Self::DEFAULT_CONDITION.to_owned(), x);
+                vec![part]
+            }
+        }
+    }
+}
+
+impl<'v, V: ValueLike<'v>> Display for SelectGen<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Repr<'a>(&'a str);
+        impl Display for Repr<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut buffer = String::new();
+                self.0.collect_repr(&mut buffer);
+                f.write_str(&buffer)
+            }
+        }
+
+        for (i, part) in self.parts.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" + ")?;
+            }
+            display_keyed_container(
+                f,
+                "select({",
+                "})",
+                ": ",
+                part.iter().map(|(k, v)| (Repr(k), v)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'v, V: ValueLike<'v>> StarlarkValue<'v> for SelectGen<V>
+where
+    Self: AnyLifetime<'v>,
+{
+    starlark_type!(Select::TYPE);
+
+    fn collect_repr_cycle(&self, collector: &mut String) {
+        collector.push_str("select(...)");
+    }
+
+    fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        let mut parts = self.value_parts();
+        parts.extend(Select::parts_of(rhs));
+        Ok(heap.alloc(Select { parts }))
+    }
+
+    fn radd(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<anyhow::Result<Value<'v>>> {
+        let mut parts = Select::parts_of(lhs);
+        parts.extend(self.value_parts());
+        Some(Ok(heap.alloc(Select { parts })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert;
+
+    #[test]
+    fn test_select() {
+        assert::all_true(
+            r#"
+x = select({"a": 1}); type(x) == "select"
+x = select({"a": 1}); repr(x) == 'select({"a": 1})'
+x = [1] + select({"a": [2]}); repr(x) == 'select({"//conditions:default": [1]}) + select({"a": [2]})'
+x = select({"a": [2]}) + select({"b": [3]}); repr(x) == 'select({"a": [2]}) + select({"b": [3]})'
+"#,
+        );
+        assert::fail("select({})", "needs at least one condition");
+        assert::fail("select({1: 2})", "Type of parameter");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let heap = Heap::new();
+        let x = assert::pass(
+            r#"
+(["a"] +
+    select({"arm": ["b"], "x86": ["c"], "//conditions:default": []}) +
+    select({"debug": ["d"], "//conditions:default": ["e"]}) +
+    ["f"])
+"#,
+        );
+        let x = Select::from_value(x.value()).unwrap();
+        let resolve = |conditions: &[&str]| {
+            x.resolve(|c| conditions.contains(&c), &heap)
+                .unwrap()
+                .to_repr()
+        };
+        assert_eq!(resolve(&[]), r#"["a", "e", "f"]"#);
+        assert_eq!(resolve(&["x86"]), r#"["a", "c", "e", "f"]"#);
+        assert_eq!(resolve(&["arm", "debug"]), r#"["a", "b", "d", "f"]"#);
+        // The first condition which holds wins
+        assert_eq!(resolve(&["x86", "arm"]), r#"["a", "b", "e", "f"]"#);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let heap = Heap::new();
+        let x = assert::pass(r#"select({"a": 1, "b": 2})"#);
+        let x = Select::from_value(x.value()).unwrap();
+        assert_eq!(
+            x.resolve(|c| c == "b", &heap).unwrap().unpack_int(),
+            Some(2)
+        );
+        let err = x.resolve(|_| false, &heap).unwrap_err();
+        assert!(
+            err.to_string().contains("conditions are `a`, `b`"),
+            "{}",
+            err
+        );
+
+        let x = assert::pass(r#"select({"a": 1}) + select({"a": "x"})"#);
+        let x = Select::from_value(x.value()).unwrap();
+        assert!(x.resolve(|_| true, &heap).is_err());
+    }
+}
@@ -399,7 +399,7 @@ impl<'a> FormatParser<'a> {
 pub(crate) fn format<'v>(
     this: &str,
     args: impl Iterator<Item = Value<'v>>,
-    kwargs: Dict<'v>,
+    kwargs: &Dict<'v>,
     string_pool: &mut StringPool,
     heap: &'v Heap,
 ) -> anyhow::Result<StringValue<'v>> {
@@ -413,7 +413,7 @@ pub(crate) fn format<'v>(
         match token {
             FormatToken::Text(text) => result.push_str(text),
             FormatToken::Capture(capture) => {
-                format_capture(capture, &mut args, &kwargs, &mut result)?
+                format_capture(capture, &mut args, kwargs, &mut result)?
             }
         }
     }
@@ -37,7 +37,7 @@ use crate::{
     values::{
         index::apply_slice,
         string::repr::string_repr,
-        types::{none::NoneOr, string::fast_string::StrIndices},
+        types::{none::NoneOr, string::fast_string::StrIndices, type_value::TypeValue},
         Heap, StarlarkValue, UnpackValue, Value, ValueError,
     },
 };
@@ -216,6 +216,8 @@ impl<'v> StarlarkValue<'v> for str {
     fn equals(&self, other: Value) -> anyhow::Result<bool> {
         if let Some(other) = other.unpack_str() {
             Ok(self == other)
+        } else if let Some(other) = other.downcast_ref::<TypeValue>() {
+            Ok(self == other.name())
         } else {
             Ok(false)
         }
@@ -224,6 +226,8 @@ impl<'v> StarlarkValue<'v> for str {
     fn compare(&self, other: Value) -> anyhow::Result<Ordering> {
         if let Some(other) = other.unpack_str() {
             Ok(self.cmp(other))
+        } else if let Some(other) = other.downcast_ref::<TypeValue>() {
+            Ok(self.cmp(other.name()))
         } else {
             ValueError::unsupported_with(self, "cmp()", other)
         }
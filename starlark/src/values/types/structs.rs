@@ -37,7 +37,7 @@
 use std::{
     cmp::Ordering,
     fmt::{self, Display},
-    hash::Hash,
+    hash::{Hash, Hasher},
     marker,
     marker::PhantomData,
 };
@@ -176,10 +176,17 @@ where
     }
 
     fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        // Equality ignores the order of fields, so the hash must too.
+        // Hash each field separately and combine them with a commutative operation,
+        // so the result only depends on the field names and values.
+        let mut res = 0u64;
         for (k, v) in self.fields.iter_hashed() {
-            Hash::hash(&k, hasher);
-            v.write_hash(hasher)?;
+            let mut field_hasher = StarlarkHasher::new();
+            Hash::hash(&k, &mut field_hasher);
+            v.write_hash(&mut field_hasher)?;
+            res = res.wrapping_add(field_hasher.finish());
         }
+        hasher.write_u64(res);
         Ok(())
     }
 
@@ -296,6 +303,17 @@ mod tests {
         },
     };
 
+    #[test]
+    fn test_hash() {
+        // Built on different heaps, with the fields in a different order
+        let x = assert::pass("struct(a = 1, b = ('x', struct(c = None)))");
+        let y = assert::pass("struct(b = ('x', struct(c = None)), a = 1)");
+        assert_eq!(x.value().get_hash().unwrap(), y.value().get_hash().unwrap());
+        let z = assert::pass("struct(a = 1, b = ('y', struct(c = None)))");
+        assert_ne!(x.value().get_hash().unwrap(), z.value().get_hash().unwrap());
+        assert::is_true("{struct(a = 1, b = 'x'): True}[struct(b = 'x', a = 1)]");
+    }
+
     #[test]
     fn test_repr() {
         assert::eq("repr(struct(a=1, b=[]))", "'struct(a=1, b=[])'");
@@ -24,6 +24,7 @@ use std::{
     slice,
 };
 
+use anyhow::Context;
 use gazebo::{
     any::AnyLifetime,
     coerce::{coerce, coerce_ref, Coerce},
@@ -33,7 +34,8 @@ use serde::{ser::SerializeTuple, Serialize};
 use crate::{
     collections::StarlarkHasher,
     values::{
-        comparison::{compare_slice, equals_slice},
+        comparison::{compare_elements, equals_slice},
+        error::ControlError,
         index::{apply_slice, convert_index},
         AllocValue, FrozenValue, Heap, StarlarkValue, UnpackValue, Value, ValueError, ValueLike,
     },
@@ -146,8 +148,10 @@ where
     }
 
     fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
-        for v in self.content() {
-            v.write_hash(hasher)?;
+        for (i, v) in self.content().iter().enumerate() {
+            v.write_hash(hasher).with_context(|| {
+                ControlError::NotHashableTupleElement(i, v.to_value().get_type().to_owned())
+            })?;
         }
         Ok(())
     }
@@ -162,7 +166,7 @@ where
     fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
         match Tuple::from_value(other) {
             None => ValueError::unsupported_with(self, "cmp()", other),
-            Some(other) => compare_slice(self.content(), other.content(), |x, y| x.compare(*y)),
+            Some(other) => compare_elements(self.content(), other.content(), false),
         }
     }
 
@@ -175,6 +179,10 @@ where
         Ok(self.len() as i32)
     }
 
+    fn length_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
     fn is_in(&self, other: Value<'v>) -> anyhow::Result<bool> {
         for x in self.content() {
             if x.equals(other)? {
@@ -299,7 +307,13 @@ impl<'v, T1: UnpackValue<'v>, T2: UnpackValue<'v>> UnpackValue<'v> for (T1, T2)
 
 #[cfg(test)]
 mod tests {
-    use crate::assert;
+    use std::hash::Hasher;
+
+    use crate::{
+        assert,
+        collections::StarlarkHasher,
+        values::{Heap, Value, ValueLike},
+    };
 
     #[test]
     fn test_to_str() {
@@ -312,6 +326,50 @@ str((1,)) == "(1,)"
         );
     }
 
+    #[test]
+    fn test_str_repr_elements() {
+        // `str` only differs from `repr` at the top level, elements always use `repr`.
+        assert::all_true(
+            r#"
+str("a") == "a"
+repr("a") == '"a"'
+str(("a",)) == '("a",)'
+repr(("a",)) == '("a",)'
+str(("a", ["b"], {"c": "d"})) == '("a", ["b"], {"c": "d"})'
+"#,
+        );
+    }
+
+    #[test]
+    fn test_write_hash() {
+        let heap = Heap::new();
+        let hash = |xs: &[Value]| {
+            let mut hasher = StarlarkHasher::new();
+            for x in xs {
+                x.write_hash(&mut hasher).unwrap();
+            }
+            hasher.finish()
+        };
+        let one = Value::new_int(1);
+        let two = Value::new_int(2);
+        let x = heap.alloc_tuple(&[one, two]);
+        let y = heap.alloc_tuple(&[two, one]);
+        assert_ne!(hash(&[x]), hash(&[y]));
+        assert_eq!(hash(&[x]), hash(&[heap.alloc_tuple(&[one, two])]));
+        // A tuple feeds its elements into the hasher in turn, as a caller streaming them would
+        assert_eq!(hash(&[x]), hash(&[one, two]));
+        assert_ne!(hash(&[one, two]), hash(&[two, one]));
+    }
+
+    #[test]
+    fn test_hash_unhashable_element() {
+        assert::fails(
+            "{(1, [2]): 3}",
+            &["index 1", "`list`", "unhashable type: list"],
+        );
+        assert::fails("{(1, (2, [3])): 4}", &["index 1", "index 0", "`list`"]);
+    }
+
     #[test]
     fn test_repr_cycle() {
         assert::eq("l = []; t = (l,); l.append(t); repr(t)", "'([(...)],)'");
@@ -0,0 +1,183 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The type objects returned by `type()`.
+//!
+//! A type object prints as the name of the type, and compares equal both to other
+//! type objects with the same name and to that name as a string, so code written
+//! when `type()` returned a string keeps working. Type objects are not strings, so
+//! string methods (e.g. `type(x).startswith("i")`) and `+` no longer work on them,
+//! use `str(type(x))` for those.
+//!
+//! ```
+//! # starlark::assert::all_true(r#"
+//! type(1) == type(2)
+//! type(1) != type("a")
+//! type(1) == "int"
+//! str(type([])) == "list"
+//! # "#);
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
+    sync::RwLock,
+};
+
+use gazebo::{any::AnyLifetime, prelude::*};
+use once_cell::sync::Lazy;
+use serde::{Serialize, Serializer};
+
+use crate as starlark;
+use crate::{
+    collections::StarlarkHasher,
+    values::{
+        string::hash_string_value, FrozenStringValue, FrozenValue, OwnedFrozenValue, StarlarkValue,
+        Value, ValueError, ValueLike,
+    },
+};
+
+/// The result of calling `type()`, identifying a type by its name.
+#[derive(Debug, Clone, Copy, Dupe, AnyLifetime)]
+pub struct TypeValue(FrozenStringValue);
+
+starlark_simple_value!(TypeValue);
+
+impl TypeValue {
+    /// The result of calling `type()` on a type.
+    pub const TYPE: &'static str = "type";
+
+    /// The type of a value.
+    pub fn of(x: Value) -> Self {
+        Self(x.get_type_value())
+    }
+
+    /// The type object for a type name, shared by every `type()` call which returns it,
+    /// so calling `type()` does not allocate.
+    pub(crate) fn frozen(name: FrozenStringValue) -> FrozenValue {
+        // Type names are static strings, so there are only as many entries as types.
+        static TYPES: Lazy<RwLock<HashMap<&'static str, OwnedFrozenValue>>> =
+            Lazy::new(Default::default);
+
+        let name_str = name.as_str();
+        if let Some(v) = TYPES.read().unwrap().get(name_str) {
+            // Entries are never removed, so the owning heap lives forever.
+            return unsafe { v.unchecked_frozen_value() };
+        }
+        let mut types = TYPES.write().unwrap();
+        let v = types
+            .entry(name_str)
+            .or_insert_with(|| OwnedFrozenValue::alloc(TypeValue(name)));
+        unsafe { v.unchecked_frozen_value() }
+    }
+
+    /// The name of the type, as returned by [`Value::get_type`].
+    pub fn name(&self) -> &'static str {
+        self.0.as_str()
+    }
+
+    pub(crate) fn name_value(&self) -> FrozenStringValue {
+        self.0
+    }
+
+    /// The type name given by either a type object or a string.
+    pub fn unpack_name<'v>(x: Value<'v>) -> Option<&'v str> {
+        match x.downcast_ref::<TypeValue>() {
+            Some(t) => Some(t.name()),
+            None => x.unpack_str(),
+        }
+    }
+}
+
+impl Display for TypeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'v> StarlarkValue<'v> for TypeValue {
+    starlark_type!(TypeValue::TYPE);
+
+    fn equals(&self, other: Value<'v>) -> anyhow::Result<bool> {
+        Ok(Self::unpack_name(other) == Some(self.name()))
+    }
+
+    fn compare(&self, other: Value<'v>) -> anyhow::Result<Ordering> {
+        match Self::unpack_name(other) {
+            Some(other) => Ok(self.name().cmp(other)),
+            None => ValueError::unsupported_with(self, "cmp()", other),
+        }
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> anyhow::Result<()> {
+        // Hash the same as the name, since we are equal to it.
+        hash_string_value(self.name(), hasher);
+        Ok(())
+    }
+}
+
+impl Serialize for TypeValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assert,
+        values::{types::type_value::TypeValue, Heap},
+    };
+
+    #[test]
+    fn test_type_value() {
+        assert::all_true(
+            r#"
+type(1) == type(2)
+type(1) != type("a")
+type(type(1)) == "type"
+str(type(1)) == "int"
+repr(type(1)) == "int"
+"int" == type(1)
+type(1) in ["float", "int"]
+{type(1): True}["int"]
+{"int": True}[type(1)]
+sorted([type("a"), type(1)]) == ["int", "string"]
+"a" < type(1)
+type(1) < "string"
+sorted(["string", type(1), "float"]) == ["float", "int", "string"]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_type_value_shared() {
+        let heap = Heap::new();
+        let int = TypeValue::frozen(heap.alloc(1).get_type_value()).to_value();
+        assert!(int.ptr_eq(TypeValue::frozen(heap.alloc(2).get_type_value()).to_value()));
+        assert_eq!(0, heap.allocated_bytes());
+        // Both the compiled `type()` and the `type` function return the shared value.
+        for program in ["x = 1\ntype(x)", "t = type\nt(1)"] {
+            let res = assert::pass(program);
+            assert!(int.ptr_eq(res.value()));
+        }
+    }
+}
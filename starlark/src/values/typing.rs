@@ -26,6 +26,7 @@ use crate::{
         dict::{Dict, DictRef},
         list::{List, ListRef},
         tuple::Tuple,
+        type_value::TypeValue,
         Heap, Trace, Tracer, Value,
     },
 };
@@ -224,7 +225,7 @@ impl TypeCompiled {
     }
 
     pub(crate) fn new<'h>(ty: Value<'h>, heap: &'h Heap) -> anyhow::Result<Self> {
-        if let Some(s) = ty.unpack_str() {
+        if let Some(s) = TypeValue::unpack_name(ty) {
             Ok(TypeCompiled::from_str(s))
         } else if ty.is_none() {
             Ok(TypeCompiled::is_none())
@@ -303,7 +304,11 @@ impl<'v> Value<'v> {
 
 #[cfg(test)]
 mod tests {
-    use crate::assert;
+    use derive_more::Display;
+    use gazebo::any::AnyLifetime;
+
+    use crate as starlark;
+    use crate::{assert, environment::GlobalsBuilder, values::StarlarkValue};
 
     #[test]
     fn test_types() {
@@ -401,4 +406,60 @@ def foo(f: int.type = None):
             "`None` of type `NoneType` does not match the type annotation `int`",
         );
     }
+
+    #[test]
+    fn test_custom_types() {
+        #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+        #[display(fmt = "foo")]
+        struct Foo;
+        starlark_simple_value!(Foo);
+        impl<'v> StarlarkValue<'v> for Foo {
+            starlark_type!("foo");
+        }
+
+        #[derive(Debug, Display, AnyLifetime, NoSerialize)]
+        #[display(fmt = "bar")]
+        struct Bar;
+        starlark_simple_value!(Bar);
+        impl<'v> StarlarkValue<'v> for Bar {
+            starlark_type!("bar");
+        }
+
+        #[starlark_module]
+        fn globals(builder: &mut GlobalsBuilder) {
+            #[starlark(type("foo"))]
+            fn new_foo() -> anyhow::Result<Foo> {
+                Ok(Foo)
+            }
+
+            #[starlark(type("bar"))]
+            fn new_bar() -> anyhow::Result<Bar> {
+                Ok(Bar)
+            }
+        }
+
+        let mut a = assert::Assert::new();
+        a.globals_add(globals);
+        a.pass(
+            r#"
+def f(x: new_foo.type) -> new_foo.type:
+    return x
+def g(x: [new_foo.type, int.type]):
+    return x
+assert_eq(new_foo.type, "foo")
+f(new_foo())
+g(new_foo())
+g(1)
+assert_true(is_type(new_bar(), [new_foo.type, new_bar.type]))
+"#,
+        );
+        a.fails(
+            "def f(x: new_foo.type):\n pass\nf(new_bar())",
+            &["type annotation", "`bar`", "`foo`", "`x`"],
+        );
+        a.fails(
+            "def g(x: [new_foo.type, int.type]):\n pass\ng(new_bar())",
+            &["type annotation", "`bar`", "`x`"],
+        );
+    }
 }
@@ -32,6 +32,7 @@ mod attrs;
 mod bc;
 mod freeze;
 mod parse;
+mod record;
 mod render;
 mod serde;
 mod trace;
@@ -83,9 +84,32 @@ mod util;
 ///   no global side effects, should not panic, and should finish in reasonable time.
 ///   The evaluator may invoke such functions early to generate more efficient code.
 ///
+/// A module of methods can have type parameters, to share methods between several types.
+/// Each type's `get_methods` then uses its own instantiation, e.g. `shape_methods::<Square>`,
+/// and a method taking `this: &T`, for a type parameter `T`, receives the value downcast to `T`:
+///
+/// ```ignore
+/// #[starlark_module]
+/// fn shape_methods<T: Shape>(builder: &mut MethodsBuilder) {
+///     fn area(this: &T) -> anyhow::Result<i32> {
+///         Ok(this.area())
+///     }
+/// }
+/// ```
+///
 /// All these functions interoperate properly with `dir()`, `getattr()` and `hasattr()`.
 ///
 /// If a desired function name is also a Rust keyword, use the `r#` prefix, e.g. `r#type`.
+/// To register a function or parameter under any other name, such as `self` which can't be
+/// written with `r#`, use `#[starlark(rename = "name")]`, which is used as given,
+/// without trimming underscores:
+///
+/// ```ignore
+/// #[starlark(rename = "self")]
+/// fn self_(#[starlark(rename = "type")] typ: &str) -> anyhow::Result<String> {
+///     Ok(typ.to_owned())
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn starlark_module(attr: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as ItemFn);
@@ -112,7 +136,8 @@ pub fn starlark_internal_bc(
     bc::starlark_internal_bc(attr, input)
 }
 
-/// Derive the `Trace` trait.
+/// Derive the `Trace` trait, tracing every field not marked `#[trace(skip)]`
+/// or `#[trace(unsafe_ignore)]`.
 #[proc_macro_derive(Trace, attributes(trace))]
 pub fn derive_trace(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     trace::derive_trace(input)
@@ -124,6 +149,20 @@ pub fn derive_freeze(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     freeze::derive_freeze(input)
 }
 
+/// Derive `AllocValue` for a struct with named fields, allocating it as a dict
+/// from each field name to the field value. Every field must implement `AllocValue`.
+#[proc_macro_derive(AllocValue)]
+pub fn derive_alloc_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    record::derive_alloc_value(input)
+}
+
+/// Derive `UnpackValue` for a struct with named fields, unpacking it from a dict
+/// or a struct with exactly those fields. Every field must implement `UnpackValue`.
+#[proc_macro_derive(UnpackValue)]
+pub fn derive_unpack_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    record::derive_unpack_value(input)
+}
+
 /// Derive the `NoSerialize` trait for serde.
 #[proc_macro_derive(NoSerialize)]
 pub fn derive_no_serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -18,7 +18,7 @@
 use gazebo::prelude::*;
 use proc_macro2::Span;
 use syn::{
-    spanned::Spanned, Attribute, FnArg, GenericArgument, Item, ItemConst, ItemFn, Meta,
+    spanned::Spanned, Attribute, FnArg, GenericArgument, Item, ItemConst, ItemFn, Lit, Meta,
     MetaNameValue, NestedMeta, Pat, PatType, PathArguments, ReturnType, Stmt, Type, TypeReference,
 };
 
@@ -44,6 +44,7 @@ pub(crate) fn parse(mut input: ItemFn) -> syn::Result<StarModule> {
     let visibility = input.vis;
     let sig_span = input.sig.span();
     let name = input.sig.ident;
+    let generics = input.sig.generics;
 
     if input.sig.inputs.len() != 1 {
         return Err(syn::Error::new(
@@ -68,11 +69,26 @@ pub(crate) fn parse(mut input: ItemFn) -> syn::Result<StarModule> {
             ));
         }
     };
+    if !generics.params.is_empty() {
+        if module_kind != ModuleKind::Methods {
+            return Err(syn::Error::new(
+                generics.span(),
+                "Only a methods module can have type parameters",
+            ));
+        }
+        if generics.lifetimes().next().is_some() || generics.const_params().next().is_some() {
+            return Err(syn::Error::new(
+                generics.span(),
+                "A methods module can only have type parameters",
+            ));
+        }
+    }
     Ok(StarModule {
         module_kind,
         visibility,
         globals_builder: *ty,
         name,
+        generics,
         docstring: module_docstring,
         stmts: input.block.stmts.into_try_map(parse_stmt)?,
     })
@@ -115,6 +131,7 @@ struct ProcessedAttributes {
     is_attribute: bool,
     type_attribute: Option<NestedMeta>,
     speculative_exec_safe: bool,
+    rename: Option<String>,
     docstring: Option<String>,
     /// Rest attributes
     attrs: Vec<Attribute>,
@@ -137,12 +154,14 @@ fn is_attribute_docstring(x: &Attribute) -> Option<String> {
 fn process_attributes(span: Span, xs: Vec<Attribute>) -> syn::Result<ProcessedAttributes> {
     const ERROR: &str = "Couldn't parse attribute. \
         Expected `#[starlark(type(\"ty\")]`, \
-        `#[starlark(attribute)]` or `#[starlark(speculative_exec_safe)]`";
+        `#[starlark(attribute)]`, `#[starlark(speculative_exec_safe)]` \
+        or `#[starlark(rename = \"name\")]`";
 
     let mut attrs = Vec::with_capacity(xs.len());
     let mut is_attribute = false;
     let mut type_attribute = None;
     let mut speculative_exec_safe = false;
+    let mut rename = None;
     let mut doc_attrs = Vec::new();
     for x in xs {
         if x.path.is_ident("starlark") {
@@ -170,6 +189,8 @@ fn process_attributes(span: Span, xs: Vec<Attribute>) -> syn::Result<ProcessedAt
                                     is_attribute = true;
                                 } else if meta.path().is_ident("speculative_exec_safe") {
                                     speculative_exec_safe = true;
+                                } else if meta.path().is_ident("rename") {
+                                    rename = Some(parse_rename(meta, ERROR)?);
                                 } else {
                                     return Err(syn::Error::new(meta.span(), ERROR));
                                 }
@@ -200,11 +221,51 @@ fn process_attributes(span: Span, xs: Vec<Attribute>) -> syn::Result<ProcessedAt
         is_attribute,
         type_attribute,
         speculative_exec_safe,
+        rename,
         docstring,
         attrs,
     })
 }
 
+/// Parse the `rename = "name"` in a `#[starlark(...)]` attribute.
+fn parse_rename(meta: Meta, error: &str) -> syn::Result<String> {
+    match meta {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(s), ..
+        }) => Ok(s.value()),
+        _ => Err(syn::Error::new(meta.span(), error)),
+    }
+}
+
+/// Parse `#[starlark(rename = "name")]` on a parameter, returning the other attributes.
+fn process_arg_attributes(xs: Vec<Attribute>) -> syn::Result<(Vec<Attribute>, Option<String>)> {
+    const ERROR: &str = "Couldn't parse attribute. \
+        Expected `#[starlark(rename = \"name\")]`";
+
+    let mut attrs = Vec::with_capacity(xs.len());
+    let mut rename = None;
+    for x in xs {
+        if x.path.is_ident("starlark") {
+            match x.parse_meta()? {
+                Meta::List(list) => {
+                    for nested in list.nested {
+                        match nested {
+                            NestedMeta::Meta(meta) if meta.path().is_ident("rename") => {
+                                rename = Some(parse_rename(meta, ERROR)?);
+                            }
+                            nested => return Err(syn::Error::new(nested.span(), ERROR)),
+                        }
+                    }
+                }
+                _ => return Err(syn::Error::new(x.span(), ERROR)),
+            }
+        } else {
+            attrs.push(x);
+        }
+    }
+    Ok((attrs, rename))
+}
+
 /// Check if given type is `anyhow::Result<T>`, and if it is, return `T`.
 fn is_anyhow_result(t: &Type) -> Option<Type> {
     let path = match t {
@@ -252,6 +313,7 @@ fn parse_fun(func: ItemFn) -> syn::Result<StarStmt> {
         is_attribute,
         type_attribute,
         speculative_exec_safe,
+        rename,
         docstring,
         attrs,
     } = process_attributes(func.span(), func.attrs)?;
@@ -299,6 +361,7 @@ fn parse_fun(func: ItemFn) -> syn::Result<StarStmt> {
         }
         Ok(StarStmt::Attr(StarAttr {
             name: func.sig.ident,
+            rename,
             arg: arg.ty,
             attrs,
             return_type: *return_type,
@@ -310,6 +373,7 @@ fn parse_fun(func: ItemFn) -> syn::Result<StarStmt> {
     } else {
         Ok(StarStmt::Fun(StarFun {
             name: func.sig.ident,
+            rename,
             type_attribute,
             attrs,
             args,
@@ -331,16 +395,20 @@ fn parse_arg(x: FnArg) -> syn::Result<StarArg> {
             pat: box Pat::Ident(ident),
             ty: box ty,
             ..
-        }) => Ok(StarArg {
-            span,
-            attrs,
-            mutable: ident.mutability.is_some(),
-            name: ident.ident,
-            by_ref: ident.by_ref.is_some(),
-            ty,
-            default: ident.subpat.map(|x| *x.1),
-            source: StarArgSource::Unknown,
-        }),
+        }) => {
+            let (attrs, rename) = process_arg_attributes(attrs)?;
+            Ok(StarArg {
+                span,
+                attrs,
+                mutable: ident.mutability.is_some(),
+                name: ident.ident,
+                rename,
+                by_ref: ident.by_ref.is_some(),
+                ty,
+                default: ident.subpat.map(|x| *x.1),
+                source: StarArgSource::Unknown,
+            })
+        }
         arg => panic!("Unexpected argument, {:?}", arg),
     }
 }
@@ -0,0 +1,139 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Derive `AllocValue` and `UnpackValue` for structs with named fields,
+//! converting to and from a dict keyed by the field names.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields,
+    FieldsNamed, GenericParam, Lifetime, LifetimeDef, TypeParamBound,
+};
+
+/// Add a `'v` lifetime if the type doesn't have one, and bound every type parameter.
+fn add_generics(input: &mut DeriveInput, bound: TypeParamBound) -> syn::Generics {
+    let tick_v = GenericParam::Lifetime(LifetimeDef::new(Lifetime::new("'v", Span::call_site())));
+    let mut has_tick_v = false;
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(type_param) = param {
+            type_param.bounds.push(bound.clone());
+        }
+        if let GenericParam::Lifetime(t) = param {
+            if t.lifetime.ident == "v" {
+                has_tick_v = true;
+            }
+        }
+    }
+    let mut generics = input.generics.clone();
+    if !has_tick_v {
+        generics.params.insert(0, tick_v);
+    }
+    generics
+}
+
+fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<&'a FieldsNamed> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(Error::new_spanned(
+                &input.ident,
+                format!("Can only derive {} for structs with named fields", derive),
+            )),
+        },
+        _ => Err(Error::new_spanned(
+            &input.ident,
+            format!("Can only derive {} for structs", derive),
+        )),
+    }
+}
+
+fn derive_alloc_value_impl(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let generics = add_generics(&mut input, parse_quote!(starlark::values::AllocValue<'v>));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let name = &input.ident;
+
+    let fields = named_fields(&input, "AllocValue")?;
+    let xs = fields.named.iter().map(|f| {
+        let field = f.ident.as_ref().unwrap();
+        let key = field.to_string();
+        quote_spanned! {f.span() =>
+            (#key, starlark::values::AllocValue::alloc_value(self.#field, heap)),
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics starlark::values::AllocValue<'v> for #name #ty_generics #where_clause {
+            fn alloc_value(self, heap: &'v starlark::values::Heap) -> starlark::values::Value<'v> {
+                starlark::__derive_refs::record::alloc_record(heap, vec![#(#xs)*])
+            }
+        }
+    })
+}
+
+fn derive_unpack_value_impl(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let generics = add_generics(&mut input, parse_quote!(starlark::values::UnpackValue<'v>));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let name = &input.ident;
+
+    let fields = named_fields(&input, "UnpackValue")?;
+    let keys: Vec<String> = fields
+        .named
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect();
+    let expected = format!("dict or struct with fields {}", keys.join(", "));
+    let xs = fields.named.iter().enumerate().map(|(i, f)| {
+        let field = &f.ident;
+        quote_spanned! {f.span() =>
+            #field: starlark::values::UnpackValue::unpack_value(values[#i])?,
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics starlark::values::UnpackValue<'v> for #name #ty_generics #where_clause {
+            fn expected() -> String {
+                #expected.to_owned()
+            }
+
+            fn unpack_value(value: starlark::values::Value<'v>) -> Option<Self> {
+                let values = starlark::__derive_refs::record::unpack_record(value, &[#(#keys),*])?;
+                Some(#name {
+                    #(#xs)*
+                })
+            }
+        }
+    })
+}
+
+pub fn derive_alloc_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_alloc_value_impl(input) {
+        Ok(s) => s.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+pub fn derive_unpack_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_unpack_value_impl(input) {
+        Ok(s) => s.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -16,45 +16,104 @@
  */
 
 use gazebo::prelude::*;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote_spanned};
+use syn::Generics;
 
 use crate::{typ::*, util::*};
 
+/// The type parameters of a generic methods module. Every generated function repeats them,
+/// since a nested function can't use the generics of the function containing it.
+struct ModuleGenerics<'a> {
+    generics: &'a Generics,
+    /// `T: Bound, ...`, to go in a generated function's parameters.
+    params: TokenStream,
+    /// `::<T, ...>`, to refer to a generated function, or nothing.
+    turbofish: TokenStream,
+    /// `where ...`, including that each `T` is a `StarlarkValue`, or nothing.
+    where_clause: TokenStream,
+}
+
+impl<'a> ModuleGenerics<'a> {
+    fn new(generics: &'a Generics, span: Span) -> Self {
+        if generics.params.is_empty() {
+            return Self {
+                generics,
+                params: quote_spanned!(span=>),
+                turbofish: quote_spanned!(span=>),
+                where_clause: quote_spanned!(span=>),
+            };
+        }
+        let params = generics.type_params();
+        let names: Vec<_> = generics.type_params().map(|t| &t.ident).collect();
+        let predicates = generics
+            .where_clause
+            .iter()
+            .flat_map(|w| w.predicates.iter());
+        Self {
+            generics,
+            params: quote_spanned!(span=> #(#params,)*),
+            turbofish: quote_spanned!(span=> ::<#(#names),*>),
+            where_clause: quote_spanned! {span=>
+                where
+                    #(#names: for<'__v> starlark::values::StarlarkValue<'__v> + 'static,)*
+                    #(#predicates,)*
+            },
+        }
+    }
+}
+
 pub(crate) fn render(x: StarModule) -> TokenStream {
     let span = x.span();
     let StarModule {
         name,
         globals_builder,
         visibility,
+        generics,
         docstring,
         stmts,
         module_kind,
     } = x;
+    let generics = ModuleGenerics::new(&generics, span);
     let statics = format_ident!("{}", module_kind.statics_type_name());
-    let stmts = stmts.into_map(render_stmt);
+    let stmts = stmts.into_map(|x| render_stmt(x, &generics));
     let set_docstring =
         docstring.map(|ds| quote_spanned!(span=> globals_builder.set_docstring(#ds);));
+    let ModuleGenerics {
+        params,
+        turbofish,
+        where_clause,
+        ..
+    } = &generics;
+    // A static in a generic function is shared by every instantiation,
+    // so we can't cache the result for a generic module.
+    let populate = if generics.generics.params.is_empty() {
+        quote_spanned! {span=>
+            static RES: starlark::environment::#statics = starlark::environment::#statics::new();
+            RES.populate(build, globals_builder);
+        }
+    } else {
+        quote_spanned!(span=> build #turbofish (globals_builder);)
+    };
     quote_spanned! {
         span=>
-        #visibility fn #name(globals_builder: #globals_builder) {
-            fn build(globals_builder: #globals_builder) {
+        #visibility fn #name<#params>(globals_builder: #globals_builder) #where_clause {
+            fn build<#params>(globals_builder: #globals_builder) #where_clause {
                 #set_docstring
                 #( #stmts )*
                 // Mute warning if stmts is empty.
                 let _ = globals_builder;
             }
-            static RES: starlark::environment::#statics = starlark::environment::#statics::new();
-            RES.populate(build, globals_builder);
+            #populate
         }
     }
 }
 
-fn render_stmt(x: StarStmt) -> TokenStream {
+fn render_stmt(x: StarStmt, generics: &ModuleGenerics) -> TokenStream {
     match x {
         StarStmt::Const(x) => render_const(x),
-        StarStmt::Attr(x) => render_attr(x),
-        StarStmt::Fun(x) => render_fun(x),
+        StarStmt::Attr(x) => render_attr(x, generics),
+        StarStmt::Fun(x) => render_fun(x, generics),
     }
 }
 
@@ -68,10 +127,36 @@ fn render_const(x: StarConst) -> TokenStream {
     }
 }
 
-fn render_attr(x: StarAttr) -> TokenStream {
+fn render_attr(x: StarAttr, generics: &ModuleGenerics) -> TokenStream {
     let span = x.span();
+    let name_str = x.name_str();
+    let ModuleGenerics {
+        params,
+        turbofish,
+        where_clause,
+        ..
+    } = generics;
+    let unpack_this = match this_type_param(&x.arg, generics.generics) {
+        Some(t) => quote_spanned! {span=>
+            starlark::eval::Arguments::check_this_type::<#t>(this)?
+        },
+        None => {
+            let arg = &x.arg;
+            quote_spanned! {span=>
+                match starlark::values::UnpackValue::unpack_value(this) {
+                    None => return Err(starlark::values::ValueError::IncorrectParameterTypeNamedWithExpected(
+                        "this".to_owned(),
+                        <#arg as starlark::values::UnpackValue>::expected(),
+                        this.get_type().to_owned(),
+                    ).into()),
+                    Some(v) => v,
+                }
+            }
+        }
+    };
     let StarAttr {
         name,
+        rename: _,
         arg,
         attrs,
         return_type,
@@ -80,7 +165,6 @@ fn render_attr(x: StarAttr) -> TokenStream {
         body,
         docstring,
     } = x;
-    let name_str = ident_string(&name);
     let docstring = match docstring {
         Some(d) => quote_spanned!(span=> Some(#d.to_owned())),
         None => quote_spanned!(span=> None),
@@ -89,44 +173,44 @@ fn render_attr(x: StarAttr) -> TokenStream {
         span=>
         #( #attrs )*
         #[allow(non_snake_case)] // Starlark doesn't have this convention
-        fn #name<'v>(
+        fn #name<'v, #params>(
             #[allow(unused_variables)]
             this: starlark::values::Value<'v>,
             heap: &'v starlark::values::Heap,
-        ) -> anyhow::Result<starlark::values::Value<'v>> {
-             fn inner<'v>(
+        ) -> anyhow::Result<starlark::values::Value<'v>> #where_clause {
+             fn inner<'v, #params>(
                 this: starlark::values::Value<'v>,
                 #[allow(unused_variables)]
                 heap: &'v starlark::values::Heap,
-            ) -> #return_type {
+            ) -> #return_type #where_clause {
                 #[allow(unused_variables)]
-                let this: #arg = match starlark::values::UnpackValue::unpack_value(this) {
-                    None => return Err(starlark::values::ValueError::IncorrectParameterTypeNamedWithExpected(
-                        "this".to_owned(),
-                        <#arg as starlark::values::UnpackValue>::expected(),
-                        this.get_type().to_owned(),
-                    ).into()),
-                    Some(v) => v,
-                };
+                let this: #arg = #unpack_this;
                 #body
             }
-            Ok(heap.alloc(inner(this, heap)?))
+            Ok(heap.alloc(inner #turbofish (this, heap)?))
         }
-        globals_builder.set_attribute_fn(#name_str, #speculative_exec_safe, #docstring, stringify!(#return_type_arg).to_owned(), #name);
+        globals_builder.set_attribute_fn(#name_str, #speculative_exec_safe, #docstring, stringify!(#return_type_arg).to_owned(), #name #turbofish);
     }
 }
 
-fn render_fun(x: StarFun) -> TokenStream {
+fn render_fun(x: StarFun, generics: &ModuleGenerics) -> TokenStream {
     let span = x.span();
 
-    let name_str = ident_string(&x.name);
+    let name_str = x.name_str();
     let signature = render_signature(&x);
     let documentation = render_documentation(&x);
-    let binding = render_binding(&x);
+    let binding = render_binding(&x, generics.generics);
     let is_method = x.is_method();
+    let ModuleGenerics {
+        params,
+        turbofish,
+        where_clause,
+        ..
+    } = generics;
 
     let StarFun {
         name,
+        rename: _,
         type_attribute,
         attrs,
         args: _,
@@ -177,7 +261,7 @@ fn render_fun(x: StarFun) -> TokenStream {
                     #speculative_exec_safe,
                     __documentation_renderer,
                     #typ,
-                    move |eval, __this, parameters| {#name(eval, __this, parameters, #signature_val_ref)},
+                    move |eval, __this, parameters| {#name #turbofish (eval, __this, parameters, #signature_val_ref)},
                 );
             },
         )
@@ -192,7 +276,7 @@ fn render_fun(x: StarFun) -> TokenStream {
                     #speculative_exec_safe,
                     __documentation_renderer,
                     #typ,
-                    move |eval, parameters| {#name(eval, parameters, #signature_val_ref)},
+                    move |eval, parameters| {#name #turbofish (eval, parameters, #signature_val_ref)},
                 );
             },
         )
@@ -202,25 +286,25 @@ fn render_fun(x: StarFun) -> TokenStream {
         span=>
         #( #attrs )*
         #[allow(non_snake_case)] // Starlark doesn't have this convention
-        fn #name<'v>(
+        fn #name<'v, #params>(
             eval: &mut starlark::eval::Evaluator<'v, '_>,
             #this_param
             parameters: &starlark::eval::Arguments<'v, '_>,
             #signature_arg
-        ) -> anyhow::Result<starlark::values::Value<'v>> {
-            fn inner<'v>(
+        ) -> anyhow::Result<starlark::values::Value<'v>> #where_clause {
+            fn inner<'v, #params>(
                 #[allow(unused_variables)]
                 eval: &mut starlark::eval::Evaluator<'v, '_>,
                 #this_param
                 __args: &starlark::eval::Arguments<'v, '_>,
                 #signature_arg
-            ) -> #return_type {
+            ) -> #return_type #where_clause {
                 #[allow(unused_variables)]
                 let heap = eval.heap();
                 #binding
                 #body
             }
-            match inner(eval, #this_arg parameters, #signature_val) {
+            match inner #turbofish (eval, #this_arg parameters, #signature_val) {
                 Ok(v) => Ok(eval.heap().alloc(v)),
                 Err(e) => Err(e),
             }
@@ -235,7 +319,7 @@ fn render_fun(x: StarFun) -> TokenStream {
 
 // Given __args and __signature (if render_signature was Some)
 // create bindings for all the arguments
-fn render_binding(x: &StarFun) -> TokenStream {
+fn render_binding(x: &StarFun, generics: &Generics) -> TokenStream {
     let span = x.args_span();
     match x.source {
         StarFunSource::Parameters => {
@@ -258,7 +342,7 @@ fn render_binding(x: &StarFun) -> TokenStream {
                 ..
             } = &x.args[1];
             let span = *span;
-            let this = render_binding_arg(&x.args[0]);
+            let this = render_binding_arg(&x.args[0], generics);
             quote_spanned! {
                 span=>
                 #this
@@ -266,7 +350,7 @@ fn render_binding(x: &StarFun) -> TokenStream {
             }
         }
         StarFunSource::Argument(arg_count) => {
-            let bind_args = x.args.map(render_binding_arg);
+            let bind_args = x.args.map(|x| render_binding_arg(x, generics));
             quote_spanned! {
                 span=>
                 let __args: [_; #arg_count] = __signature.collect_into(__args, eval.heap())?;
@@ -274,7 +358,7 @@ fn render_binding(x: &StarFun) -> TokenStream {
             }
         }
         StarFunSource::Positional(required, optional) => {
-            let bind_args = x.args.map(render_binding_arg);
+            let bind_args = x.args.map(|x| render_binding_arg(x, generics));
             if optional == 0 {
                 quote_spanned! {
                     span=>
@@ -296,10 +380,13 @@ fn render_binding(x: &StarFun) -> TokenStream {
 }
 
 // Create a binding for an argument given. If it requires an index, take from the index
-fn render_binding_arg(arg: &StarArg) -> TokenStream {
+fn render_binding_arg(arg: &StarArg, generics: &Generics) -> TokenStream {
     let span = arg.span;
     let name = &arg.name;
-    let name_str = ident_string(name);
+    let name_str = match &arg.rename {
+        Some(x) => x.clone(),
+        None => ident_string(name),
+    };
     let ty = &arg.ty;
 
     let source = match arg.source {
@@ -311,7 +398,9 @@ fn render_binding_arg(arg: &StarArg) -> TokenStream {
     };
 
     // Rust doesn't have powerful enough nested if yet
-    let next = if arg.is_this() {
+    let next = if let Some(t) = arg.this_type_param(generics) {
+        quote_spanned! { span=> starlark::eval::Arguments::check_this_type::<#t>(#source)? }
+    } else if arg.is_this() {
         quote_spanned! { span=> starlark::eval::Arguments::check_this(#source)? }
     } else if arg.is_option() {
         assert!(
@@ -353,7 +442,7 @@ fn render_binding_arg(arg: &StarArg) -> TokenStream {
 fn render_signature(x: &StarFun) -> Option<TokenStream> {
     let span = x.args_span();
     if let StarFunSource::Argument(args_count) = x.source {
-        let name_str = ident_string(&x.name);
+        let name_str = x.name_str();
         let sig_args = x.args.map(render_signature_arg);
         Some(quote_spanned! {
             span=>
@@ -376,7 +465,7 @@ fn render_documentation(x: &StarFun) -> TokenStream {
         StarFunSource::Positional(required, optional) => Some(required + optional),
         StarFunSource::Unknown | StarFunSource::Parameters | StarFunSource::ThisParameters => None,
     };
-    let name_str = ident_string(&x.name);
+    let name_str = x.name_str();
     let documentation_signature = match args_count {
         Some(args_count) => {
             let sig_args = x.args.map(render_signature_arg);
@@ -432,8 +521,16 @@ fn render_signature_arg(arg: &StarArg) -> TokenStream {
     let span = arg.span;
 
     let mut name_str_full = (if arg.by_ref { "$" } else { "" }).to_owned();
-    name_str_full += &ident_string(&arg.name);
-    let name_str = name_str_full.trim_matches('_');
+    let name_str = match &arg.rename {
+        Some(rename) => {
+            name_str_full += rename;
+            &name_str_full
+        }
+        None => {
+            name_str_full += &ident_string(&arg.name);
+            name_str_full.trim_matches('_')
+        }
+    };
 
     if arg.is_args() {
         assert!(arg.default.is_none(), "Can't have *args with a default");
@@ -19,7 +19,8 @@ use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned};
 use syn::{
     parse::ParseStream, parse_macro_input, parse_quote, spanned::Spanned, Attribute, Data,
-    DataEnum, DataStruct, DeriveInput, Fields, GenericParam, Lifetime, LifetimeDef, TypeParamBound,
+    DataEnum, DataStruct, DeriveInput, Field, Fields, GenericParam, Lifetime, LifetimeDef,
+    TypeParamBound,
 };
 
 pub fn derive_trace(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -58,21 +59,56 @@ pub fn derive_trace(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     gen.into()
 }
 
-/// Parse attribute `#[trace(unsafe_ignore)]`.
+/// How the derived `trace` treats a field.
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum FieldMode {
+    /// Call `trace` on the field.
+    Trace,
+    /// `#[trace(skip)]`: the field is `'static`, so can't contain a `Value<'v>`,
+    /// and is checked to be so at compile time.
+    Skip,
+    /// `#[trace(unsafe_ignore)]`: the field is not traced, and nothing is checked.
+    UnsafeIgnore,
+}
+
+/// Parse attribute `#[trace(skip)]` or `#[trace(unsafe_ignore)]`.
 ///
-/// Currently it fails on any attribute argument other than `unsafe_ignore`.
+/// Currently it fails on any other attribute argument.
 #[cfg_attr(feature = "gazebo_lint", allow(gazebo_lint_impl_dupe))] // The custom_keyword macro
-fn is_ignore(attrs: &[Attribute]) -> bool {
+fn field_mode(attrs: &[Attribute]) -> FieldMode {
+    syn::custom_keyword!(skip);
     syn::custom_keyword!(unsafe_ignore);
 
-    attrs.iter().any(|a| {
-        a.path.is_ident("trace")
-            && a.parse_args_with(|input: ParseStream| {
-                let ignore = input.parse::<Option<unsafe_ignore>>()?.is_some();
-                Ok(ignore)
-            })
-            .unwrap()
-    })
+    for a in attrs {
+        if a.path.is_ident("trace") {
+            return a
+                .parse_args_with(|input: ParseStream| {
+                    if input.parse::<Option<skip>>()?.is_some() {
+                        Ok(FieldMode::Skip)
+                    } else {
+                        input.parse::<unsafe_ignore>()?;
+                        Ok(FieldMode::UnsafeIgnore)
+                    }
+                })
+                .unwrap();
+        }
+    }
+    FieldMode::Trace
+}
+
+fn trace_field(f: &Field, access: TokenStream) -> TokenStream {
+    match field_mode(&f.attrs) {
+        FieldMode::Trace => quote_spanned! {f.span() =>
+            starlark::values::Trace::trace(&mut #access, tracer);
+        },
+        FieldMode::Skip => quote_spanned! {f.span() =>
+            {
+                fn assert_static<T: 'static>(_: &T) {}
+                assert_static(&#access);
+            }
+        },
+        FieldMode::UnsafeIgnore => quote!(),
+    }
 }
 
 fn trace_struct(data: &DataStruct) -> TokenStream {
@@ -81,15 +117,9 @@ fn trace_struct(data: &DataStruct) -> TokenStream {
             let xs: Vec<_> = fields
                 .named
                 .iter()
-                .filter_map(|f| {
-                    if !is_ignore(&f.attrs) {
-                        let name = &f.ident;
-                        Some(quote_spanned! {f.span() =>
-                            starlark::values::Trace::trace(&mut self.#name, tracer);
-                        })
-                    } else {
-                        None
-                    }
+                .map(|f| {
+                    let name = &f.ident;
+                    trace_field(f, quote! { self.#name })
                 })
                 .collect();
             quote! {
@@ -101,13 +131,9 @@ fn trace_struct(data: &DataStruct) -> TokenStream {
                 .unnamed
                 .iter()
                 .enumerate()
-                .filter_map(|(i, f)| {
-                    if !is_ignore(&f.attrs) {
-                        let i = syn::Index::from(i);
-                        Some(quote_spanned! {f.span() => starlark::values::Trace::trace(&mut self.#i, tracer);})
-                    } else {
-                        None
-                    }
+                .map(|(i, f)| {
+                    let i = syn::Index::from(i);
+                    trace_field(f, quote! { self.#i })
                 })
                 .collect();
             quote! {
@@ -128,7 +154,7 @@ fn trace_enum(data: &DataEnum) -> TokenStream {
         match &variant.fields {
             Fields::Named(fields) => {
                 for field in &fields.named {
-                    if is_ignore(&field.attrs) {
+                    if field_mode(&field.attrs) != FieldMode::Trace {
                         continue;
                     }
                     // TODO: implement
@@ -137,7 +163,7 @@ fn trace_enum(data: &DataEnum) -> TokenStream {
             }
             Fields::Unnamed(fields) => {
                 for field in &fields.unnamed {
-                    if is_ignore(&field.attrs) {
+                    if field_mode(&field.attrs) != FieldMode::Trace {
                         continue;
                     }
                     // TODO: implement
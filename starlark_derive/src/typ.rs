@@ -16,7 +16,10 @@
  */
 
 use proc_macro2::{Ident, Span};
-use syn::{spanned::Spanned, Attribute, Block, Expr, NestedMeta, Pat, Type, Visibility};
+use syn::{
+    spanned::Spanned, Attribute, Block, Expr, Generics, NestedMeta, Pat, Type, TypePath,
+    TypeReference, Visibility,
+};
 
 use crate::{parse::ModuleKind, util::*};
 
@@ -28,6 +31,8 @@ pub(crate) struct StarModule {
     // make sense
     pub globals_builder: Type,
     pub name: Ident,
+    /// Type parameters of a methods module, so methods can be shared between types.
+    pub generics: Generics,
     pub docstring: Option<String>,
     pub stmts: Vec<StarStmt>,
 }
@@ -80,6 +85,8 @@ impl StarConst {
 #[derive(Debug)]
 pub(crate) struct StarFun {
     pub name: Ident,
+    /// The name in Starlark, from `#[starlark(rename = "...")]`.
+    pub rename: Option<String>,
     pub type_attribute: Option<NestedMeta>,
     pub attrs: Vec<Attribute>,
     pub args: Vec<StarArg>,
@@ -112,6 +119,14 @@ impl StarFun {
             .unwrap_or_else(|| self.name.span())
     }
 
+    /// The name the function is registered under.
+    pub(crate) fn name_str(&self) -> String {
+        match &self.rename {
+            Some(x) => x.clone(),
+            None => ident_string(&self.name),
+        }
+    }
+
     pub(crate) fn args_span(&self) -> Span {
         self.args
             .iter()
@@ -124,6 +139,8 @@ impl StarFun {
 #[derive(Debug)]
 pub(crate) struct StarAttr {
     pub name: Ident,
+    /// The name in Starlark, from `#[starlark(rename = "...")]`.
+    pub rename: Option<String>,
     pub arg: Type,
     pub attrs: Vec<Attribute>,
     /// `anyhow::Result<T>`.
@@ -142,6 +159,14 @@ impl StarAttr {
             .join(self.body.span())
             .unwrap_or_else(|| self.name.span())
     }
+
+    /// The name the attribute is registered under.
+    pub(crate) fn name_str(&self) -> String {
+        match &self.rename {
+            Some(x) => x.clone(),
+            None => ident_string(&self.name),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -151,6 +176,8 @@ pub(crate) struct StarArg {
     pub mutable: bool,
     pub by_ref: bool,
     pub name: Ident,
+    /// The name in Starlark, from `#[starlark(rename = "...")]`.
+    pub rename: Option<String>,
     pub ty: Type,
     pub default: Option<Pat>,
     pub source: StarArgSource,
@@ -274,6 +301,14 @@ impl StarArg {
         self.name == "this" || self.name == "_this"
     }
 
+    /// If this is `this: &T` where `T` is one of the module type parameters, return `T`.
+    pub fn this_type_param<'a>(&'a self, generics: &Generics) -> Option<&'a Ident> {
+        if !self.is_this() {
+            return None;
+        }
+        this_type_param(&self.ty, generics)
+    }
+
     pub fn is_args(&self) -> bool {
         self.name == "args"
     }
@@ -282,3 +317,19 @@ impl StarArg {
         self.name == "kwargs"
     }
 }
+
+/// If `ty` is `&T` where `T` is one of the type parameters in `generics`, return `T`.
+pub(crate) fn this_type_param<'a>(ty: &'a Type, generics: &Generics) -> Option<&'a Ident> {
+    if let Type::Reference(TypeReference {
+        mutability: None,
+        elem: box Type::Path(TypePath { qself: None, path }),
+        ..
+    }) = ty
+    {
+        let ident = path.get_ident()?;
+        if generics.type_params().any(|t| &t.ident == ident) {
+            return Some(ident);
+        }
+    }
+    None
+}